@@ -2968,6 +2968,7 @@ pub mod args {
     pub const NET_ADDRESS: Arg<SocketAddr> = arg("net-address");
     pub const NAMADA_START_TIME: ArgOpt<DateTimeUtc> = arg_opt("time");
     pub const NO_CONVERSIONS: ArgFlag = flag("no-conversions");
+    pub const NO_FEE_UNSHIELD: ArgFlag = flag("no-fee-unshield");
     pub const NUT: ArgFlag = flag("nut");
     pub const OUT_FILE_PATH_OPT: ArgOpt<PathBuf> = arg_opt("out-file-path");
     pub const OUTPUT: ArgOpt<PathBuf> = arg_opt("output");
@@ -5813,6 +5814,7 @@ pub mod args {
                 fee_unshield: self
                     .fee_unshield
                     .map(|ref fee_unshield| ctx.get_cached(fee_unshield)),
+                no_fee_unshield: self.no_fee_unshield,
                 gas_limit: self.gas_limit,
                 signing_keys: self
                     .signing_keys
@@ -5884,6 +5886,15 @@ pub mod args {
                 "The spending key to be used for fee unshielding. If none is \
                  provided, fee will be paid from the unshielded balance only.",
             ))
+            .arg(
+                NO_FEE_UNSHIELD
+                    .def()
+                    .help(
+                        "Never unshield funds to pay the fees, even if the \
+                         unshielded balance is too low.",
+                    )
+                    .conflicts_with(FEE_UNSHIELD_SPENDING_KEY.name),
+            )
             .arg(GAS_LIMIT.def().help(
                 "The multiplier of the gas limit resolution defining the \
                  maximum amount of gas needed to run transaction.",
@@ -5960,6 +5971,7 @@ pub mod args {
                 FEE_AMOUNT_OPT.parse(matches).map(InputAmount::Unvalidated);
             let fee_token = FEE_TOKEN.parse(matches);
             let fee_unshield = FEE_UNSHIELD_SPENDING_KEY.parse(matches);
+            let no_fee_unshield = NO_FEE_UNSHIELD.parse(matches);
             let _wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
             let gas_limit = GAS_LIMIT.parse(matches);
             let wallet_alias_force = WALLET_ALIAS_FORCE.parse(matches);
@@ -5985,6 +5997,7 @@ pub mod args {
                 fee_amount,
                 fee_token,
                 fee_unshield,
+                no_fee_unshield,
                 gas_limit,
                 expiration,
                 disposable_signing_key,
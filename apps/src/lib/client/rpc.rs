@@ -1250,12 +1250,7 @@ pub async fn query_account(context: &impl Namada, args: args::QueryAccount) {
         .await
         .unwrap();
     if let Some(account) = account {
-        display_line!(context.io(), "Address: {}", account.address);
-        display_line!(context.io(), "Threshold: {}", account.threshold);
-        display_line!(context.io(), "Public keys:");
-        for (public_key, _) in account.public_keys_map.pk_to_idx {
-            display_line!(context.io(), "- {}", public_key);
-        }
+        display_line!(context.io(), "{}", account);
     } else {
         display_line!(context.io(), "No account exists for {}", args.owner);
     }
@@ -10,7 +10,7 @@ use itertools::{Either, Itertools};
 use ledger_namada_rs::NamadaApp;
 use ledger_transport_hid::hidapi::HidApi;
 use ledger_transport_hid::TransportNativeHID;
-use namada::core::types::account::AccountPublicKeysMap;
+use namada::core::types::account::{AccountPublicKeysMap, MAX_ACCOUNT_KEYS};
 use namada::core::types::address::{Address, EstablishedAddress};
 use namada::core::types::chain::ChainId;
 use namada::core::types::string_encoding::StringEncoded;
@@ -78,6 +78,7 @@ fn get_tx_args(use_device: bool) -> TxArgs {
         wrapper_fee_payer: None,
         fee_token: nam(),
         fee_unshield: None,
+        no_fee_unshield: false,
         gas_limit: Default::default(),
         expiration: None,
         disposable_signing_key: false,
@@ -732,6 +733,7 @@ impl<T> Signed<T> {
             public_keys: pks.clone(),
             threshold,
             fee_payer: get_sentinel_pubkey(),
+            requires_pk_reveal: false,
         };
 
         let mut tx = self.data.tx_to_sign();
@@ -1253,7 +1255,7 @@ pub fn validate_established_account(
         );
         is_valid = false;
     }
-    if tx.public_keys.len() > u8::MAX as usize {
+    if tx.public_keys.len() > MAX_ACCOUNT_KEYS {
         eprintln!(
             "The number of configured public keys is way too fucking big"
         );
@@ -2053,6 +2053,37 @@ mod test_finalize_block {
         assert!(rp1 > rp2);
         assert!(rp2 > rp3);
         assert!(rp3 > rp4);
+
+        // The PoS inflation parameters of the new epoch are also kept in
+        // their history
+        let staked_ratio: Dec = shell
+            .wl_storage
+            .read(&parameters::storage::get_staked_ratio_key())
+            .unwrap()
+            .unwrap();
+        let inflation: token::Amount = shell
+            .wl_storage
+            .read(&parameters::storage::get_pos_inflation_amount_key())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            parameters::read_staked_ratio_at_epoch(&shell.wl_storage, Epoch(1))
+                .unwrap(),
+            Some(staked_ratio)
+        );
+        assert_eq!(
+            parameters::read_pos_inflation_amount_at_epoch(
+                &shell.wl_storage,
+                Epoch(1)
+            )
+            .unwrap(),
+            Some(inflation)
+        );
+        assert_eq!(
+            parameters::read_staked_ratio_at_epoch(&shell.wl_storage, Epoch(0))
+                .unwrap(),
+            None
+        );
     }
 
     /// A unit test for PoS inflationary rewards claiming and querying
@@ -50,7 +50,7 @@ use namada::ledger::storage::{
     DBIter, Sha256Hasher, Storage, StorageHasher, TempWlStorage, WlStorage, DB,
     EPOCH_SWITCH_BLOCKS_DELAY,
 };
-use namada::ledger::storage_api::tx::validate_tx_bytes;
+use namada::ledger::storage_api::tx::check_tx_bytes;
 use namada::ledger::storage_api::{self, StorageRead};
 use namada::ledger::{parameters, pos, protocol};
 use namada::proof_of_stake::slashing::{process_slashes, slash};
@@ -1064,11 +1064,11 @@ where
         //
         // NB: always keep this as the first tx check,
         // as it is a pretty cheap one
-        if !validate_tx_bytes(&self.wl_storage, tx_bytes.len())
+        if let Err(err) = check_tx_bytes(&self.wl_storage, tx_bytes.len())
             .expect("Failed to get max tx bytes param from storage")
         {
             response.code = ResultCode::TooLarge.into();
-            response.log = format!("{INVALID_MSG}: Tx too large");
+            response.log = format!("{INVALID_MSG}: {err}");
             return response;
         }
 
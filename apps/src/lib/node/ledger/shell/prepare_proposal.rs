@@ -389,9 +389,12 @@ mod test_prepare_proposal {
     };
     use namada::proof_of_stake::types::WeightedValidator;
     use namada::proof_of_stake::Epoch;
-    use namada::proto::{Code, Data, Header, Section, Signature, Signed};
+    use namada::proto::{
+        Code, Data, Header, Section, SignableEthMessage, Signature, Signed,
+    };
     use namada::types::address::{self, Address};
     use namada::types::ethereum_events::EthereumEvent;
+    use namada::types::keccak::keccak_hash;
     use namada::types::key::RefTo;
     use namada::types::storage::{BlockHeight, InnerEthEventsQueue};
     use namada::types::token;
@@ -400,12 +403,13 @@ mod test_prepare_proposal {
         ethereum_tx_data_variants, EthereumTxData,
     };
     use namada::types::transaction::{Fee, TxType, WrapperTx};
-    use namada::types::vote_extensions::ethereum_events;
+    use namada::types::vote_extensions::{bridge_pool_roots, ethereum_events};
 
     use super::*;
     use crate::config::ValidatorLocalConfig;
     use crate::node::ledger::shell::test_utils::{
-        self, gen_keypair, get_pkh_from_address, TestShell,
+        self, gen_keypair, gen_secp256k1_keypair, get_pkh_from_address,
+        TestShell,
     };
     use crate::node::ledger::shims::abcipp_shim_types::shim::request::FinalizeBlock;
     use crate::wallet;
@@ -572,6 +576,143 @@ mod test_prepare_proposal {
         check_eth_events_filtering(&shell, signed_vote_extension);
     }
 
+    /// Test that vote extensions from addresses which are not consensus
+    /// validators in the current epoch are filtered out when deserializing
+    /// them.
+    #[test]
+    fn test_deserialize_vote_extensions_filters_non_validators() {
+        const LAST_HEIGHT: BlockHeight = BlockHeight(2);
+
+        let (shell, _recv, _, _) = test_utils::setup_at_height(LAST_HEIGHT);
+
+        let bp_vext_tx = |validator_addr: Address| -> TxBytes {
+            let sig = Signed::<_, SignableEthMessage>::new(
+                &gen_secp256k1_keypair(),
+                keccak_hash([1, 2, 3]),
+            )
+            .sig;
+            let protocol_key =
+                shell.mode.get_protocol_key().expect("Test failed");
+            let ext = bridge_pool_roots::Vext {
+                block_height: LAST_HEIGHT,
+                validator_addr,
+                sig,
+            }
+            .sign(protocol_key);
+            EthereumTxData::BridgePoolVext(ext)
+                .sign(protocol_key, shell.chain_id.clone())
+                .to_bytes()
+                .into()
+        };
+        let validator_tx = bp_vext_tx(wallet::defaults::validator_address());
+        let non_validator_tx = bp_vext_tx(wallet::defaults::bertha_address());
+
+        let txs = [non_validator_tx, validator_tx.clone()];
+        let deserialized: Vec<_> =
+            shell.deserialize_vote_extensions(&txs).collect();
+        assert_eq!(deserialized, vec![validator_tx]);
+    }
+
+    /// Test that vote extensions are filtered against the consensus
+    /// validators of the epoch they were signed at, across an epoch
+    /// boundary where the validator leaves the set.
+    #[test]
+    fn test_deserialize_vote_extensions_at_epoch_boundary() {
+        use namada::tendermint::abci::types::VoteInfo;
+
+        let (mut shell, _recv, _, _) = test_utils::setup_at_height(3u64);
+
+        let bp_vext_tx = |shell: &TestShell, block_height| -> TxBytes {
+            let sig = Signed::<_, SignableEthMessage>::new(
+                &gen_secp256k1_keypair(),
+                keccak_hash([1, 2, 3]),
+            )
+            .sig;
+            let protocol_key =
+                shell.mode.get_protocol_key().expect("Test failed");
+            let ext = bridge_pool_roots::Vext {
+                block_height,
+                validator_addr: wallet::defaults::validator_address(),
+                sig,
+            }
+            .sign(protocol_key);
+            EthereumTxData::BridgePoolVext(ext)
+                .sign(protocol_key, shell.chain_id.clone())
+                .to_bytes()
+                .into()
+        };
+        let old_epoch_tx = bp_vext_tx(
+            &shell,
+            shell.wl_storage.storage.get_last_block_height(),
+        );
+
+        // remove all validators of the next epoch
+        assert_eq!(shell.wl_storage.storage.get_current_epoch().0.0, 0);
+        let validators_handle = consensus_validator_set_handle().at(&1.into());
+        let consensus_in_mem = validators_handle
+            .iter(&shell.wl_storage)
+            .expect("Test failed")
+            .map(|val| {
+                let (
+                    NestedSubKey::Data {
+                        key: stake,
+                        nested_sub_key: SubKey::Data(position),
+                    },
+                    ..,
+                ) = val.expect("Test failed");
+                (stake, position)
+            })
+            .collect::<Vec<_>>();
+        for (val_stake, val_position) in consensus_in_mem.into_iter() {
+            validators_handle
+                .at(&val_stake)
+                .remove(&mut shell.wl_storage, &val_position)
+                .expect("Test failed");
+        }
+
+        // we advance forward to the next epoch
+        let params = shell.wl_storage.pos_queries().get_pos_params();
+        let consensus_set: Vec<WeightedValidator> =
+            read_consensus_validator_set_addresses_with_stake(
+                &shell.wl_storage,
+                Epoch::default(),
+            )
+            .unwrap()
+            .into_iter()
+            .collect();
+        let val1 = consensus_set[0].clone();
+        let pkh1 = get_pkh_from_address(
+            &shell.wl_storage,
+            &params,
+            val1.address.clone(),
+            Epoch::default(),
+        );
+        let votes = vec![VoteInfo {
+            validator: crate::facade::tendermint::abci::types::Validator {
+                address: pkh1,
+                power: (u128::try_from(val1.bonded_stake).expect("Test failed") as u64).try_into().unwrap(),
+            },
+            sig_info: crate::facade::tendermint::abci::types::BlockSignatureInfo::LegacySigned,
+        }];
+        let req = FinalizeBlock {
+            proposer_address: pkh1.to_vec(),
+            votes,
+            ..Default::default()
+        };
+        assert_eq!(shell.start_new_epoch(Some(req)).0, 1);
+        let new_epoch_tx = bp_vext_tx(
+            &shell,
+            shell.wl_storage.storage.get_last_block_height(),
+        );
+
+        // the vext signed in the previous epoch is still proposed, whereas
+        // the one signed after the validator left the set is filtered out
+        let txs = [old_epoch_tx.clone(), new_epoch_tx];
+        let deserialized: Vec<_> =
+            shell.deserialize_vote_extensions(&txs).collect();
+        assert_eq!(deserialized, vec![old_epoch_tx]);
+    }
+
     /// Test if Ethereum events validation and inclusion in a block
     /// behaves as expected, considering <= 2/3 voting power.
     #[test]
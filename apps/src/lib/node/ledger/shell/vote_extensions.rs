@@ -4,8 +4,11 @@ pub mod bridge_pool_vext;
 pub mod eth_events;
 pub mod val_set_update;
 
+use namada::ledger::pos::PosQueries;
+use namada::proof_of_stake::storage::validator_protocol_key_handle;
 use namada::proto::{SignableEthMessage, Signed};
 use namada::types::keccak::keccak_hash;
+use namada::types::storage::Epoch;
 use namada::types::transaction::protocol::EthereumTxData;
 use namada::types::vote_extensions::{
     bridge_pool_roots, ethereum_events, validator_set_update, VoteExtension,
@@ -18,6 +21,20 @@ use crate::node::ledger::shims::abcipp_shim_types::shim::TxBytes;
 /// Message to be passed to `.expect()` calls in this module.
 const VALIDATOR_EXPECT_MSG: &str = "Only validators receive this method call.";
 
+/// Statistics on the vote extensions handled while compressing them, e.g.
+/// to be exported as metrics.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VextCompressionStats {
+    /// The number of vote extensions given to the compression
+    pub total: usize,
+    /// The number of valid vote extensions that were compressed
+    pub accepted: usize,
+    /// The number of invalid vote extensions that were filtered out
+    pub rejected: usize,
+    /// The number of validators that submitted conflicting signatures
+    pub double_signed: usize,
+}
+
 /// The error yielded from validating faulty vote extensions in the shell
 #[derive(Error, Debug)]
 pub enum VoteExtensionError {
@@ -63,6 +80,28 @@ pub enum VoteExtensionError {
     EthereumBridgeInactive,
 }
 
+/// A local signing key of a validator which doesn't match the key
+/// registered on chain for the current epoch
+#[derive(Error, Debug)]
+pub enum SigningKeyMismatch {
+    #[error(
+        "The local protocol key {local} doesn't match the one registered on \
+         chain: {registered:?}"
+    )]
+    ProtocolKey {
+        local: common::PublicKey,
+        registered: Option<common::PublicKey>,
+    },
+    #[error(
+        "The local Ethereum hot key {local} doesn't match the one registered \
+         on chain: {registered:?}"
+    )]
+    EthHotKey {
+        local: common::PublicKey,
+        registered: Option<common::PublicKey>,
+    },
+}
+
 impl<D, H> Shell<D, H>
 where
     D: DB + for<'iter> DBIter<'iter> + Sync + 'static,
@@ -73,6 +112,15 @@ where
     /// INVARIANT: This method must be stateless.
     #[inline]
     pub fn craft_extension(&mut self) -> VoteExtension {
+        if let Err(err) = self.validate_signing_keys() {
+            tracing::error!(
+                %err,
+                "The vote extensions of this validator are signed with keys \
+                 other than the ones registered on chain, and will fail to \
+                 be verified by other nodes. Please check the validator's \
+                 configured keys."
+            );
+        }
         VoteExtension {
             ethereum_events: self.extend_vote_with_ethereum_events(),
             bridge_pool_root: self.extend_vote_with_bp_roots(),
@@ -80,6 +128,43 @@ where
         }
     }
 
+    /// Check that the local protocol and Ethereum hot keys of this validator
+    /// match the keys registered on chain for the current epoch.
+    pub fn validate_signing_keys(&self) -> Result<(), SigningKeyMismatch> {
+        let validator_addr = self
+            .mode
+            .get_validator_address()
+            .expect(VALIDATOR_EXPECT_MSG);
+        let epoch = self.wl_storage.storage.get_current_epoch().0;
+        let pos_queries = self.wl_storage.pos_queries();
+
+        let local = self
+            .mode
+            .get_protocol_key()
+            .expect(VALIDATOR_EXPECT_MSG)
+            .ref_to();
+        let registered = validator_protocol_key_handle(validator_addr)
+            .get(&self.wl_storage, epoch, &pos_queries.get_pos_params())
+            .ok()
+            .flatten();
+        if registered.as_ref() != Some(&local) {
+            return Err(SigningKeyMismatch::ProtocolKey { local, registered });
+        }
+
+        let local = self
+            .mode
+            .get_eth_bridge_keypair()
+            .expect(VALIDATOR_EXPECT_MSG)
+            .ref_to();
+        let registered =
+            pos_queries.read_validator_eth_hot_key(validator_addr, Some(epoch));
+        if registered.as_ref() != Some(&local) {
+            return Err(SigningKeyMismatch::EthHotKey { local, registered });
+        }
+
+        Ok(())
+    }
+
     /// Extend PreCommit votes with [`ethereum_events::Vext`] instances.
     #[inline]
     pub fn extend_vote_with_ethereum_events(
@@ -208,6 +293,32 @@ where
             })
     }
 
+    /// Check if the given validator is in the consensus set of the
+    /// epoch a vote extension was signed at. At an epoch boundary, the
+    /// vote extensions of the previous epoch are thus checked against
+    /// the validator set they were signed with.
+    fn is_consensus_validator_at(
+        &self,
+        validator: &Address,
+        epoch: Option<Epoch>,
+    ) -> bool {
+        let is_validator = epoch.map_or(false, |epoch| {
+            self.wl_storage
+                .pos_queries()
+                .get_validator_from_address(validator, Some(epoch))
+                .is_ok()
+        });
+        if !is_validator {
+            tracing::debug!(
+                %validator,
+                ?epoch,
+                "Filtering out a vote extension from an address which is \
+                 not a consensus validator at the epoch it was signed at"
+            );
+        }
+        is_validator
+    }
+
     /// Given a slice of [`TxBytes`], return an iterator over the
     /// ones we could deserialize to vote extension protocol txs.
     pub fn deserialize_vote_extensions<'shell>(
@@ -226,7 +337,26 @@ where
                     return None;
                 }
             };
-            match (&tx).try_into().ok()? {
+            let tx_data: EthereumTxData = (&tx).try_into().ok()?;
+            let pos_queries = self.wl_storage.pos_queries();
+            let (validator, epoch) = match &tx_data {
+                EthereumTxData::BridgePoolVext(ext) => (
+                    &ext.data.validator_addr,
+                    pos_queries.get_epoch(ext.data.block_height),
+                ),
+                EthereumTxData::EthEventsVext(ext) => (
+                    &ext.data.validator_addr,
+                    pos_queries.get_epoch(ext.data.block_height),
+                ),
+                EthereumTxData::ValSetUpdateVext(ext) => {
+                    (&ext.data.validator_addr, Some(ext.data.signing_epoch))
+                }
+                _ => return None,
+            };
+            if !self.is_consensus_validator_at(validator, epoch) {
+                return None;
+            }
+            match tx_data {
                 EthereumTxData::BridgePoolVext(_) => Some(tx_bytes.clone()),
                 EthereumTxData::EthEventsVext(ext) => {
                     // NB: only propose events with at least
@@ -251,10 +381,11 @@ where
                     // to remove it from the mempool this way, but it
                     // will eventually be evicted, getting replaced
                     // by newer txs.
+                    let next_epoch = ext.data.signing_epoch.checked_add(1)?;
                     (!self
                         .wl_storage
                         .ethbridge_queries()
-                        .valset_upd_seen(ext.data.signing_epoch.next()))
+                        .valset_upd_seen(next_epoch))
                     .then(|| tx_bytes.clone())
                 }
                 _ => None,
@@ -281,3 +412,110 @@ pub fn iter_protocol_txs(
     .into_iter()
     .flatten()
 }
+
+#[cfg(test)]
+mod test_vote_extensions {
+    use assert_matches::assert_matches;
+    use namada::types::storage::BlockHeight;
+    use namada::types::vote_extensions::VextReassemblyError;
+
+    use super::*;
+    use crate::node::ledger::shell::test_utils::{self, gen_secp256k1_keypair};
+    use crate::wallet;
+
+    /// Test that a local Ethereum hot key which doesn't match the one
+    /// registered on chain is detected
+    #[test]
+    fn test_validate_signing_keys_hot_key_mismatch() {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+        assert!(shell.validate_signing_keys().is_ok());
+
+        let mismatched = gen_secp256k1_keypair();
+        if let ShellMode::Validator {
+            data:
+                ValidatorData {
+                    keys:
+                        ValidatorKeys {
+                            eth_bridge_keypair, ..
+                        },
+                    ..
+                },
+            ..
+        } = &mut shell.mode
+        {
+            *eth_bridge_keypair = mismatched.clone();
+        }
+
+        assert_matches!(
+            shell.validate_signing_keys(),
+            Err(SigningKeyMismatch::EthHotKey { local, registered })
+                if local == mismatched.ref_to() && registered.is_some()
+        );
+    }
+
+    /// Test that flattening a [`VoteExtension`] into its protocol txs, and
+    /// then reassembling it, yields back the original vote extension
+    #[test]
+    fn test_vote_extension_protocol_txs_round_trip() {
+        let (protocol_key, eth_bridge_key) = wallet::defaults::validator_keys();
+        let validator_addr = wallet::defaults::validator_address();
+        let block_height = BlockHeight(1);
+
+        let ext = VoteExtension {
+            ethereum_events: Some(
+                ethereum_events::Vext {
+                    validator_addr: validator_addr.clone(),
+                    block_height,
+                    ethereum_events: vec![],
+                }
+                .sign(&protocol_key),
+            ),
+            bridge_pool_root: Some(
+                bridge_pool_roots::Vext {
+                    block_height,
+                    validator_addr: validator_addr.clone(),
+                    sig: Signed::<_, SignableEthMessage>::new(
+                        &eth_bridge_key,
+                        keccak_hash([1, 2, 3]),
+                    )
+                    .sig,
+                }
+                .sign(&protocol_key),
+            ),
+            validator_set_update: Some(
+                validator_set_update::Vext {
+                    validator_addr,
+                    voting_powers: Default::default(),
+                    signing_epoch: 0.into(),
+                }
+                .sign(&eth_bridge_key),
+            ),
+        };
+
+        let protocol_txs: Vec<_> = iter_protocol_txs(ext.clone()).collect();
+        assert_eq!(protocol_txs.len(), 3);
+        assert_eq!(
+            VoteExtension::from_protocol_txs(protocol_txs.clone()),
+            Ok(ext.clone())
+        );
+
+        // a partial vote extension is reassembled as well
+        let partial = VoteExtension {
+            ethereum_events: None,
+            ..ext
+        };
+        assert_eq!(
+            VoteExtension::from_protocol_txs(iter_protocol_txs(
+                partial.clone()
+            )),
+            Ok(partial)
+        );
+
+        // duplicates are rejected
+        let duplicated = protocol_txs.iter().chain(&protocol_txs[..1]).cloned();
+        assert_matches!(
+            VoteExtension::from_protocol_txs(duplicated),
+            Err(VextReassemblyError::Duplicate(_))
+        );
+    }
+}
@@ -231,10 +231,22 @@ where
     /// When vote extensions are being used, this performs a check
     /// that at least 2/3 of the validators by voting power have
     /// included ethereum events in their vote extension.
+    #[inline]
     pub fn compress_ethereum_events(
         &self,
         vote_extensions: Vec<Signed<ethereum_events::Vext>>,
     ) -> Option<ethereum_events::VextDigest> {
+        self.compress_ethereum_events_and_report(vote_extensions)
+            .map(|(digest, _stats)| digest)
+    }
+
+    /// This method behaves exactly like [`Self::compress_ethereum_events`],
+    /// with the added bonus of reporting statistics on the compressed vote
+    /// extensions.
+    pub fn compress_ethereum_events_and_report(
+        &self,
+        vote_extensions: Vec<Signed<ethereum_events::Vext>>,
+    ) -> Option<(ethereum_events::VextDigest, VextCompressionStats)> {
         #[allow(clippy::question_mark)]
         if self.wl_storage.storage.last_block.is_none() {
             return None;
@@ -242,10 +254,16 @@ where
 
         let mut event_observers = BTreeMap::new();
         let mut signatures = HashMap::new();
+        let mut double_signers = BTreeSet::new();
+        let mut stats = VextCompressionStats {
+            total: vote_extensions.len(),
+            ..Default::default()
+        };
 
         for (_validator_voting_power, vote_extension) in
             self.filter_invalid_eth_events_vexts(vote_extensions)
         {
+            stats.accepted += 1;
             let validator_addr = vote_extension.data.validator_addr;
             let block_height = vote_extension.data.block_height;
 
@@ -276,15 +294,20 @@ where
                      constructing ethereum_events::VextDigest - maybe private \
                      key of validator is being used by multiple nodes?"
                 );
+                if existing_sig != sig {
+                    double_signers.insert(validator_addr);
+                }
             }
         }
+        stats.rejected = stats.total - stats.accepted;
+        stats.double_signed = double_signers.len();
 
         let events: Vec<MultiSignedEthEvent> = event_observers
             .into_iter()
             .map(|(event, signers)| MultiSignedEthEvent { event, signers })
             .collect();
 
-        Some(ethereum_events::VextDigest { events, signatures })
+        Some((ethereum_events::VextDigest { events, signatures }, stats))
     }
 }
 
@@ -315,6 +338,7 @@ mod test_vote_extensions {
     use namada::types::vote_extensions::ethereum_events;
 
     use crate::node::ledger::shell::test_utils::*;
+    use crate::node::ledger::shell::vote_extensions::VextCompressionStats;
     use crate::node::ledger::shims::abcipp_shim_types::shim::request::FinalizeBlock;
 
     /// Test validating Ethereum events.
@@ -702,4 +726,58 @@ mod test_vote_extensions {
             shell.wl_storage.storage.get_last_block_height()
         ))
     }
+
+    /// Test that the statistics of the compression of a mix of valid and
+    /// invalid [`ethereum_events::Vext`] instances are reported, including
+    /// the validators that signed conflicting vote extensions
+    #[test]
+    fn test_compress_ethereum_events_stats() {
+        let (shell, _, _, _) = setup_at_height(3u64);
+        let address = shell.mode.get_validator_address().unwrap().clone();
+        let protocol_key =
+            shell.mode.get_protocol_key().expect("Test failed").clone();
+        let block_height = shell.wl_storage.storage.get_last_block_height();
+        let make_vext = || ethereum_events::Vext {
+            ethereum_events: vec![EthereumEvent::TransfersToEthereum {
+                nonce: 0.into(),
+                transfers: vec![TransferToEthereum {
+                    amount: 100.into(),
+                    asset: EthAddress([1; 20]),
+                    receiver: EthAddress([2; 20]),
+                    checksum: Hash::default(),
+                }],
+                relayer: gen_established_address(),
+            }],
+            block_height,
+            validator_addr: address.clone(),
+        };
+
+        let valid_vext = make_vext().sign(&protocol_key);
+        // the same validator signs a different vote extension at the same
+        // block height
+        let conflicting_vext = make_vext().sign(&protocol_key);
+        // a vote extension signed with a key other than the validator's
+        let invalid_vext = make_vext().sign(&gen_keypair());
+
+        let (digest, stats) = shell
+            .compress_ethereum_events_and_report(vec![
+                valid_vext.clone(),
+                valid_vext,
+                invalid_vext,
+                conflicting_vext,
+            ])
+            .expect("Test failed");
+
+        assert_eq!(
+            stats,
+            VextCompressionStats {
+                total: 4,
+                accepted: 3,
+                rejected: 1,
+                double_signed: 1,
+            }
+        );
+        assert_eq!(digest.events.len(), 2);
+        assert_eq!(digest.signatures.len(), 1);
+    }
 }
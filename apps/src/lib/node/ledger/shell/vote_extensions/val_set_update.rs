@@ -1,11 +1,12 @@
 //! Extend Tendermint votes with validator set updates, to be relayed to
 //! Namada's Ethereum bridge smart contracts.
 
-use std::collections::HashMap;
+use std::collections::{btree_map, BTreeMap, HashMap};
 
 use namada::ledger::pos::PosQueries;
 use namada::ledger::storage::traits::StorageHasher;
 use namada::ledger::storage::{DBIter, DB};
+use namada::types::address::Address;
 use namada::types::storage::Epoch;
 use namada::types::token;
 use namada::types::vote_extensions::validator_set_update;
@@ -77,14 +78,22 @@ where
             );
             return Err(VoteExtensionError::UnexpectedEpoch);
         }
+        let Some(next_epoch) = signing_epoch.checked_add(1) else {
+            tracing::debug!(
+                vext_epoch = ?signing_epoch,
+                "Validator set update vote extension issued for the last \
+                 representable epoch.",
+            );
+            return Err(VoteExtensionError::UnexpectedEpoch);
+        };
         if self
             .wl_storage
             .ethbridge_queries()
-            .valset_upd_seen(signing_epoch.next())
+            .valset_upd_seen(next_epoch)
         {
             let err = VoteExtensionError::ValsetUpdProofAvailable;
             tracing::debug!(
-                proof_epoch = ?signing_epoch.next(),
+                proof_epoch = ?next_epoch,
                 "{err}"
             );
             return Err(err);
@@ -94,7 +103,7 @@ where
         for (eth_addr_book, namada_addr, namada_power) in self
             .wl_storage
             .ethbridge_queries()
-            .get_consensus_eth_addresses(Some(signing_epoch.next()))
+            .get_consensus_eth_addresses(Some(next_epoch))
             .iter()
         {
             let &ext_power = match ext.data.voting_powers.get(&eth_addr_book) {
@@ -179,6 +188,42 @@ where
         })
     }
 
+    /// Takes a list of signed validator set update vote extensions,
+    /// and splits it into the valid instances, alongside the voting
+    /// power of their signers, and the addresses of the validators
+    /// whose vote extensions were rejected, together with the reason
+    /// why they were rejected.
+    pub fn partition_valset_upd_vexts(
+        &self,
+        vote_extensions: impl IntoIterator<Item = validator_set_update::SignedVext>,
+    ) -> (
+        Vec<(token::Amount, validator_set_update::SignedVext)>,
+        Vec<(Address, VoteExtensionError)>,
+    ) {
+        let last_epoch = self.wl_storage.storage.get_current_epoch().0;
+        let mut valid = vec![];
+        let mut rejected = vec![];
+
+        for vote_extension in vote_extensions {
+            let validator_addr = vote_extension.data.validator_addr.clone();
+            match self.validate_valset_upd_vext_and_get_it_back(
+                vote_extension,
+                last_epoch,
+            ) {
+                Ok(ext) => valid.push(ext),
+                Err(err) => {
+                    tracing::debug!(
+                        %validator_addr,
+                        "Rejected validator set update vote extension: {err}"
+                    );
+                    rejected.push((validator_addr, err));
+                }
+            }
+        }
+
+        (valid, rejected)
+    }
+
     /// Takes a list of signed validator set update vote extensions,
     /// and filters out invalid instances.
     #[inline]
@@ -188,38 +233,120 @@ where
         + 'static,
     ) -> impl Iterator<Item = (token::Amount, validator_set_update::SignedVext)> + '_
     {
-        self.validate_valset_upd_vext_list(vote_extensions)
-            .filter_map(|ext| ext.ok())
+        let (valid, _rejected) =
+            self.partition_valset_upd_vexts(vote_extensions);
+        valid.into_iter()
     }
 
-    /// Compresses a set of signed validator set update vote extensions into a
-    /// single [`validator_set_update::VextDigest`], whilst filtering
-    /// invalid [`validator_set_update::SignedVext`] instances in the
-    /// process.
+    /// Compresses a set of signed validator set update vote extensions into
+    /// one [`validator_set_update::VextDigest`] per signing epoch, whilst
+    /// filtering invalid [`validator_set_update::SignedVext`] instances in
+    /// the process.
+    ///
+    /// Vote extensions signed over different epochs attest to different
+    /// validator sets, therefore they are never merged into the same
+    /// digest.
+    #[inline]
     pub fn compress_valset_updates(
         &self,
         vote_extensions: Vec<validator_set_update::SignedVext>,
-    ) -> Option<validator_set_update::VextDigest> {
+    ) -> Option<BTreeMap<Epoch, validator_set_update::VextDigest>> {
+        self.compress_valset_updates_and_report(vote_extensions)
+            .map(|compressed| compressed.digests)
+    }
+
+    /// This method behaves exactly like [`Self::compress_valset_updates`],
+    /// with the added bonus of reporting the validators that submitted
+    /// conflicting signatures for the same signing epoch, as well as
+    /// statistics on the compressed vote extensions.
+    pub fn compress_valset_updates_and_report(
+        &self,
+        vote_extensions: Vec<validator_set_update::SignedVext>,
+    ) -> Option<CompressedValsetUpdates> {
         #[allow(clippy::question_mark)]
         if self.wl_storage.storage.last_block.is_none() {
             return None;
         }
 
-        let mut voting_powers = None;
-        let mut signatures = HashMap::new();
+        let total = vote_extensions.len();
+        let (valid, rejected) =
+            self.partition_valset_upd_vexts(vote_extensions);
+        let valid_vexts = valid
+            .into_iter()
+            .map(|(_validator_voting_power, vote_extension)| vote_extension);
 
-        for (_validator_voting_power, mut vote_extension) in
-            self.filter_invalid_valset_upd_vexts(vote_extensions)
-        {
-            if voting_powers.is_none() {
-                voting_powers = Some(std::mem::take(
-                    &mut vote_extension.data.voting_powers,
-                ));
-            }
+        let mut compressed =
+            CompressedValsetUpdates::from_valid_vexts(valid_vexts);
+        let rejected =
+            rejected.len() + compressed.divergent_voting_powers.len();
+        compressed.stats = VextCompressionStats {
+            total,
+            accepted: total - rejected,
+            rejected,
+            double_signed: compressed.double_signers.len(),
+        };
+        Some(compressed)
+    }
+}
 
+/// Validator set update vote extensions compressed into one
+/// [`validator_set_update::VextDigest`] per signing epoch.
+#[derive(Debug, Default)]
+pub struct CompressedValsetUpdates {
+    /// The compressed vote extensions, keyed by their signing epoch.
+    pub digests: BTreeMap<Epoch, validator_set_update::VextDigest>,
+    /// The validators that submitted conflicting signatures for the same
+    /// signing epoch.
+    pub double_signers: Vec<Address>,
+    /// The validators whose vote extensions were rejected, for attesting
+    /// to voting powers other than the ones of the first vote extension
+    /// compressed for the same signing epoch.
+    pub divergent_voting_powers: Vec<Address>,
+    /// Statistics on the compressed vote extensions.
+    pub stats: VextCompressionStats,
+}
+
+impl CompressedValsetUpdates {
+    /// Compress a set of validator set update vote extensions, which are
+    /// assumed to have been validated already.
+    fn from_valid_vexts(
+        vote_extensions: impl IntoIterator<Item = validator_set_update::SignedVext>,
+    ) -> Self {
+        let mut compressed = Self::default();
+
+        for mut vote_extension in vote_extensions {
             let validator_addr = vote_extension.data.validator_addr;
             let signing_epoch = vote_extension.data.signing_epoch;
 
+            let digest = match compressed.digests.entry(signing_epoch) {
+                btree_map::Entry::Vacant(entry) => {
+                    entry.insert(validator_set_update::VextDigest {
+                        signatures: HashMap::new(),
+                        voting_powers: std::mem::take(
+                            &mut vote_extension.data.voting_powers,
+                        ),
+                    })
+                }
+                btree_map::Entry::Occupied(entry) => {
+                    // all the vote extensions signed over the same epoch
+                    // must attest to the same voting powers
+                    if entry.get().voting_powers
+                        != vote_extension.data.voting_powers
+                    {
+                        tracing::error!(
+                            %validator_addr,
+                            ?signing_epoch,
+                            "Rejected validator set update vote extension \
+                             whose voting powers diverge from the ones of \
+                             other vote extensions of the same epoch"
+                        );
+                        compressed.divergent_voting_powers.push(validator_addr);
+                        continue;
+                    }
+                    entry.into_mut()
+                }
+            };
+
             // register the signature of `validator_addr`
             let addr = validator_addr.clone();
             let sig = vote_extension.sig.clone();
@@ -230,7 +357,7 @@ where
                 %validator_addr,
                 "Inserting signature into validator_set_update::VextDigest"
             );
-            if let Some(existing_sig) = signatures.insert(addr, sig) {
+            if let Some(existing_sig) = digest.signatures.insert(addr, sig) {
                 tracing::warn!(
                     sig = ?vote_extension.sig,
                     ?existing_sig,
@@ -240,20 +367,21 @@ where
                      constructing validator_set_update::VextDigest - maybe \
                      private key of validator is being used by multiple nodes?"
                 );
+                if existing_sig != vote_extension.sig
+                    && !compressed.double_signers.contains(&validator_addr)
+                {
+                    compressed.double_signers.push(validator_addr);
+                }
             }
         }
 
-        let voting_powers = voting_powers.unwrap_or_default();
-
-        Some(validator_set_update::VextDigest {
-            signatures,
-            voting_powers,
-        })
+        compressed
     }
 }
 
 #[cfg(test)]
 mod test_vote_extensions {
+    use assert_matches::assert_matches;
     use namada::core::ledger::storage_api::collections::lazy_map::{
         NestedSubKey, SubKey,
     };
@@ -269,7 +397,13 @@ mod test_vote_extensions {
     use namada::types::vote_extensions::validator_set_update;
     use namada_sdk::eth_bridge::EthBridgeQueries;
 
-    use crate::node::ledger::shell::test_utils::{self, get_pkh_from_address};
+    use super::CompressedValsetUpdates;
+    use crate::node::ledger::shell::test_utils::{
+        self, get_pkh_from_address, TestShell,
+    };
+    use crate::node::ledger::shell::vote_extensions::{
+        VextCompressionStats, VoteExtensionError,
+    };
     use crate::node::ledger::shims::abcipp_shim_types::shim::request::FinalizeBlock;
     use crate::wallet;
 
@@ -520,4 +654,191 @@ mod test_vote_extensions {
         // TODO: we need to prove ownership of validator keys
         // https://github.com/anoma/namada/issues/106
     }
+
+    /// Sign a valid [`validator_set_update::Vext`] over `signing_epoch`,
+    /// with the keys of the validator running the test shell.
+    fn sign_valset_upd_vext(
+        shell: &TestShell,
+        signing_epoch: Epoch,
+    ) -> validator_set_update::SignedVext {
+        let validator_addr =
+            shell.mode.get_validator_address().unwrap().clone();
+        let eth_bridge_key =
+            shell.mode.get_eth_bridge_keypair().expect("Test failed");
+        let voting_powers = shell
+            .wl_storage
+            .ethbridge_queries()
+            .get_consensus_eth_addresses(Some(signing_epoch.next()))
+            .iter()
+            .map(|(eth_addr_book, _, voting_power)| {
+                (eth_addr_book, voting_power)
+            })
+            .collect();
+        validator_set_update::Vext {
+            voting_powers,
+            signing_epoch,
+            validator_addr,
+        }
+        .sign(eth_bridge_key)
+    }
+
+    /// Test that [`validator_set_update::Vext`] instances signed over
+    /// different epochs are not merged into the same digest
+    #[test]
+    fn test_compress_valset_updates_groups_by_epoch() {
+        let (mut shell, _recv, _, _) = test_utils::setup();
+
+        let first_epoch = shell.wl_storage.storage.get_current_epoch().0;
+        let first_vext = sign_valset_upd_vext(&shell, first_epoch);
+
+        let second_epoch = shell.start_new_epoch(None);
+        assert_eq!(second_epoch, first_epoch.next());
+        let second_vext = sign_valset_upd_vext(&shell, second_epoch);
+
+        let digests = shell
+            .compress_valset_updates(vec![
+                first_vext.clone(),
+                second_vext.clone(),
+            ])
+            .expect("Test failed");
+
+        assert_eq!(digests.len(), 2);
+        assert_eq!(
+            digests[&first_epoch],
+            validator_set_update::VextDigest::singleton(first_vext)
+        );
+        assert_eq!(
+            digests[&second_epoch],
+            validator_set_update::VextDigest::singleton(second_vext)
+        );
+    }
+
+    /// Test that a [`validator_set_update::Vext`] with an incorrect
+    /// signature is reported in the rejected bucket, alongside the
+    /// address of its signer
+    #[test]
+    fn test_partition_valset_upd_vexts() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let validator_addr =
+            shell.mode.get_validator_address().unwrap().clone();
+        let signing_epoch = shell.wl_storage.storage.get_current_epoch().0;
+
+        let valid_vext = sign_valset_upd_vext(&shell, signing_epoch);
+        let mut invalid_vext = valid_vext.clone();
+        invalid_vext.sig = test_utils::invalidate_signature(invalid_vext.sig);
+
+        let (valid, rejected) = shell
+            .partition_valset_upd_vexts(vec![valid_vext.clone(), invalid_vext]);
+
+        assert_eq!(valid.len(), 1);
+        assert_eq!(valid[0].1, valid_vext);
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].0, validator_addr);
+        assert_matches!(rejected[0].1, VoteExtensionError::VerifySigFailed);
+    }
+
+    /// Test that a validator which submitted two different signatures
+    /// over the same epoch is reported as a double signer, and that the
+    /// digest output is preserved
+    #[test]
+    fn test_compress_valset_updates_reports_double_signing() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let validator_addr =
+            shell.mode.get_validator_address().unwrap().clone();
+        let signing_epoch = shell.wl_storage.storage.get_current_epoch().0;
+
+        let first_vext = sign_valset_upd_vext(&shell, signing_epoch);
+        let mut second_vext = first_vext.clone();
+        second_vext.sig = test_utils::invalidate_signature(second_vext.sig);
+
+        let compressed = CompressedValsetUpdates::from_valid_vexts([
+            first_vext,
+            second_vext.clone(),
+        ]);
+
+        assert_eq!(compressed.double_signers, vec![validator_addr]);
+        assert_eq!(compressed.digests.len(), 1);
+        assert_eq!(
+            compressed.digests[&signing_epoch],
+            validator_set_update::VextDigest::singleton(second_vext)
+        );
+    }
+
+    /// Test that submitting the same signature twice is not reported as
+    /// double signing
+    #[test]
+    fn test_compress_valset_updates_duplicate_vext_not_double_signing() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let signing_epoch = shell.wl_storage.storage.get_current_epoch().0;
+        let vext = sign_valset_upd_vext(&shell, signing_epoch);
+
+        let compressed = shell
+            .compress_valset_updates_and_report(vec![vext.clone(), vext])
+            .expect("Test failed");
+
+        assert!(compressed.double_signers.is_empty());
+        assert_eq!(compressed.digests.len(), 1);
+    }
+
+    /// Test that a [`validator_set_update::Vext`] attesting to different
+    /// voting powers than the first one compressed for the same epoch is
+    /// rejected
+    #[test]
+    fn test_compress_valset_updates_divergent_voting_powers() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let validator_addr =
+            shell.mode.get_validator_address().unwrap().clone();
+        let signing_epoch = shell.wl_storage.storage.get_current_epoch().0;
+
+        let first_vext = sign_valset_upd_vext(&shell, signing_epoch);
+        let mut divergent = first_vext.data.clone();
+        assert!(!divergent.voting_powers.is_empty());
+        divergent.voting_powers.clear();
+        let second_vext = divergent
+            .sign(shell.mode.get_eth_bridge_keypair().expect("Test failed"));
+
+        let compressed = CompressedValsetUpdates::from_valid_vexts([
+            first_vext.clone(),
+            second_vext,
+        ]);
+
+        assert_eq!(compressed.divergent_voting_powers, vec![validator_addr]);
+        assert!(compressed.double_signers.is_empty());
+        assert_eq!(
+            compressed.digests[&signing_epoch],
+            validator_set_update::VextDigest::singleton(first_vext)
+        );
+    }
+
+    /// Test that the statistics of the compression of a mix of valid and
+    /// invalid [`validator_set_update::Vext`] instances are reported
+    #[test]
+    fn test_compress_valset_updates_stats() {
+        let (shell, _recv, _, _) = test_utils::setup();
+        let signing_epoch = shell.wl_storage.storage.get_current_epoch().0;
+
+        let valid_vext = sign_valset_upd_vext(&shell, signing_epoch);
+        let mut invalid_vext = valid_vext.clone();
+        invalid_vext.sig = test_utils::invalidate_signature(invalid_vext.sig);
+
+        let compressed = shell
+            .compress_valset_updates_and_report(vec![
+                valid_vext.clone(),
+                invalid_vext.clone(),
+                valid_vext,
+                invalid_vext,
+            ])
+            .expect("Test failed");
+
+        assert_eq!(
+            compressed.stats,
+            VextCompressionStats {
+                total: 4,
+                accepted: 2,
+                rejected: 2,
+                double_signed: 0,
+            }
+        );
+        assert_eq!(compressed.digests.len(), 1);
+    }
 }
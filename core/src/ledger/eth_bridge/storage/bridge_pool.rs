@@ -8,12 +8,14 @@ use ethabi::Token;
 use eyre::eyre;
 use namada_macros::StorageKeys;
 
+use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
 use crate::types::address::{Address, InternalAddress};
 use crate::types::eth_abi::Encode;
 use crate::types::eth_bridge_pool::PendingTransfer;
 use crate::types::hash::Hash;
 use crate::types::keccak::{keccak_hash, KeccakHash};
 use crate::types::storage::{BlockHeight, DbKeySeg, Key, KeySeg};
+use crate::types::token::Amount;
 
 /// Prefix to be used in Bridge pool tree root computations.
 /// This value corresponds to leaf nodes.
@@ -94,6 +96,53 @@ pub fn is_pending_transfer_key(key: &Key) -> bool {
     !Segments::ALL.iter().any(|s| s == &segment)
 }
 
+/// Read all the transfers pending in the Bridge pool, ordered by their
+/// storage keys.
+pub fn read_bridge_pool<S: StorageRead>(
+    storage: &S,
+) -> storage_api::Result<Vec<PendingTransfer>> {
+    let prefix = BRIDGE_POOL_ADDRESS.to_db_key().into();
+    let mut transfers: Vec<(Key, PendingTransfer)> =
+        storage_api::iter_prefix_with_filter(
+            storage,
+            &prefix,
+            is_pending_transfer_key,
+        )?
+        .collect::<storage_api::Result<_>>()?;
+    transfers.sort_by(|(key_a, _), (key_b, _)| key_a.cmp(key_b));
+    Ok(transfers
+        .into_iter()
+        .map(|(_, transfer)| transfer)
+        .collect())
+}
+
+/// Evict the transfers pending in the Bridge pool whose gas fees fall below
+/// the minimum of their gas token, returning the evicted transfers in the
+/// order of their keys. Transfers paying gas in tokens without a minimum
+/// are kept.
+///
+/// NB: the escrowed funds of the evicted transfers are not refunded.
+pub fn evict_stale_transfers<S>(
+    storage: &mut S,
+    min_gas_fee: &BTreeMap<Address, Amount>,
+) -> storage_api::Result<Vec<PendingTransfer>>
+where
+    S: StorageRead + StorageWrite,
+{
+    let stale: Vec<_> = read_bridge_pool(storage)?
+        .into_iter()
+        .filter(|transfer| {
+            min_gas_fee
+                .get(&transfer.gas_fee.token)
+                .map_or(false, |min| transfer.gas_fee.amount < *min)
+        })
+        .collect();
+    for transfer in &stale {
+        storage.delete(&get_pending_key(transfer))?;
+    }
+    Ok(stale)
+}
+
 /// A simple Merkle tree for the Ethereum bridge pool
 ///
 /// Note that an empty tree has root [0u8; 20] by definition.
@@ -445,11 +494,12 @@ mod test_bridge_pool_tree {
     use proptest::prelude::*;
 
     use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
     use crate::types::address::nam;
     use crate::types::eth_bridge_pool::{
         GasFee, TransferToEthereum, TransferToEthereumKind,
     };
-    use crate::types::ethereum_events::EthAddress;
+    use crate::types::ethereum_events::{EthAddress, Uint};
 
     /// An established user address for testing & development
     fn bertha_address() -> Address {
@@ -457,6 +507,89 @@ mod test_bridge_pool_tree {
             .expect("The token address decoding shouldn't fail")
     }
 
+    /// Test reading back the transfers pending in the Bridge pool, in the
+    /// order of their keys.
+    #[test]
+    fn test_read_bridge_pool() {
+        let mut wl_storage = TestWlStorage::default();
+        let mut transfers = vec![];
+        for i in 0..3 {
+            let transfer = PendingTransfer {
+                transfer: TransferToEthereum {
+                    kind: TransferToEthereumKind::Erc20,
+                    asset: EthAddress([i; 20]),
+                    sender: bertha_address(),
+                    recipient: EthAddress([i + 1; 20]),
+                    amount: (i as u64).into(),
+                },
+                gas_fee: GasFee {
+                    token: nam(),
+                    amount: 0.into(),
+                    payer: bertha_address(),
+                },
+            };
+            wl_storage
+                .write(&get_pending_key(&transfer), &transfer)
+                .expect("Test failed");
+            transfers.push(transfer);
+        }
+        // the other keys of the Bridge pool must be skipped
+        wl_storage
+            .write(&get_nonce_key(), Uint::from(1u64))
+            .expect("Test failed");
+        wl_storage
+            .write_bytes(&get_signed_root_key(), [0u8; 8])
+            .expect("Test failed");
+
+        transfers.sort_by_key(get_pending_key);
+        assert_eq!(
+            read_bridge_pool(&wl_storage).expect("Test failed"),
+            transfers
+        );
+    }
+
+    /// Test that transfers whose gas fees fall below the minimum of their
+    /// gas token are evicted from the Bridge pool, while others stay.
+    #[test]
+    fn test_evict_stale_transfers() {
+        let mut wl_storage = TestWlStorage::default();
+        let pending = |gas_token: Address, gas_amount: u64| PendingTransfer {
+            transfer: TransferToEthereum {
+                kind: TransferToEthereumKind::Erc20,
+                asset: EthAddress([0; 20]),
+                sender: bertha_address(),
+                recipient: EthAddress([1; 20]),
+                amount: gas_amount.into(),
+            },
+            gas_fee: GasFee {
+                token: gas_token,
+                amount: gas_amount.into(),
+                payer: bertha_address(),
+            },
+        };
+        let stale = pending(nam(), 5);
+        let adequate = pending(nam(), 10);
+        let no_minimum = pending(bertha_address(), 0);
+        for transfer in [&stale, &adequate, &no_minimum] {
+            wl_storage
+                .write(&get_pending_key(transfer), transfer)
+                .expect("Test failed");
+        }
+
+        let min_gas_fee = BTreeMap::from([(nam(), Amount::from(10))]);
+        let evicted = evict_stale_transfers(&mut wl_storage, &min_gas_fee)
+            .expect("Test failed");
+        assert_eq!(evicted, vec![stale.clone()]);
+
+        let mut remaining = vec![adequate, no_minimum];
+        remaining.sort_by_key(get_pending_key);
+        assert_eq!(
+            read_bridge_pool(&wl_storage).expect("Test failed"),
+            remaining
+        );
+        assert!(!stale.is_in_pool(&wl_storage).expect("Test failed"));
+    }
+
     /// Test that if tree has a single leaf, its root is the hash
     /// of that leaf
     #[test]
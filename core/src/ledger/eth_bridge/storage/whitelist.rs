@@ -7,10 +7,11 @@ use std::str::FromStr;
 
 use super::super::ADDRESS as BRIDGE_ADDRESS;
 use super::{prefix as ethbridge_key_prefix, wrapped_erc20s};
+use crate::ledger::storage_api::{self, StorageRead};
 use crate::types::ethereum_events::EthAddress;
 use crate::types::storage;
 use crate::types::storage::DbKeySeg;
-use crate::types::token::{denom_key, minted_balance_key};
+use crate::types::token::{denom_key, minted_balance_key, Amount};
 
 mod segments {
     //! Storage key segments under the token whitelist.
@@ -116,10 +117,53 @@ pub fn is_cap_or_whitelisted_key(key: &storage::Key) -> bool {
     }
 }
 
+/// The whitelist status of an ERC20 asset, such as wNAM.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WnamStatus {
+    /// Whether the asset is whitelisted or not.
+    pub whitelisted: bool,
+    /// The token cap of the asset.
+    pub cap: Amount,
+}
+
+/// Read the whitelist status of the given ERC20 asset. An asset missing from
+/// the whitelist is not whitelisted and has a zero cap.
+pub fn read_wnam_whitelist_status<S>(
+    storage: &S,
+    asset: EthAddress,
+) -> storage_api::Result<WnamStatus>
+where
+    S: StorageRead,
+{
+    let whitelisted = storage
+        .read(
+            &Key {
+                asset,
+                suffix: KeyType::Whitelisted,
+            }
+            .into(),
+        )?
+        .unwrap_or(false);
+    let cap = storage
+        .read(
+            &Key {
+                asset,
+                suffix: KeyType::Cap,
+            }
+            .into(),
+        )?
+        .unwrap_or_default();
+    Ok(WnamStatus { whitelisted, cap })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::ethereum_events::testing::DAI_ERC20_ETH_ADDRESS;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::ledger::storage_api::StorageWrite;
+    use crate::types::ethereum_events::testing::{
+        DAI_ERC20_ETH_ADDRESS, USDC_ERC20_ETH_ADDRESS,
+    };
 
     /// Test that storage key serialization yields the expected value.
     #[test]
@@ -163,4 +207,54 @@ mod tests {
         };
         assert!(!is_cap_or_whitelisted_key(&unexpected_key));
     }
+
+    /// Test reading the whitelist status of a whitelisted and of an absent
+    /// asset.
+    #[test]
+    fn test_read_wnam_whitelist_status() {
+        let mut storage = TestWlStorage::default();
+        let cap = Amount::from_u64(1_000);
+        storage
+            .write(
+                &Key {
+                    asset: DAI_ERC20_ETH_ADDRESS,
+                    suffix: KeyType::Whitelisted,
+                }
+                .into(),
+                true,
+            )
+            .unwrap();
+        storage
+            .write(
+                &Key {
+                    asset: DAI_ERC20_ETH_ADDRESS,
+                    suffix: KeyType::Cap,
+                }
+                .into(),
+                cap,
+            )
+            .unwrap();
+
+        let status =
+            read_wnam_whitelist_status(&storage, DAI_ERC20_ETH_ADDRESS)
+                .unwrap();
+        assert_eq!(
+            status,
+            WnamStatus {
+                whitelisted: true,
+                cap
+            }
+        );
+
+        let status =
+            read_wnam_whitelist_status(&storage, USDC_ERC20_ETH_ADDRESS)
+                .unwrap();
+        assert_eq!(
+            status,
+            WnamStatus {
+                whitelisted: false,
+                cap: Amount::zero()
+            }
+        );
+    }
 }
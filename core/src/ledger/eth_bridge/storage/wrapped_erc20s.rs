@@ -3,6 +3,7 @@
 use eyre::eyre;
 
 use crate::types::address::{Address, InternalAddress};
+use crate::types::eth_bridge_pool::TransferToEthereumKind;
 use crate::types::ethereum_events::EthAddress;
 use crate::types::storage::{self, DbKeySeg};
 use crate::types::token::{
@@ -19,6 +20,18 @@ pub fn nut(address: &EthAddress) -> Address {
     Address::Internal(InternalAddress::Nut(*address))
 }
 
+/// Construct the token address of the given kind of transfer to Ethereum
+/// from an ERC20 address, i.e. either a wrapped ERC20 or a NUT.
+pub fn asset_address(
+    kind: TransferToEthereumKind,
+    asset: &EthAddress,
+) -> Address {
+    match kind {
+        TransferToEthereumKind::Erc20 => token(asset),
+        TransferToEthereumKind::Nut => nut(asset),
+    }
+}
+
 /// Represents the type of a key relating to a wrapped ERC20
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone)]
 pub enum KeyType {
@@ -331,4 +344,19 @@ mod test {
 
         assert!(has_erc20_segment(&key));
     }
+
+    #[test]
+    fn test_asset_address() {
+        assert_eq!(
+            asset_address(
+                TransferToEthereumKind::Erc20,
+                &DAI_ERC20_ETH_ADDRESS
+            ),
+            dai_erc20_token()
+        );
+        assert_eq!(
+            asset_address(TransferToEthereumKind::Nut, &DAI_ERC20_ETH_ADDRESS),
+            Address::Internal(InternalAddress::Nut(DAI_ERC20_ETH_ADDRESS))
+        );
+    }
 }
@@ -351,6 +351,11 @@ impl VpGasMeter {
             current_gas: Gas::default(),
         }
     }
+
+    /// Get the gas consumed by the VP so far
+    pub fn get_current_gas(&self) -> Gas {
+        self.current_gas
+    }
 }
 
 impl VpsGas {
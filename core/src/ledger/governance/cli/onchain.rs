@@ -1,7 +1,9 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
 
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use super::validation::{
     is_valid_author_balance, is_valid_content, is_valid_default_proposal_data,
@@ -17,6 +19,7 @@ use crate::types::storage::Epoch;
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
 )]
+#[serde(deny_unknown_fields)]
 /// The proposal structure
 pub struct OnChainProposal {
     /// The proposal id
@@ -33,10 +36,58 @@ pub struct OnChainProposal {
     pub grace_epoch: Epoch,
 }
 
+impl OnChainProposal {
+    /// The length of the proposal content, measured as the sum of the
+    /// lengths of its keys and values, as done by the content validation.
+    pub fn content_size(&self) -> usize {
+        self.content
+            .iter()
+            .map(|(key, value)| key.len() + value.len())
+            .sum()
+    }
+
+    /// Check if the proposal content does not exceed the given maximum
+    /// content size.
+    pub fn content_fits(&self, max: u64) -> bool {
+        self.content_size() as u64 <= max
+    }
+
+    /// The epochs of the lifecycle of the proposal
+    pub fn timeline(&self) -> ProposalTimeline {
+        ProposalTimeline {
+            start: self.voting_start_epoch,
+            end: self.voting_end_epoch,
+            grace: self.grace_epoch,
+        }
+    }
+}
+
+/// The epochs of the lifecycle of a proposal
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProposalTimeline {
+    /// The epoch from which voting is allowed
+    pub start: Epoch,
+    /// The epoch from which voting is stopped
+    pub end: Epoch,
+    /// The epoch from which the proposal is executed
+    pub grace: Epoch,
+}
+
+impl Display for ProposalTimeline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "voting E{}–E{}, executes at E{}",
+            self.start, self.end, self.grace
+        )
+    }
+}
+
 /// Pgf default proposal
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
 )]
+#[serde(deny_unknown_fields)]
 pub struct DefaultProposal {
     /// The proposal data
     pub proposal: OnChainProposal,
@@ -84,7 +135,7 @@ impl DefaultProposal {
             governance_parameters.min_proposal_fund,
         )?;
         is_valid_content(
-            &self.proposal.content,
+            &self.proposal,
             governance_parameters.max_proposal_content_size,
         )?;
         is_valid_default_proposal_data(
@@ -106,6 +157,7 @@ impl TryFrom<&[u8]> for DefaultProposal {
 
 /// Pgf stewards proposal
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PgfStewardProposal {
     /// The proposal data
     pub proposal: OnChainProposal,
@@ -162,7 +214,7 @@ impl PgfStewardProposal {
             governance_parameters.min_proposal_fund,
         )?;
         is_valid_content(
-            &self.proposal.content,
+            &self.proposal,
             governance_parameters.max_proposal_content_size,
         )?;
         is_valid_pgf_stewards_data(&self.data, &self.proposal.author)?;
@@ -183,6 +235,7 @@ impl TryFrom<&[u8]> for PgfStewardProposal {
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
 )]
+#[serde(deny_unknown_fields)]
 pub struct PgfFundingProposal {
     /// The proposal data
     pub proposal: OnChainProposal,
@@ -225,7 +278,7 @@ impl PgfFundingProposal {
             governance_parameters.max_proposal_period,
         )?;
         is_valid_content(
-            &self.proposal.content,
+            &self.proposal,
             governance_parameters.max_proposal_content_size,
         )?;
         is_valid_pgf_funding_data(&self.data)?;
@@ -271,6 +324,46 @@ impl PgfAction {
     }
 }
 
+/// Errors applying pgf steward actions to a steward set
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum StewardUpdateError {
+    /// The steward to add is already in the set
+    #[error("The address {0} is already a pgf steward")]
+    AlreadySteward(Address),
+    /// The steward to remove is not in the set
+    #[error("The address {0} is not a pgf steward")]
+    NotSteward(Address),
+}
+
+/// Apply the given pgf steward actions, in order, to the current set of
+/// stewards. If any action is invalid, the set is left untouched.
+pub fn apply_steward_actions(
+    current: &mut BTreeSet<Address>,
+    actions: &[PgfSteward],
+) -> Result<(), StewardUpdateError> {
+    let mut stewards = current.clone();
+    for PgfSteward { action, address } in actions {
+        match action {
+            PgfAction::Add => {
+                if !stewards.insert(address.clone()) {
+                    return Err(StewardUpdateError::AlreadySteward(
+                        address.clone(),
+                    ));
+                }
+            }
+            PgfAction::Remove => {
+                if !stewards.remove(address) {
+                    return Err(StewardUpdateError::NotSteward(
+                        address.clone(),
+                    ));
+                }
+            }
+        }
+    }
+    *current = stewards;
+    Ok(())
+}
+
 /// Pgf fundings
 #[derive(
     Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
@@ -366,3 +459,188 @@ impl ProposalVote {
         std::mem::discriminant(self) == std::mem::discriminant(other)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+
+    /// Test that the proposal content size is checked against the given
+    /// maximum, consistently with the content validation.
+    #[test]
+    fn test_content_fits() {
+        let proposal = OnChainProposal {
+            id: None,
+            content: BTreeMap::from([
+                ("title".to_string(), "Proposal".to_string()),
+                ("details".to_string(), "Some details".to_string()),
+            ]),
+            author: established_address_1(),
+            voting_start_epoch: Epoch(0),
+            voting_end_epoch: Epoch(12),
+            grace_epoch: Epoch(18),
+        };
+        let size = proposal.content_size();
+        assert_eq!(size, 32);
+
+        assert!(proposal.content_fits(size as u64));
+        assert!(proposal.content_fits(size as u64 + 1));
+        assert!(is_valid_content(&proposal, size as u64).is_ok());
+        assert!(!proposal.content_fits(size as u64 - 1));
+        assert!(is_valid_content(&proposal, size as u64 - 1).is_err());
+    }
+
+    /// Test adding and removing pgf stewards.
+    #[test]
+    fn test_apply_steward_actions() {
+        let mut stewards = BTreeSet::from([established_address_1()]);
+
+        apply_steward_actions(
+            &mut stewards,
+            &[PgfSteward {
+                action: PgfAction::Add,
+                address: established_address_2(),
+            }],
+        )
+        .expect("Test failed");
+        assert_eq!(
+            stewards,
+            BTreeSet::from([established_address_1(), established_address_2()])
+        );
+
+        apply_steward_actions(
+            &mut stewards,
+            &[PgfSteward {
+                action: PgfAction::Remove,
+                address: established_address_1(),
+            }],
+        )
+        .expect("Test failed");
+        assert_eq!(stewards, BTreeSet::from([established_address_2()]));
+    }
+
+    /// Test that removing an address which is not a pgf steward fails,
+    /// leaving the stewards untouched.
+    #[test]
+    fn test_apply_steward_actions_remove_missing() {
+        let mut stewards = BTreeSet::from([established_address_1()]);
+
+        let result = apply_steward_actions(
+            &mut stewards,
+            &[
+                PgfSteward {
+                    action: PgfAction::Remove,
+                    address: established_address_1(),
+                },
+                PgfSteward {
+                    action: PgfAction::Remove,
+                    address: established_address_2(),
+                },
+            ],
+        );
+        assert_eq!(
+            result,
+            Err(StewardUpdateError::NotSteward(established_address_2()))
+        );
+        assert_eq!(stewards, BTreeSet::from([established_address_1()]));
+    }
+
+    /// Test that adding an existing pgf steward fails.
+    #[test]
+    fn test_apply_steward_actions_add_duplicate() {
+        let mut stewards = BTreeSet::from([established_address_1()]);
+
+        let result = apply_steward_actions(
+            &mut stewards,
+            &[PgfSteward {
+                action: PgfAction::Add,
+                address: established_address_1(),
+            }],
+        );
+        assert_eq!(
+            result,
+            Err(StewardUpdateError::AlreadySteward(established_address_1()))
+        );
+        assert_eq!(stewards, BTreeSet::from([established_address_1()]));
+    }
+
+    /// Test that proposal files are parsed, unless they contain unknown
+    /// fields.
+    #[test]
+    fn test_proposal_file_unknown_fields() {
+        let proposal = DefaultProposal {
+            proposal: OnChainProposal {
+                id: Some(0),
+                content: BTreeMap::from([(
+                    "title".to_string(),
+                    "Proposal".to_string(),
+                )]),
+                author: established_address_1(),
+                voting_start_epoch: Epoch(0),
+                voting_end_epoch: Epoch(12),
+                grace_epoch: Epoch(18),
+            },
+            data: None,
+        };
+        let json = serde_json::to_value(&proposal).expect("Test failed");
+
+        let parsed = DefaultProposal::try_from(json.to_string().as_bytes())
+            .expect("Test failed");
+        assert_eq!(parsed.proposal.author, proposal.proposal.author);
+        assert_eq!(parsed.proposal.grace_epoch, proposal.proposal.grace_epoch);
+
+        let mut stray = json.clone();
+        stray["extra"] = serde_json::json!(true);
+        assert!(
+            DefaultProposal::try_from(stray.to_string().as_bytes()).is_err()
+        );
+
+        // a typo'd key must not be silently dropped, even if the
+        // proposal is otherwise complete
+        let mut typo = json;
+        typo["proposal"]["voting_strat_epoch"] = serde_json::json!(0);
+        assert!(
+            DefaultProposal::try_from(typo.to_string().as_bytes()).is_err()
+        );
+    }
+
+    /// Test the rendering of the timeline of a proposal.
+    #[test]
+    fn test_proposal_timeline() {
+        let proposal = OnChainProposal {
+            id: Some(1),
+            content: BTreeMap::new(),
+            author: established_address_1(),
+            voting_start_epoch: Epoch(3),
+            voting_end_epoch: Epoch(15),
+            grace_epoch: Epoch(21),
+        };
+        let timeline = proposal.timeline();
+
+        assert_eq!(
+            timeline,
+            ProposalTimeline {
+                start: Epoch(3),
+                end: Epoch(15),
+                grace: Epoch(21),
+            }
+        );
+        assert_eq!(timeline.to_string(), "voting E3–E15, executes at E21");
+    }
+
+    /// Test that a grace epoch lower than the end epoch is rejected,
+    /// rather than underflowing.
+    #[test]
+    fn test_grace_epoch_before_end_epoch() {
+        assert_eq!(
+            is_valid_grace_epoch(Epoch(5), Epoch(6), 1),
+            Err(ProposalValidation::InvalidGraceEpochOrder(
+                Epoch(5),
+                Epoch(6)
+            ))
+        );
+        assert!(is_valid_grace_epoch(Epoch(7), Epoch(6), 1).is_ok());
+    }
+}
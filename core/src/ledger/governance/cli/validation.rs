@@ -1,8 +1,6 @@
-use std::collections::BTreeMap;
-
 use thiserror::Error;
 
-use super::onchain::{PgfFunding, StewardsUpdate};
+use super::onchain::{OnChainProposal, PgfFunding, StewardsUpdate};
 use crate::types::address::Address;
 use crate::types::storage::Epoch;
 use crate::types::token;
@@ -32,6 +30,12 @@ pub enum ProposalValidation {
          end epoch must be at least {0}, but found {1}"
     )]
     InvalidEndGraceDifference(u64, u64),
+    /// The proposal grace epoch comes before its end epoch
+    #[error(
+        "Invalid proposal grace epoch: grace epoch {0} must not be lower than \
+         end epoch {1}"
+    )]
+    InvalidGraceEpochOrder(Epoch, Epoch),
     /// The proposal difference between end and grace epoch is invalid
     #[error(
         "Invalid proposal period: difference between proposal start and grace \
@@ -135,7 +139,13 @@ pub fn is_valid_grace_epoch(
     proposal_end_epoch: Epoch,
     min_proposal_grace_epoch: u64,
 ) -> Result<(), ProposalValidation> {
-    let grace_period = proposal_grace_epoch.0 - proposal_end_epoch.0;
+    let grace_period = proposal_grace_epoch
+        .checked_sub(proposal_end_epoch)
+        .ok_or(ProposalValidation::InvalidGraceEpochOrder(
+            proposal_grace_epoch,
+            proposal_end_epoch,
+        ))?
+        .0;
 
     if grace_period > 0 && grace_period >= min_proposal_grace_epoch {
         Ok(())
@@ -165,17 +175,10 @@ pub fn is_valid_proposal_period(
 }
 
 pub fn is_valid_content(
-    proposal_content: &BTreeMap<String, String>,
+    proposal: &OnChainProposal,
     max_content_length: u64,
 ) -> Result<(), ProposalValidation> {
-    let proposal_content_keys_length: u64 =
-        proposal_content.keys().map(|key| key.len() as u64).sum();
-    let proposal_content_values_length: u64 = proposal_content
-        .values()
-        .map(|value| value.len() as u64)
-        .sum();
-    let proposal_content_length =
-        proposal_content_values_length + proposal_content_keys_length;
+    let proposal_content_length = proposal.content_size() as u64;
 
     if proposal_content_length <= max_content_length {
         Ok(())
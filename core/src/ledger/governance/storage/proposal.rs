@@ -8,6 +8,7 @@ use thiserror::Error;
 use crate::ledger::governance::cli::onchain::{
     PgfAction, PgfContinous, PgfRetro, PgfSteward, StewardsUpdate,
 };
+use crate::ledger::governance::parameters::GovernanceParameters;
 use crate::ledger::governance::utils::{ProposalStatus, TallyType};
 use crate::ledger::storage_api::token::Amount;
 use crate::types::address::Address;
@@ -129,6 +130,17 @@ impl ProposalType {
     pub fn is_default(&self) -> bool {
         matches!(self, ProposalType::Default(_))
     }
+
+    /// Get the minimum voting period in epochs for this kind of proposal.
+    /// All kinds currently use the global minimum from the governance
+    /// parameters.
+    pub fn min_voting_period(&self, params: &GovernanceParameters) -> u64 {
+        match self {
+            ProposalType::Default(_)
+            | ProposalType::PGFSteward(_)
+            | ProposalType::PGFPayment(_) => params.min_proposal_voting_period,
+        }
+    }
 }
 
 impl Display for ProposalType {
@@ -374,3 +386,30 @@ pub mod testing {
                 .boxed())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::testing::arb_proposal_type;
+    use super::*;
+
+    proptest! {
+        /// Test that every kind of proposal defaults to the global minimum
+        /// voting period
+        #[test]
+        fn test_min_voting_period_default(
+            proposal_type in arb_proposal_type(),
+            min_proposal_voting_period in 1..100_u64,
+        ) {
+            let params = GovernanceParameters {
+                min_proposal_voting_period,
+                ..Default::default()
+            };
+            prop_assert_eq!(
+                proposal_type.min_voting_period(&params),
+                min_proposal_voting_period
+            );
+        }
+    }
+}
@@ -4,7 +4,7 @@ pub use ics23::ProofSpec;
 
 use crate::ledger::storage_api::{Error, StorageRead, StorageWrite};
 use crate::types::address::Address;
-use crate::types::ibc::{IbcEvent, IbcShieldedTransfer};
+use crate::types::ibc::{IbcEvent, IbcEventType, IbcShieldedTransfer};
 use crate::types::token::DenominatedAmount;
 
 /// IBC context trait to be implemented in integration that can read and write
@@ -18,6 +18,14 @@ pub trait IbcStorageContext: StorageRead + StorageWrite {
         event_type: impl AsRef<str>,
     ) -> Result<Vec<IbcEvent>, Error>;
 
+    /// Get IBC events of the given type
+    fn get_ibc_events_typed(
+        &self,
+        ty: IbcEventType,
+    ) -> Result<Vec<IbcEvent>, Error> {
+        self.get_ibc_events(ty)
+    }
+
     /// Transfer token
     fn transfer_token(
         &mut self,
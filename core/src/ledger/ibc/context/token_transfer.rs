@@ -12,7 +12,7 @@ use crate::ibc::apps::transfer::types::{PrefixedCoin, PrefixedDenom};
 use crate::ibc::core::channel::types::error::ChannelError;
 use crate::ibc::core::handler::types::error::ContextError;
 use crate::ibc::core::host::types::identifiers::{ChannelId, PortId};
-use crate::ledger::ibc::storage;
+use crate::ledger::ibc::{ibc_denom_token, storage};
 use crate::ledger::storage_api::token::read_denom;
 use crate::types::address::{Address, InternalAddress};
 use crate::types::token;
@@ -42,10 +42,7 @@ where
         &self,
         coin: &PrefixedCoin,
     ) -> Result<(Address, token::DenominatedAmount), TokenTransferError> {
-        let token = match Address::decode(coin.denom.base_denom.as_str()) {
-            Ok(token_addr) if coin.denom.trace_path.is_empty() => token_addr,
-            _ => storage::ibc_token(coin.denom.to_string()),
-        };
+        let token = ibc_denom_token(&coin.denom);
 
         // Convert IBC amount to Namada amount for the token
         let denom = read_denom(&*self.inner.borrow(), &token)
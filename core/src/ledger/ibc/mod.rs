@@ -36,12 +36,13 @@ use crate::ibc::core::host::types::identifiers::{ChannelId, PortId};
 use crate::ibc::core::router::types::error::RouterError;
 use crate::ibc::core::router::types::module::ModuleId;
 use crate::ibc::primitives::proto::Any;
-use crate::types::address::{Address, MASP};
+use crate::ledger::storage_api::{self, StorageRead};
+use crate::types::address::{Address, InternalAddress, MASP};
 use crate::types::ibc::{
-    get_shielded_transfer, is_ibc_denom, MsgShieldedTransfer,
-    EVENT_TYPE_DENOM_TRACE, EVENT_TYPE_PACKET,
+    get_shielded_transfer, is_ibc_denom, IbcEventType, MsgShieldedTransfer,
 };
 use crate::types::masp::PaymentAddress;
+use crate::types::token::minted_balance_key;
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -182,7 +183,7 @@ where
             .ctx
             .inner
             .borrow()
-            .get_ibc_events(EVENT_TYPE_PACKET)
+            .get_ibc_events_typed(IbcEventType::Packet)
             .map_err(|_| {
                 Error::Denom("Reading the IBC event failed".to_string())
             })?;
@@ -214,7 +215,7 @@ where
             .ctx
             .inner
             .borrow()
-            .get_ibc_events(EVENT_TYPE_DENOM_TRACE)
+            .get_ibc_events_typed(IbcEventType::DenomTrace)
             .map_err(|_| {
                 Error::Denom("Reading the IBC event failed".to_string())
             })?;
@@ -261,7 +262,7 @@ where
                     .ctx
                     .inner
                     .borrow()
-                    .get_ibc_events(EVENT_TYPE_PACKET)
+                    .get_ibc_events_typed(IbcEventType::Packet)
                     .map_err(|_| {
                         Error::MaspTx(
                             "Reading the IBC event failed".to_string(),
@@ -341,6 +342,39 @@ pub fn received_ibc_token(
     Ok(storage::ibc_token(ibc_denom.to_string()))
 }
 
+/// Get the token address of the given IBC denom. A denom without a trace whose
+/// base denom is an address is a Namada token, any other denom is an
+/// `IbcToken`.
+pub fn ibc_denom_token(ibc_denom: &PrefixedDenom) -> Address {
+    match Address::decode(ibc_denom.base_denom.as_str()) {
+        Ok(token) if ibc_denom.trace_path.is_empty() => token,
+        _ => storage::ibc_token(ibc_denom.to_string()),
+    }
+}
+
+/// Resolve an IBC denom trace (e.g. `transfer/channel-0/uatom`) to the
+/// address of the corresponding token on this chain. Returns `None` if the
+/// trace cannot be parsed or if no such `IbcToken` has been minted. Without
+/// access to the storage, e.g. to display a tx on a hardware wallet,
+/// [`ibc_denom_token`] gives the address the denom would resolve to.
+pub fn resolve_ibc_denom<S>(
+    storage: &S,
+    trace: &str,
+) -> storage_api::Result<Option<Address>>
+where
+    S: StorageRead,
+{
+    let Ok(ibc_denom) = PrefixedDenom::from_str(trace) else {
+        return Ok(None);
+    };
+    let token = ibc_denom_token(&ibc_denom);
+    if !matches!(token, Address::Internal(InternalAddress::IbcToken(_))) {
+        return Ok(Some(token));
+    }
+    let is_minted = storage.has_key(&minted_balance_key(&token))?;
+    Ok(is_minted.then_some(token))
+}
+
 #[cfg(any(test, feature = "testing"))]
 /// Testing helpers ans strategies for IBC
 pub mod testing {
@@ -516,3 +550,39 @@ pub mod testing {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::ledger::storage_api::token::credit_tokens;
+    use crate::types::address::nam;
+    use crate::types::address::testing::established_address_1;
+    use crate::types::token::Amount;
+
+    #[test]
+    fn test_resolve_ibc_denom() {
+        let mut storage = TestWlStorage::default();
+        let trace = "transfer/channel-0/transfer/channel-1/uatom";
+        let token = storage::ibc_token(trace);
+        credit_tokens(
+            &mut storage,
+            &token,
+            &established_address_1(),
+            Amount::from_u64(100),
+        )
+        .unwrap();
+
+        assert_eq!(resolve_ibc_denom(&storage, trace).unwrap(), Some(token));
+        // A Namada token without a trace resolves to itself
+        assert_eq!(
+            resolve_ibc_denom(&storage, &nam().to_string()).unwrap(),
+            Some(nam())
+        );
+        // A trace that has never been received is unrecognized
+        assert_eq!(
+            resolve_ibc_denom(&storage, "transfer/channel-2/uatom").unwrap(),
+            None
+        );
+    }
+}
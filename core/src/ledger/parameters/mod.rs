@@ -4,6 +4,7 @@ pub mod storage;
 use std::collections::BTreeMap;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use borsh_ext::BorshSerializeExt;
 use thiserror::Error;
 
 use super::storage::types;
@@ -14,6 +15,7 @@ use crate::types::address::{Address, InternalAddress};
 use crate::types::chain::ProposalBytes;
 use crate::types::dec::Dec;
 use crate::types::hash::Hash;
+use crate::types::storage::{Epoch, Key, KeySeg};
 use crate::types::time::DurationSecs;
 use crate::types::token;
 
@@ -88,6 +90,44 @@ pub struct EpochDuration {
     pub min_duration: DurationSecs,
 }
 
+/// A change to a single protocol parameter. The changes computed by
+/// [`Parameters::diff`] hold the values encoded as they are stored, while the
+/// parameter updates return them decoded.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParamChange<T = Vec<u8>> {
+    /// The storage key of the changed parameter
+    pub key: Key,
+    /// The value of the parameter before the change, if it was set
+    pub old: Option<T>,
+    /// The value of the parameter after the change
+    pub new: T,
+}
+
+impl<T> ParamChange<T> {
+    /// Get the name of the changed parameter, i.e. the last segment of its
+    /// storage key
+    pub fn name(&self) -> String {
+        self.key.last().map(KeySeg::raw).unwrap_or_default()
+    }
+}
+
+/// A change to a protocol parameter that cannot be modified by governance.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReadOnlyViolation {
+    /// The storage key of the read-only parameter
+    pub key: Key,
+}
+
+/// Error of an attempt to initialize the parameters in a storage that already
+/// contains some of them. It can be recovered from a [`storage_api::Error`]
+/// with [`storage_api::Error::downcast`].
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Protocol parameters are already initialized: Key {key}")]
+pub struct AlreadyInitialized {
+    /// The storage key of a parameter that is already set
+    pub key: Key,
+}
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum ReadError {
@@ -109,11 +149,117 @@ pub enum WriteError {
 }
 
 impl Parameters {
+    /// Construct parameters with the defaults of a local testnet, accepting
+    /// only the given native token for the payment of fees. All the fields
+    /// can be adjusted after construction.
+    ///
+    /// The values mirror `genesis/localnet/parameters.toml`. In particular,
+    /// `epochs_per_year` is `31_536_000`, which sets the min duration of an
+    /// epoch to a single second, so that epochs advance every
+    /// `min_num_of_blocks` blocks, as wanted on a local testnet.
+    pub fn with_defaults(
+        native_token: Address,
+        implicit_vp_code_hash: Hash,
+    ) -> Self {
+        let epochs_per_year: u64 = 31_536_000;
+        Self {
+            max_tx_bytes: 1024 * 1024,
+            epoch_duration: EpochDuration {
+                min_num_of_blocks: 4,
+                min_duration: DurationSecs(
+                    60 * 60 * 24 * 365 / epochs_per_year,
+                ),
+            },
+            max_expected_time_per_block: DurationSecs(30),
+            max_proposal_bytes: ProposalBytes::default(),
+            max_block_gas: 20_000_000,
+            vp_whitelist: vec![],
+            tx_whitelist: vec![],
+            implicit_vp_code_hash: Some(implicit_vp_code_hash),
+            epochs_per_year,
+            max_signatures_per_transaction: 15,
+            staked_ratio: Dec::zero(),
+            pos_inflation_amount: token::Amount::zero(),
+            fee_unshielding_gas_limit: 20_000,
+            fee_unshielding_descriptions_limit: 15,
+            minimum_gas_price: BTreeMap::from([(
+                native_token,
+                token::Amount::from_u64(1),
+            )]),
+        }
+    }
+
     /// Initialize parameters in storage in the genesis block.
     pub fn init_storage<S>(&self, storage: &mut S) -> storage_api::Result<()>
     where
         S: StorageRead + StorageWrite,
     {
+        for (key, value) in self.encode_storage_values() {
+            storage.write_bytes(&key, value)?;
+        }
+        Ok(())
+    }
+
+    /// Initialize parameters in storage, like [`Parameters::init_storage`],
+    /// but fail with an [`AlreadyInitialized`] error if any of the parameters
+    /// is already set, so that live values are never overwritten.
+    pub fn init_storage_checked<S>(
+        &self,
+        storage: &mut S,
+    ) -> storage_api::Result<()>
+    where
+        S: StorageRead + StorageWrite,
+    {
+        for (key, _) in self.encode_storage_values() {
+            if storage.has_key(&key)? {
+                return Err(storage_api::Error::new(AlreadyInitialized {
+                    key,
+                }));
+            }
+        }
+        self.init_storage(storage)
+    }
+
+    /// Compute the changes needed to turn these parameters into `new`. Only
+    /// the parameters whose value differs are included.
+    pub fn diff(&self, new: &Parameters) -> Vec<ParamChange> {
+        self.encode_storage_values()
+            .into_iter()
+            .zip(new.encode_storage_values())
+            .filter_map(|((key, old_value), (_, new_value))| {
+                (old_value != new_value).then_some(ParamChange {
+                    key,
+                    old: Some(old_value),
+                    new: new_value,
+                })
+            })
+            .collect()
+    }
+
+    /// Compute the changes needed to turn these parameters into `new`, such
+    /// that they can be applied by a governance proposal. If the diff
+    /// touches any read-only parameter, the violations are returned instead.
+    pub fn diff_as_proposal_changes(
+        &self,
+        new: &Parameters,
+    ) -> Result<Vec<ParamChange>, Vec<ReadOnlyViolation>> {
+        let (read_only, writable): (Vec<_>, Vec<_>) =
+            self.diff(new).into_iter().partition(|change| {
+                storage::is_read_only_parameter_key(&change.key)
+            });
+        if read_only.is_empty() {
+            Ok(writable)
+        } else {
+            Err(read_only
+                .into_iter()
+                .map(|change| ReadOnlyViolation { key: change.key })
+                .collect())
+        }
+    }
+
+    /// Get the storage keys of the parameters alongside their encoded values,
+    /// as written by [`Parameters::init_storage`].
+    fn encode_storage_values(&self) -> Vec<(Key, Vec<u8>)> {
         let Self {
             max_tx_bytes,
             epoch_duration,
@@ -131,90 +277,76 @@ impl Parameters {
             fee_unshielding_gas_limit,
             fee_unshielding_descriptions_limit,
         } = self;
+        let lowercase = |whitelist: &Vec<String>| {
+            whitelist
+                .iter()
+                .map(|id| id.to_lowercase())
+                .collect::<Vec<String>>()
+        };
 
-        // write max tx bytes parameter
-        let max_tx_bytes_key = storage::get_max_tx_bytes_key();
-        storage.write(&max_tx_bytes_key, max_tx_bytes)?;
-
-        // write max proposal bytes parameter
-        let max_proposal_bytes_key = storage::get_max_proposal_bytes_key();
-        storage.write(&max_proposal_bytes_key, max_proposal_bytes)?;
-
-        // write max block gas parameter
-        let max_block_gas_key = storage::get_max_block_gas_key();
-        storage.write(&max_block_gas_key, max_block_gas)?;
-
-        // write epoch parameters
-        let epoch_key = storage::get_epoch_duration_storage_key();
-        storage.write(&epoch_key, epoch_duration)?;
-
-        // write fee unshielding gas limit
-        let fee_unshielding_gas_limit_key =
-            storage::get_fee_unshielding_gas_limit_key();
-        storage
-            .write(&fee_unshielding_gas_limit_key, fee_unshielding_gas_limit)?;
-
-        // write fee unshielding descriptions limit
-        let fee_unshielding_descriptions_limit_key =
-            storage::get_fee_unshielding_descriptions_limit_key();
-        storage.write(
-            &fee_unshielding_descriptions_limit_key,
-            fee_unshielding_descriptions_limit,
-        )?;
-
-        // write vp whitelist parameter
-        let vp_whitelist_key = storage::get_vp_whitelist_storage_key();
-        let vp_whitelist = vp_whitelist
-            .iter()
-            .map(|id| id.to_lowercase())
-            .collect::<Vec<String>>();
-        storage.write(&vp_whitelist_key, vp_whitelist)?;
-
-        // write tx whitelist parameter
-        let tx_whitelist_key = storage::get_tx_whitelist_storage_key();
-        let tx_whitelist = tx_whitelist
-            .iter()
-            .map(|id| id.to_lowercase())
-            .collect::<Vec<String>>();
-        storage.write(&tx_whitelist_key, tx_whitelist)?;
-
-        // write max expected time per block
-        let max_expected_time_per_block_key =
-            storage::get_max_expected_time_per_block_key();
-        storage.write(
-            &max_expected_time_per_block_key,
-            max_expected_time_per_block,
-        )?;
-
-        // write implicit vp parameter
-        let implicit_vp_key = storage::get_implicit_vp_key();
-        // Using `fn write_bytes` here, because implicit_vp code hash doesn't
-        // need to be encoded, it's bytes already.
-        storage.write_bytes(
-            &implicit_vp_key,
-            implicit_vp_code_hash.unwrap_or_default(),
-        )?;
-
-        let epochs_per_year_key = storage::get_epochs_per_year_key();
-        storage.write(&epochs_per_year_key, epochs_per_year)?;
-
-        let max_signatures_per_transaction_key =
-            storage::get_max_signatures_per_transaction_key();
-        storage.write(
-            &max_signatures_per_transaction_key,
-            max_signatures_per_transaction,
-        )?;
-
-        let staked_ratio_key = storage::get_staked_ratio_key();
-        storage.write(&staked_ratio_key, staked_ratio)?;
-
-        let pos_inflation_key = storage::get_pos_inflation_amount_key();
-        storage.write(&pos_inflation_key, pos_inflation_amount)?;
-
-        let gas_cost_key = storage::get_gas_cost_key();
-        storage.write(&gas_cost_key, minimum_gas_price)?;
-
-        Ok(())
+        vec![
+            (
+                storage::get_max_tx_bytes_key(),
+                max_tx_bytes.serialize_to_vec(),
+            ),
+            (
+                storage::get_max_proposal_bytes_key(),
+                max_proposal_bytes.serialize_to_vec(),
+            ),
+            (
+                storage::get_max_block_gas_key(),
+                max_block_gas.serialize_to_vec(),
+            ),
+            (
+                storage::get_epoch_duration_storage_key(),
+                epoch_duration.serialize_to_vec(),
+            ),
+            (
+                storage::get_fee_unshielding_gas_limit_key(),
+                fee_unshielding_gas_limit.serialize_to_vec(),
+            ),
+            (
+                storage::get_fee_unshielding_descriptions_limit_key(),
+                fee_unshielding_descriptions_limit.serialize_to_vec(),
+            ),
+            (
+                storage::get_vp_whitelist_storage_key(),
+                lowercase(vp_whitelist).serialize_to_vec(),
+            ),
+            (
+                storage::get_tx_whitelist_storage_key(),
+                lowercase(tx_whitelist).serialize_to_vec(),
+            ),
+            (
+                storage::get_max_expected_time_per_block_key(),
+                max_expected_time_per_block.serialize_to_vec(),
+            ),
+            // The implicit VP code hash is stored as raw bytes, not encoded
+            (
+                storage::get_implicit_vp_key(),
+                implicit_vp_code_hash.unwrap_or_default().0.to_vec(),
+            ),
+            (
+                storage::get_epochs_per_year_key(),
+                epochs_per_year.serialize_to_vec(),
+            ),
+            (
+                storage::get_max_signatures_per_transaction_key(),
+                max_signatures_per_transaction.serialize_to_vec(),
+            ),
+            (
+                storage::get_staked_ratio_key(),
+                staked_ratio.serialize_to_vec(),
+            ),
+            (
+                storage::get_pos_inflation_amount_key(),
+                pos_inflation_amount.serialize_to_vec(),
+            ),
+            (
+                storage::get_gas_cost_key(),
+                minimum_gas_price.serialize_to_vec(),
+            ),
+        ]
     }
 }
 
@@ -229,31 +361,47 @@ where
     storage.read(&key)
 }
 
+/// Write a new value for the parameter at the given key and return the change
+/// from its prior value, so that the caller can log or index it.
+fn update_parameter<S, T>(
+    storage: &mut S,
+    key: Key,
+    new: T,
+) -> storage_api::Result<ParamChange<T>>
+where
+    S: StorageRead + StorageWrite,
+    T: BorshSerialize + BorshDeserialize,
+{
+    let old = storage.read(&key)?;
+    storage.write(&key, &new)?;
+    Ok(ParamChange { key, old, new })
+}
+
 /// Update the max_expected_time_per_block parameter in storage. Returns the
-/// parameters and gas cost.
+/// parameter change.
 pub fn update_max_expected_time_per_block_parameter<S>(
     storage: &mut S,
     value: &DurationSecs,
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<DurationSecs>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_max_expected_time_per_block_key();
-    storage.write(&key, value)
+    update_parameter(storage, key, *value)
 }
 
-/// Update the vp whitelist parameter in storage. Returns the parameters and gas
-/// cost.
+/// Update the vp whitelist parameter in storage. Returns the parameter change.
 pub fn update_vp_whitelist_parameter<S>(
     storage: &mut S,
     value: Vec<String>,
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<Vec<String>>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_vp_whitelist_storage_key();
-    storage.write(
-        &key,
+    update_parameter(
+        storage,
+        key,
         value
             .iter()
             .map(|id| id.to_lowercase())
@@ -261,18 +409,18 @@ where
     )
 }
 
-/// Update the tx whitelist parameter in storage. Returns the parameters and gas
-/// cost.
+/// Update the tx whitelist parameter in storage. Returns the parameter change.
 pub fn update_tx_whitelist_parameter<S>(
     storage: &mut S,
     value: Vec<String>,
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<Vec<String>>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_tx_whitelist_storage_key();
-    storage.write(
-        &key,
+    update_parameter(
+        storage,
+        key,
         value
             .iter()
             .map(|id| id.to_lowercase())
@@ -280,84 +428,148 @@ where
     )
 }
 
-/// Update the epoch parameter in storage. Returns the parameters and gas
-/// cost.
+/// Update the epoch parameter in storage. Returns the parameter change.
 pub fn update_epoch_parameter<S>(
     storage: &mut S,
     value: &EpochDuration,
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<EpochDuration>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_epoch_duration_storage_key();
-    storage.write(&key, value)
+    update_parameter(storage, key, value.clone())
 }
 
-/// Update the epochs_per_year parameter in storage. Returns the parameters and
-/// gas cost.
+/// Update the epochs_per_year parameter in storage. Returns the parameter
+/// change.
 pub fn update_epochs_per_year_parameter<S>(
     storage: &mut S,
     value: &u64,
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<u64>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_epochs_per_year_key();
-    storage.write(&key, value)
+    update_parameter(storage, key, *value)
 }
 
-/// Update the PoS staked ratio parameter in storage. Returns the parameters and
-/// gas cost.
+/// Update the PoS staked ratio parameter in storage. Returns the parameter
+/// change.
 pub fn update_staked_ratio_parameter<S>(
     storage: &mut S,
     value: &Dec,
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<Dec>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_staked_ratio_key();
-    storage.write(&key, value)
+    update_parameter(storage, key, *value)
 }
 
-/// Update the PoS inflation rate parameter in storage. Returns the parameters
-/// and gas cost.
+/// Update the PoS inflation amount parameter in storage. Returns the
+/// parameter change. The value is a [`token::Amount`], like
+/// [`Parameters::pos_inflation_amount`].
 pub fn update_pos_inflation_amount_parameter<S>(
     storage: &mut S,
-    value: &u64,
-) -> storage_api::Result<()>
+    value: &token::Amount,
+) -> storage_api::Result<ParamChange<token::Amount>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_pos_inflation_amount_key();
-    storage.write(&key, value)
+    update_parameter(storage, key, *value)
+}
+
+/// Update the PoS staked ratio parameter in storage, also keeping a snapshot
+/// of its value for the given epoch under a separate history sub-key. This is
+/// called by the protocol when applying the PoS inflation of a new epoch.
+/// Returns the parameter change.
+pub fn write_staked_ratio_at_epoch<S>(
+    storage: &mut S,
+    epoch: Epoch,
+    value: &Dec,
+) -> storage_api::Result<ParamChange<Dec>>
+where
+    S: StorageRead + StorageWrite,
+{
+    storage.write(&storage::get_staked_ratio_at_epoch_key(epoch), *value)?;
+    update_staked_ratio_parameter(storage, value)
 }
 
-/// Update the implicit VP parameter in storage. Return the gas cost.
+/// Read the snapshot of the PoS staked ratio parameter at the given epoch, if
+/// any was written with [`write_staked_ratio_at_epoch`].
+pub fn read_staked_ratio_at_epoch<S>(
+    storage: &S,
+    epoch: Epoch,
+) -> storage_api::Result<Option<Dec>>
+where
+    S: StorageRead,
+{
+    storage.read(&storage::get_staked_ratio_at_epoch_key(epoch))
+}
+
+/// Update the PoS inflation amount parameter in storage, also keeping a
+/// snapshot of its value for the given epoch under a separate history
+/// sub-key. This is called by the protocol when applying the PoS inflation
+/// of a new epoch. Returns the parameter change.
+pub fn write_pos_inflation_amount_at_epoch<S>(
+    storage: &mut S,
+    epoch: Epoch,
+    value: &token::Amount,
+) -> storage_api::Result<ParamChange<token::Amount>>
+where
+    S: StorageRead + StorageWrite,
+{
+    storage.write(
+        &storage::get_pos_inflation_amount_at_epoch_key(epoch),
+        *value,
+    )?;
+    update_pos_inflation_amount_parameter(storage, value)
+}
+
+/// Read the snapshot of the PoS inflation amount parameter at the given
+/// epoch, if any was written with [`write_pos_inflation_amount_at_epoch`].
+pub fn read_pos_inflation_amount_at_epoch<S>(
+    storage: &S,
+    epoch: Epoch,
+) -> storage_api::Result<Option<token::Amount>>
+where
+    S: StorageRead,
+{
+    storage.read(&storage::get_pos_inflation_amount_at_epoch_key(epoch))
+}
+
+/// Update the implicit VP parameter in storage. Returns the parameter change.
 pub fn update_implicit_vp<S>(
     storage: &mut S,
     implicit_vp: &[u8],
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<Vec<u8>>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_implicit_vp_key();
+    let old = storage.read_bytes(&key)?;
     // Using `fn write_bytes` here, because implicit_vp doesn't need to be
     // encoded, it's bytes already.
-    storage.write_bytes(&key, implicit_vp)
+    storage.write_bytes(&key, implicit_vp)?;
+    Ok(ParamChange {
+        key,
+        old,
+        new: implicit_vp.to_vec(),
+    })
 }
 
-/// Update the max signatures per transaction storage parameter
+/// Update the max signatures per transaction storage parameter. Returns the
+/// parameter change.
 pub fn update_max_signature_per_tx<S>(
     storage: &mut S,
     value: u8,
-) -> storage_api::Result<()>
+) -> storage_api::Result<ParamChange<u8>>
 where
     S: StorageRead + StorageWrite,
 {
     let key = storage::get_max_signatures_per_transaction_key();
-    // Using `fn write_bytes` here, because implicit_vp doesn't need to be
-    // encoded, it's bytes already.
-    storage.write(&key, value)
+    update_parameter(storage, key, value)
 }
 
 /// Read the the epoch duration parameter from store
@@ -375,6 +587,18 @@ where
         .into_storage_result()
 }
 
+/// Read the value of the given parameter
+pub fn read_parameter<S, T>(
+    storage: &S,
+    key: &storage::ParameterKey,
+) -> storage_api::Result<Option<T>>
+where
+    S: StorageRead,
+    T: BorshDeserialize,
+{
+    storage.read(key.as_ref())
+}
+
 /// Read the cost per unit of gas for the provided token
 pub fn read_gas_cost<S>(
     storage: &S,
@@ -383,13 +607,38 @@ pub fn read_gas_cost<S>(
 where
     S: StorageRead,
 {
-    let gas_cost_table: BTreeMap<Address, Amount> = storage
-        .read(&storage::get_gas_cost_key())?
-        .ok_or(ReadError::ParametersMissing)
-        .into_storage_result()?;
+    let gas_cost_table: BTreeMap<Address, Amount> =
+        read_parameter(storage, &storage::ParameterKey::minimum_gas_price())?
+            .ok_or(ReadError::ParametersMissing)
+            .into_storage_result()?;
     Ok(gas_cost_table.get(token).map(|amount| amount.to_owned()))
 }
 
+/// Check if the given token is accepted for the payment of fees, i.e. if it
+/// has a minimum gas price
+pub fn is_fee_token_allowed<S>(
+    storage: &S,
+    token: &Address,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    Ok(read_gas_cost(storage, token)?.is_some())
+}
+
+/// Read all the tokens accepted for the payment of fees, ordered by their
+/// addresses
+pub fn allowed_fee_tokens<S>(storage: &S) -> storage_api::Result<Vec<Address>>
+where
+    S: StorageRead,
+{
+    let gas_cost_table: BTreeMap<Address, Amount> =
+        read_parameter(storage, &storage::ParameterKey::minimum_gas_price())?
+            .ok_or(ReadError::ParametersMissing)
+            .into_storage_result()?;
+    Ok(gas_cost_table.into_keys().collect())
+}
+
 /// Read all the parameters from storage. Returns the parameters and gas
 /// cost.
 pub fn read<S>(storage: &S) -> storage_api::Result<Parameters>
@@ -525,3 +774,259 @@ where
         fee_unshielding_descriptions_limit,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+
+    fn test_parameters() -> Parameters {
+        Parameters {
+            max_tx_bytes: 1024 * 1024,
+            epoch_duration: EpochDuration {
+                min_num_of_blocks: 1,
+                min_duration: DurationSecs(3600),
+            },
+            max_expected_time_per_block: DurationSecs(3600),
+            max_proposal_bytes: Default::default(),
+            max_block_gas: 100,
+            vp_whitelist: vec![],
+            tx_whitelist: vec![],
+            implicit_vp_code_hash: Default::default(),
+            epochs_per_year: 365,
+            max_signatures_per_transaction: 10,
+            staked_ratio: Default::default(),
+            pos_inflation_amount: Default::default(),
+            fee_unshielding_gas_limit: 0,
+            fee_unshielding_descriptions_limit: 0,
+            minimum_gas_price: Default::default(),
+        }
+    }
+
+    #[test]
+    fn test_diff_as_proposal_changes_writable() {
+        let params = test_parameters();
+        let new_params = Parameters {
+            max_block_gas: 200,
+            max_signatures_per_transaction: 15,
+            ..params.clone()
+        };
+
+        let changes = params
+            .diff_as_proposal_changes(&new_params)
+            .expect("Only writable parameters were changed");
+        assert_eq!(
+            changes,
+            vec![
+                ParamChange {
+                    key: storage::get_max_block_gas_key(),
+                    old: Some(100_u64.serialize_to_vec()),
+                    new: 200_u64.serialize_to_vec(),
+                },
+                ParamChange {
+                    key: storage::get_max_signatures_per_transaction_key(),
+                    old: Some(10_u8.serialize_to_vec()),
+                    new: 15_u8.serialize_to_vec(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diff_as_proposal_changes_read_only_violation() {
+        let params = test_parameters();
+        let new_params = Parameters {
+            max_block_gas: 200,
+            epochs_per_year: 730,
+            ..params.clone()
+        };
+
+        let violations = params
+            .diff_as_proposal_changes(&new_params)
+            .expect_err("A read-only parameter was changed");
+        assert_eq!(
+            violations,
+            vec![ReadOnlyViolation {
+                key: storage::get_epochs_per_year_key(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_parameter_key_try_from() {
+        let key = storage::get_max_block_gas_key();
+        let param_key = storage::ParameterKey::try_from(key.clone())
+            .expect("The max block gas key is a parameter key");
+        assert_eq!(param_key, storage::ParameterKey::max_block_gas());
+        assert_eq!(Key::from(param_key), key);
+
+        let key = token::balance_key(
+            &crate::types::address::nam(),
+            &crate::types::address::testing::established_address_1(),
+        );
+        assert_eq!(
+            storage::ParameterKey::try_from(key.clone()),
+            Err(storage::NotParameterKey(key))
+        );
+        assert!(storage::ParameterKey::try_from(Key::default()).is_err());
+    }
+
+    #[test]
+    fn test_update_epochs_per_year_parameter_change() {
+        let mut storage = TestWlStorage::default();
+
+        let change =
+            update_epochs_per_year_parameter(&mut storage, &365).unwrap();
+        assert_eq!(
+            change,
+            ParamChange {
+                key: storage::get_epochs_per_year_key(),
+                old: None,
+                new: 365,
+            }
+        );
+        assert_eq!(change.name(), "epochs_per_year");
+
+        let change =
+            update_epochs_per_year_parameter(&mut storage, &730).unwrap();
+        assert_eq!(
+            change,
+            ParamChange {
+                key: storage::get_epochs_per_year_key(),
+                old: Some(365),
+                new: 730,
+            }
+        );
+        assert_eq!(
+            storage
+                .read::<u64>(&storage::get_epochs_per_year_key())
+                .unwrap(),
+            Some(730)
+        );
+    }
+
+    #[test]
+    fn test_init_storage_checked_twice() {
+        let mut storage = TestWlStorage::default();
+        let params = test_parameters();
+
+        params.init_storage_checked(&mut storage).unwrap();
+        let staked_ratio = Dec::new(1, 1).unwrap();
+        storage
+            .write(&storage::get_staked_ratio_key(), staked_ratio)
+            .unwrap();
+
+        let err = params
+            .init_storage_checked(&mut storage)
+            .unwrap_err()
+            .downcast::<AlreadyInitialized>()
+            .unwrap();
+        assert!(storage::is_parameter_key(&err.key));
+        // The live values were not overwritten
+        assert_eq!(
+            storage
+                .read::<Dec>(&storage::get_staked_ratio_key())
+                .unwrap(),
+            Some(staked_ratio)
+        );
+    }
+
+    #[test]
+    fn test_staked_ratio_and_inflation_history() {
+        let mut storage = TestWlStorage::default();
+        let (epoch_1, epoch_2) = (Epoch(1), Epoch(2));
+        let ratio_1 = Dec::new(1, 1).unwrap();
+        let ratio_2 = Dec::new(2, 1).unwrap();
+        let inflation_1 = token::Amount::native_whole(10);
+        let inflation_2 = token::Amount::native_whole(20);
+
+        write_staked_ratio_at_epoch(&mut storage, epoch_1, &ratio_1).unwrap();
+        write_pos_inflation_amount_at_epoch(
+            &mut storage,
+            epoch_1,
+            &inflation_1,
+        )
+        .unwrap();
+        let change =
+            write_staked_ratio_at_epoch(&mut storage, epoch_2, &ratio_2)
+                .unwrap();
+        assert_eq!(change.old, Some(ratio_1));
+        write_pos_inflation_amount_at_epoch(
+            &mut storage,
+            epoch_2,
+            &inflation_2,
+        )
+        .unwrap();
+
+        assert_eq!(
+            read_staked_ratio_at_epoch(&storage, epoch_1).unwrap(),
+            Some(ratio_1)
+        );
+        assert_eq!(
+            read_staked_ratio_at_epoch(&storage, epoch_2).unwrap(),
+            Some(ratio_2)
+        );
+        assert_eq!(
+            read_pos_inflation_amount_at_epoch(&storage, epoch_1).unwrap(),
+            Some(inflation_1)
+        );
+        assert_eq!(
+            read_pos_inflation_amount_at_epoch(&storage, epoch_2).unwrap(),
+            Some(inflation_2)
+        );
+        assert_eq!(
+            read_staked_ratio_at_epoch(&storage, Epoch(3)).unwrap(),
+            None
+        );
+        // The current values are the latest ones
+        assert_eq!(
+            storage
+                .read::<Dec>(&storage::get_staked_ratio_key())
+                .unwrap(),
+            Some(ratio_2)
+        );
+        assert_eq!(
+            storage
+                .read::<token::Amount>(&storage::get_pos_inflation_amount_key())
+                .unwrap(),
+            Some(inflation_2)
+        );
+    }
+
+    #[test]
+    fn test_fee_tokens() {
+        let mut storage = TestWlStorage::default();
+        let key = storage::get_gas_cost_key();
+        let allowed = crate::types::address::nam();
+        let disallowed =
+            crate::types::address::testing::established_address_1();
+
+        storage
+            .write(&key, BTreeMap::<Address, Amount>::new())
+            .unwrap();
+        assert!(!is_fee_token_allowed(&storage, &allowed).unwrap());
+        assert!(allowed_fee_tokens(&storage).unwrap().is_empty());
+
+        storage
+            .write(
+                &key,
+                BTreeMap::from([(allowed.clone(), Amount::from_u64(1))]),
+            )
+            .unwrap();
+        assert!(is_fee_token_allowed(&storage, &allowed).unwrap());
+        assert!(!is_fee_token_allowed(&storage, &disallowed).unwrap());
+        assert_eq!(allowed_fee_tokens(&storage).unwrap(), vec![allowed]);
+    }
+
+    #[test]
+    fn test_with_defaults_round_trip() {
+        let mut storage = TestWlStorage::default();
+        let params = Parameters::with_defaults(
+            crate::types::address::nam(),
+            Hash::sha256(b"vp_implicit"),
+        );
+
+        params.init_storage(&mut storage).unwrap();
+        assert_eq!(read(&storage).unwrap(), params);
+    }
+}
@@ -1,10 +1,11 @@
 //! Parameters storage
 
 use namada_macros::StorageKeys;
+use thiserror::Error;
 
 use super::ADDRESS;
 use crate::types::address::Address;
-use crate::types::storage::{DbKeySeg, Key};
+use crate::types::storage::{DbKeySeg, Epoch, Key, KeySeg};
 
 #[derive(StorageKeys)]
 struct Keys {
@@ -46,9 +47,89 @@ struct Keys {
     max_signatures_per_transaction: &'static str,
 }
 
+/// Sub-key under which the per-epoch snapshots of the parameters overwritten
+/// by the protocol are kept, apart from the current values of the parameters.
+const HISTORY_KEY_SEG: &str = "history";
+
+/// A storage key that is known to belong to the parameters' sub-space.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ParameterKey(Key);
+
+/// Error of a storage key that is not a parameter key
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("The storage key {0} is not a parameter key")]
+pub struct NotParameterKey(pub Key);
+
+impl TryFrom<Key> for ParameterKey {
+    type Error = NotParameterKey;
+
+    fn try_from(key: Key) -> Result<Self, Self::Error> {
+        if is_parameter_key(&key) {
+            Ok(Self(key))
+        } else {
+            Err(NotParameterKey(key))
+        }
+    }
+}
+
+impl From<ParameterKey> for Key {
+    fn from(ParameterKey(key): ParameterKey) -> Self {
+        key
+    }
+}
+
+impl AsRef<Key> for ParameterKey {
+    fn as_ref(&self) -> &Key {
+        &self.0
+    }
+}
+
+/// Implement a constructor on [`ParameterKey`] for each of the given
+/// parameters, from the getter generated by the `StorageKeys` derive.
+macro_rules! parameter_key_constructors {
+    ($($name:ident => $getter:ident),* $(,)?) => {
+        impl ParameterKey {
+            $(
+                #[doc = concat!("The `", stringify!($name), "` parameter key.")]
+                pub fn $name() -> Self {
+                    Self($getter(ADDRESS))
+                }
+            )*
+        }
+    };
+}
+
+parameter_key_constructors! {
+    eth_start_height => get_eth_start_height_key_at_addr,
+    active_status => get_active_status_key_at_addr,
+    min_confirmations => get_min_confirmations_key_at_addr,
+    native_erc20 => get_native_erc20_key_at_addr,
+    bridge_contract_address => get_bridge_contract_address_key_at_addr,
+    pos_inflation_amount => get_pos_inflation_amount_key_at_addr,
+    staked_ratio => get_staked_ratio_key_at_addr,
+    epoch_duration => get_epoch_duration_key_at_addr,
+    epochs_per_year => get_epochs_per_year_key_at_addr,
+    implicit_vp => get_implicit_vp_key_at_addr,
+    max_expected_time_per_block => get_max_expected_time_per_block_key_at_addr,
+    tx_whitelist => get_tx_whitelist_key_at_addr,
+    vp_whitelist => get_vp_whitelist_key_at_addr,
+    max_proposal_bytes => get_max_proposal_bytes_key_at_addr,
+    max_tx_bytes => get_max_tx_bytes_key_at_addr,
+    max_block_gas => get_max_block_gas_key_at_addr,
+    minimum_gas_price => get_minimum_gas_price_key_at_addr,
+    fee_unshielding_gas_limit => get_fee_unshielding_gas_limit_key_at_addr,
+    fee_unshielding_descriptions_limit =>
+        get_fee_unshielding_descriptions_limit_key_at_addr,
+    max_signatures_per_transaction =>
+        get_max_signatures_per_transaction_key_at_addr,
+}
+
 /// Returns if the key is a parameter key.
 pub fn is_parameter_key(key: &Key) -> bool {
-    matches!(&key.segments[0], DbKeySeg::AddressSeg(addr) if addr == &ADDRESS)
+    matches!(
+        key.segments.first(),
+        Some(DbKeySeg::AddressSeg(addr)) if addr == &ADDRESS
+    )
 }
 
 /// Returns if the key is a protocol parameter key.
@@ -64,6 +145,45 @@ pub fn is_protocol_parameter_key(key: &Key) -> bool {
     Keys::ALL.binary_search(&segment).is_ok()
 }
 
+/// Returns if the key is a PoS parameter key that is overwritten by the
+/// protocol every epoch, i.e. `pos_inflation_amount` or `staked_ratio`.
+pub fn is_protocol_updated_parameter_key(key: &Key) -> bool {
+    is_staked_ratio_key(key) || is_pos_inflation_amount_key(key)
+}
+
+/// Returns if the key is a core or Ethereum bridge protocol parameter key,
+/// i.e. a protocol parameter that is not overwritten by the protocol every
+/// epoch.
+pub fn is_core_protocol_parameter_key(key: &Key) -> bool {
+    is_protocol_parameter_key(key) && !is_protocol_updated_parameter_key(key)
+}
+
+/// Returns the keys of all the parameters, ordered by their
+/// sub-keys.
+pub fn all_parameter_keys() -> Vec<Key> {
+    Keys::ALL
+        .iter()
+        .map(|segment| Key {
+            segments: vec![
+                DbKeySeg::AddressSeg(ADDRESS),
+                DbKeySeg::StringSeg(segment.to_string()),
+            ],
+        })
+        .collect()
+}
+
+/// Returns if the key is a protocol parameter that cannot be changed by
+/// governance. These are either read only or overwritten by the protocol
+/// every epoch.
+pub fn is_read_only_parameter_key(key: &Key) -> bool {
+    is_epoch_duration_storage_key(key)
+        || is_max_expected_time_per_block_key(key)
+        || is_vp_whitelist_key(key)
+        || is_tx_whitelist_key(key)
+        || is_epochs_per_year_key(key)
+        || is_protocol_updated_parameter_key(key)
+}
+
 /// Returns if the key is an epoch storage key.
 pub fn is_epoch_duration_storage_key(key: &Key) -> bool {
     is_epoch_duration_key_at_addr(key, &ADDRESS)
@@ -164,6 +284,28 @@ pub fn get_pos_inflation_amount_key() -> Key {
     get_pos_inflation_amount_key_at_addr(ADDRESS)
 }
 
+/// Storage key used for the snapshot of the given parameter at the given
+/// epoch, under the history sub-key.
+fn get_history_key(parameter: &str, epoch: Epoch) -> Key {
+    Key::from(ADDRESS.to_db_key())
+        .push(&HISTORY_KEY_SEG.to_owned())
+        .and_then(|key| key.push(&parameter.to_owned()))
+        .and_then(|key| key.push(&epoch))
+        .expect("Cannot obtain a storage key")
+}
+
+/// Storage key used for the snapshot of the staked ratio parameter at the
+/// given epoch.
+pub fn get_staked_ratio_at_epoch_key(epoch: Epoch) -> Key {
+    get_history_key(Keys::VALUES.staked_ratio, epoch)
+}
+
+/// Storage key used for the snapshot of the inflation amount parameter at
+/// the given epoch.
+pub fn get_pos_inflation_amount_at_epoch_key(epoch: Epoch) -> Key {
+    get_history_key(Keys::VALUES.pos_inflation_amount, epoch)
+}
+
 /// Storage key used for the max proposal bytes.
 pub fn get_max_proposal_bytes_key() -> Key {
     get_max_proposal_bytes_key_at_addr(ADDRESS)
@@ -188,3 +330,69 @@ pub fn get_gas_cost_key() -> Key {
 pub fn get_max_signatures_per_transaction_key() -> Key {
     get_max_signatures_per_transaction_key_at_addr(ADDRESS)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_parameter_keys() {
+        let keys = all_parameter_keys();
+        // The number of fields of `Keys`
+        assert_eq!(keys.len(), 20);
+        assert_eq!(keys.len(), Keys::ALL.len());
+        assert!(keys.iter().all(is_protocol_parameter_key));
+        assert_eq!(
+            keys.iter()
+                .filter(|key| is_core_protocol_parameter_key(key))
+                .count(),
+            18
+        );
+        assert!(keys.contains(&get_epoch_duration_storage_key()));
+        assert!(keys.contains(&get_max_signatures_per_transaction_key()));
+    }
+
+    #[test]
+    fn test_is_core_protocol_parameter_key() {
+        // Core and Ethereum bridge parameters
+        assert!(is_core_protocol_parameter_key(
+            &get_epoch_duration_storage_key()
+        ));
+        assert!(is_core_protocol_parameter_key(
+            ParameterKey::native_erc20().as_ref()
+        ));
+        // PoS parameters overwritten by the protocol, which remain protocol
+        // parameters that governance cannot change
+        for key in [get_staked_ratio_key(), get_pos_inflation_amount_key()] {
+            assert!(!is_core_protocol_parameter_key(&key));
+            assert!(is_protocol_updated_parameter_key(&key));
+            assert!(is_protocol_parameter_key(&key));
+            assert!(is_read_only_parameter_key(&key));
+        }
+        // Unknown parameter sub-keys
+        let unknown = Key::from(ADDRESS.to_db_key())
+            .push(&"unknown".to_owned())
+            .unwrap();
+        assert!(!is_protocol_parameter_key(&unknown));
+        assert!(!is_core_protocol_parameter_key(&unknown));
+    }
+
+    #[test]
+    fn test_history_keys() {
+        let epoch = Epoch(1);
+        let history_keys = [
+            (get_staked_ratio_key(), get_staked_ratio_at_epoch_key(epoch)),
+            (
+                get_pos_inflation_amount_key(),
+                get_pos_inflation_amount_at_epoch_key(epoch),
+            ),
+        ];
+        for (live_key, history_key) in history_keys {
+            // The snapshots are kept apart from the current values
+            assert!(history_key.split_prefix(&live_key).is_none());
+            assert!(is_parameter_key(&history_key));
+            assert!(!is_protocol_parameter_key(&history_key));
+            assert!(!is_protocol_updated_parameter_key(&history_key));
+        }
+    }
+}
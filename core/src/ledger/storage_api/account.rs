@@ -1,7 +1,7 @@
 //! Cryptographic signature keys storage API
 
 use super::*;
-use crate::types::account::AccountPublicKeysMap;
+use crate::types::account::{Account, AccountPublicKeysMap};
 use crate::types::address::Address;
 use crate::types::key::*;
 use crate::types::storage::Key;
@@ -65,6 +65,25 @@ where
     Ok(AccountPublicKeysMap::from_iter(public_keys))
 }
 
+/// Get the account associated with an address, if it exists. A missing
+/// threshold defaults to 1.
+pub fn get_account<S>(storage: &S, owner: &Address) -> Result<Option<Account>>
+where
+    S: StorageRead,
+{
+    if !exists(storage, owner)? {
+        return Ok(None);
+    }
+    let public_keys_map = public_keys_index_map(storage, owner)?;
+    let threshold = threshold(storage, owner)?;
+
+    Ok(Some(Account {
+        public_keys_map,
+        threshold: threshold.unwrap_or(1),
+        address: owner.clone(),
+    }))
+}
+
 /// Check if a user account exists in storage
 pub fn exists<S>(storage: &S, owner: &Address) -> Result<bool>
 where
@@ -105,3 +124,38 @@ where
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::address::testing::established_address_1;
+    use crate::types::key::testing::common_sk_from_simple_seed;
+
+    /// Test that an account is read back from its sub-keys
+    #[test]
+    fn test_get_account() {
+        let mut storage = TestWlStorage::default();
+        let owner = established_address_1();
+        assert!(get_account(&storage, &owner).unwrap().is_none());
+
+        let public_keys = [
+            common_sk_from_simple_seed(0).ref_to(),
+            common_sk_from_simple_seed(1).ref_to(),
+        ];
+        init_account_storage(&mut storage, &owner, &public_keys, 2).unwrap();
+        storage
+            .write_bytes(&Key::validity_predicate(&owner), [])
+            .unwrap();
+
+        let account = get_account(&storage, &owner).unwrap().unwrap();
+        assert_eq!(account.address, owner);
+        assert_eq!(account.threshold, 2);
+        for (index, public_key) in public_keys.iter().enumerate() {
+            assert_eq!(
+                account.get_public_key_from_index(index as u8).as_ref(),
+                Some(public_key)
+            );
+        }
+    }
+}
@@ -18,3 +18,38 @@ where
 
     Ok(())
 }
+
+/// Check if the PK of an implicit account has been revealed
+pub fn has_revealed_pk<S>(storage: &S, owner: &Address) -> Result<bool>
+where
+    S: StorageRead,
+{
+    storage.has_key(&is_pk_revealed_key(owner))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::key::testing::keypair_1;
+
+    /// Test that a revealed PK is found under its implicit address
+    #[test]
+    fn test_has_revealed_pk() {
+        let mut storage = TestWlStorage::default();
+        let public_key = keypair_1().ref_to();
+        let owner: Address = (&public_key).into();
+        assert!(matches!(owner, Address::Implicit(_)));
+
+        let key = is_pk_revealed_key(&owner);
+        assert!(is_pks_key(&key).is_some());
+        assert!(!has_revealed_pk(&storage, &owner).unwrap());
+
+        reveal_pk(&mut storage, &public_key).unwrap();
+        assert!(has_revealed_pk(&storage, &owner).unwrap());
+        assert_eq!(
+            storage.read::<common::PublicKey>(&key).unwrap(),
+            Some(public_key)
+        );
+    }
+}
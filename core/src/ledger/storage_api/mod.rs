@@ -8,6 +8,7 @@ pub mod governance;
 pub mod key;
 pub mod pgf;
 pub mod token;
+pub mod tracer;
 pub mod tx;
 pub mod validation;
 
@@ -62,6 +63,39 @@ pub trait StorageRead {
     /// Storage `has_key` in. It will try to read from the storage.
     fn has_key(&self, key: &storage::Key) -> Result<bool>;
 
+    /// Storage `has_key` of the key made of the given prefix followed by the
+    /// given segments.
+    fn has_key_with_segments(
+        &self,
+        prefix: &storage::Key,
+        segments: &[storage::DbKeySeg],
+    ) -> Result<bool> {
+        let mut key = prefix.clone();
+        key.segments.extend_from_slice(segments);
+        self.has_key(&key)
+    }
+
+    /// List the keys with a matching prefix that would be deleted by
+    /// [`StorageWrite::delete_prefix`], i.e. all of them except for the
+    /// validity predicate keys.
+    fn list_prefix_deletable(
+        &self,
+        prefix: &storage::Key,
+    ) -> Result<Vec<storage::Key>>
+    where
+        Self: Sized,
+    {
+        iter_prefix_bytes(self, prefix)?
+            .filter_map(|res| match res {
+                // Skip validity predicates as they cannot be deleted
+                Ok((key, _val)) if key.is_validity_predicate().is_some() => {
+                    None
+                }
+                res => Some(res.map(|(key, _val)| key)),
+            })
+            .collect()
+    }
+
     /// Storage prefix iterator ordered by the storage keys. It will try to get
     /// an iterator from the storage.
     ///
@@ -115,6 +149,42 @@ pub trait StorageWrite {
         self.write_bytes(key, bytes)
     }
 
+    /// Write a value to be encoded with Borsh at the given key to storage,
+    /// charging `gas_per_byte` for each byte of the encoded value with the
+    /// given `charge` function before writing it. The value is only encoded
+    /// once.
+    fn write_metered<T: BorshSerialize>(
+        &mut self,
+        key: &storage::Key,
+        val: T,
+        gas_per_byte: u64,
+        charge: &mut impl FnMut(u64),
+    ) -> Result<()> {
+        let bytes = val.serialize_to_vec();
+        charge(gas_per_byte.saturating_mul(bytes.len() as u64));
+        self.write_bytes(key, bytes)
+    }
+
+    /// Write a value to be encoded with Borsh at the given key to storage,
+    /// only if it differs from the value that is currently stored. Returns
+    /// whether the value was written. This avoids adding the key to the set
+    /// of changed keys that the VPs have to validate.
+    fn write_if_changed<T>(
+        &mut self,
+        key: &storage::Key,
+        val: T,
+    ) -> Result<bool>
+    where
+        Self: StorageRead,
+        T: BorshSerialize + BorshDeserialize + PartialEq,
+    {
+        if self.read::<T>(key)?.as_ref() == Some(&val) {
+            return Ok(false);
+        }
+        self.write(key, val)?;
+        Ok(true)
+    }
+
     /// Write a value as bytes at the given key to storage.
     fn write_bytes(
         &mut self,
@@ -130,17 +200,8 @@ pub trait StorageWrite {
     where
         Self: StorageRead + Sized,
     {
-        let keys = iter_prefix_bytes(self, prefix)?
-            .map(|res| {
-                let (key, _val) = res?;
-                Ok(key)
-            })
-            .collect::<Result<Vec<storage::Key>>>();
-        for key in keys? {
-            // Skip validity predicates as they cannot be deleted
-            if key.is_validity_predicate().is_none() {
-                self.delete(&key)?;
-            }
+        for key in self.list_prefix_deletable(prefix)? {
+            self.delete(&key)?;
         }
         Ok(())
     }
@@ -174,6 +235,60 @@ pub fn iter_prefix_bytes<'a>(
     Ok(iter)
 }
 
+/// Iterate the raw items that are immediate children of the given prefix,
+/// i.e. whose keys have exactly one more segment than the prefix, ordered by
+/// the storage keys. Deeper descendants of the prefix are skipped.
+pub fn iter_prefix_immediate<'a>(
+    storage: &'a impl StorageRead,
+    prefix: &crate::types::storage::Key,
+) -> Result<impl Iterator<Item = Result<(storage::Key, Vec<u8>)>> + 'a> {
+    let depth = prefix.segments.len() + 1;
+    let iter = iter_prefix_bytes(storage, prefix)?.filter(move |item| {
+        // Keep errors, such that they are propagated
+        item.as_ref()
+            .map_or(true, |(key, _)| key.segments.len() == depth)
+    });
+    Ok(iter)
+}
+
+/// Move all the items under the `from` prefix to the `to` prefix, preserving
+/// their key suffixes, and return the number of migrated items. Validity
+/// predicate keys are left in place. Fails without touching the storage if
+/// the prefixes are equal or one is nested under the other, as the migrated
+/// items would then overwrite or delete each other.
+pub fn migrate_prefix<S>(
+    storage: &mut S,
+    from: &storage::Key,
+    to: &storage::Key,
+) -> Result<u64>
+where
+    S: StorageRead + StorageWrite,
+{
+    if from.split_prefix(to).is_some() || to.split_prefix(from).is_some() {
+        return Err(Error::new_const(
+            "Cannot migrate storage items between overlapping prefixes",
+        ));
+    }
+    let items = iter_prefix_bytes(storage, from)?
+        .filter(|item| {
+            item.as_ref()
+                .map_or(true, |(key, _)| key.is_validity_predicate().is_none())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut migrated = 0_u64;
+    for (key, val) in items {
+        let new_key = match key.split_prefix(from) {
+            Some(Some(suffix)) => to.join(&suffix),
+            Some(None) => to.clone(),
+            None => continue,
+        };
+        storage.write_bytes(&new_key, val)?;
+        storage.delete(&key)?;
+        migrated += 1;
+    }
+    Ok(migrated)
+}
+
 /// Iterate Borsh encoded items matching the given prefix, ordered by the
 /// storage keys.
 pub fn iter_prefix<'a, T>(
@@ -272,3 +387,263 @@ where
     });
     Ok(iter)
 }
+
+/// Iterate Borsh encoded items matching the given prefix, with the storage
+/// keys parsed into a domain type using the given `parse_key` function,
+/// ordered by the storage keys.
+///
+/// The items whose key cannot be parsed (i.e. `parse_key` returns `None`) are
+/// skipped with a logged warning and their values are not decoded.
+pub fn iter_prefix_parsed<'a, K, T, P>(
+    storage: &'a impl StorageRead,
+    prefix: &crate::types::storage::Key,
+    parse_key: P,
+) -> Result<impl Iterator<Item = Result<(K, T)>> + 'a>
+where
+    T: BorshDeserialize,
+    P: Fn(&storage::Key) -> Option<K> + 'a,
+{
+    let iter = storage.iter_prefix(prefix)?;
+    let iter = itertools::unfold(iter, move |iter| {
+        // The loop is for skipping the keys that cannot be parsed - we
+        // `continue` when the current key fails to parse.
+        loop {
+            match storage.iter_next(iter) {
+                Ok(Some((key, val))) => {
+                    let key =
+                        match storage::Key::parse(key).into_storage_result() {
+                            Ok(key) => key,
+                            Err(err) => {
+                                // Propagate key encoding errors into Iterator's
+                                // Item
+                                return Some(Err(err));
+                            }
+                        };
+                    let parsed_key = match parse_key(&key) {
+                        Some(parsed_key) => parsed_key,
+                        None => {
+                            tracing::warn!(
+                                "Skipping the storage key {key} that couldn't \
+                                 be parsed"
+                            );
+                            continue;
+                        }
+                    };
+                    let val =
+                        match T::try_from_slice(&val).into_storage_result() {
+                            Ok(val) => val,
+                            Err(err) => {
+                                // Propagate val encoding errors into Iterator's
+                                // Item
+                                return Some(Err(err));
+                            }
+                        };
+                    return Some(Ok((parsed_key, val)));
+                }
+                Ok(None) => return None,
+                Err(err) => {
+                    // Propagate `iter_next` errors into Iterator's Item
+                    return Some(Err(err));
+                }
+            }
+        }
+    });
+    Ok(iter)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::governance::storage::keys as governance_keys;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::storage::DbKeySeg;
+
+    /// Test that `iter_prefix_parsed` yields the parsed proposal ids and
+    /// skips the keys whose trailing segment isn't an id.
+    #[test]
+    fn test_iter_prefix_parsed_proposal_ids() {
+        let mut wl_storage = TestWlStorage::default();
+        let epoch = 3;
+
+        for id in [0_u64, 2, 7] {
+            let key = governance_keys::get_committing_proposals_key(id, epoch);
+            wl_storage.write(&key, id).unwrap();
+        }
+        let prefix = governance_keys::get_commiting_proposals_prefix(epoch);
+        let bad_key = prefix.push(&"not-an-id".to_owned()).unwrap();
+        wl_storage.write(&bad_key, u64::MAX).unwrap();
+
+        let parse_id = |key: &storage::Key| match key.last() {
+            Some(DbKeySeg::StringSeg(id)) => id.parse::<u64>().ok(),
+            _ => None,
+        };
+        let parsed =
+            iter_prefix_parsed::<u64, u64, _>(&wl_storage, &prefix, parse_id)
+                .unwrap()
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+
+        assert_eq!(parsed, vec![(0, 0), (2, 2), (7, 7)]);
+    }
+
+    /// Test that a metered write charges the gas for every encoded byte and
+    /// writes the value
+    #[test]
+    fn test_write_metered() {
+        let mut wl_storage = TestWlStorage::default();
+        let key = storage::Key::parse("metered").unwrap();
+        let val = vec![1_u64, 2, 3];
+        let gas_per_byte = 7;
+
+        let mut charged = 0;
+        wl_storage
+            .write_metered(&key, &val, gas_per_byte, &mut |gas| charged += gas)
+            .unwrap();
+
+        assert_eq!(charged, gas_per_byte * val.serialize_to_vec().len() as u64);
+        assert_eq!(wl_storage.read::<Vec<u64>>(&key).unwrap(), Some(val));
+    }
+
+    /// Test that probing a prefix with extra segments matches `has_key` on the
+    /// composite key
+    #[test]
+    fn test_has_key_with_segments() {
+        let mut wl_storage = TestWlStorage::default();
+        let prefix = storage::Key::parse("prefix").unwrap();
+        let owner = crate::types::address::nam();
+        let present = [
+            DbKeySeg::StringSeg("sub".to_owned()),
+            DbKeySeg::AddressSeg(owner.clone()),
+        ];
+        let missing = [
+            DbKeySeg::StringSeg("other".to_owned()),
+            DbKeySeg::AddressSeg(owner),
+        ];
+        let composite = |segments: &[DbKeySeg]| {
+            let mut key = prefix.clone();
+            key.segments.extend_from_slice(segments);
+            key
+        };
+        wl_storage.write(&composite(&present), 1_u64).unwrap();
+
+        for segments in [&present[..], &missing[..], &[]] {
+            assert_eq!(
+                wl_storage.has_key_with_segments(&prefix, segments).unwrap(),
+                wl_storage.has_key(&composite(segments)).unwrap()
+            );
+        }
+        assert!(wl_storage.has_key_with_segments(&prefix, &present).unwrap());
+        assert!(!wl_storage.has_key_with_segments(&prefix, &missing).unwrap());
+    }
+
+    #[test]
+    fn test_iter_prefix_immediate() {
+        let mut wl_storage = TestWlStorage::default();
+        let prefix = storage::Key::parse("prefix").unwrap();
+        let child_a = prefix.push(&"a".to_owned()).unwrap();
+        let child_b = prefix.push(&"b".to_owned()).unwrap();
+        let grandchild = child_a.push(&"nested".to_owned()).unwrap();
+        let unrelated = storage::Key::parse("other/a").unwrap();
+        for key in [&child_a, &child_b, &grandchild, &unrelated] {
+            wl_storage.write(key, 1_u64).unwrap();
+        }
+
+        let keys: Vec<storage::Key> =
+            iter_prefix_immediate(&wl_storage, &prefix)
+                .unwrap()
+                .map(|item| item.unwrap().0)
+                .collect();
+        assert_eq!(keys, vec![child_a, child_b]);
+    }
+
+    #[test]
+    fn test_migrate_prefix() {
+        let mut wl_storage = TestWlStorage::default();
+        let from = storage::Key::parse("old").unwrap();
+        let to = storage::Key::parse("new/prefix").unwrap();
+        let suffixes = [
+            storage::Key::parse("a").unwrap(),
+            storage::Key::parse("b/nested").unwrap(),
+        ];
+        for (i, suffix) in suffixes.iter().enumerate() {
+            wl_storage.write(&from.join(suffix), i as u64).unwrap();
+        }
+        let unrelated = storage::Key::parse("older/a").unwrap();
+        wl_storage.write(&unrelated, 42_u64).unwrap();
+
+        let migrated = migrate_prefix(&mut wl_storage, &from, &to).unwrap();
+        assert_eq!(migrated, suffixes.len() as u64);
+
+        for (i, suffix) in suffixes.iter().enumerate() {
+            assert_eq!(
+                wl_storage.read::<u64>(&to.join(suffix)).unwrap(),
+                Some(i as u64)
+            );
+            assert!(!wl_storage.has_key(&from.join(suffix)).unwrap());
+        }
+        assert_eq!(wl_storage.read::<u64>(&unrelated).unwrap(), Some(42));
+    }
+
+    #[test]
+    fn test_migrate_prefix_overlapping() {
+        let mut wl_storage = TestWlStorage::default();
+        let from = storage::Key::parse("old").unwrap();
+        let nested = storage::Key::parse("old/nested").unwrap();
+        let key = from.join(&storage::Key::parse("a").unwrap());
+        wl_storage.write(&key, 1_u64).unwrap();
+
+        // Migrating to the same prefix or to an overlapping one is rejected
+        // and the items are left untouched
+        for (from, to) in [(&from, &from), (&from, &nested), (&nested, &from)] {
+            assert!(migrate_prefix(&mut wl_storage, from, to).is_err());
+            assert_eq!(wl_storage.read::<u64>(&key).unwrap(), Some(1));
+        }
+    }
+
+    #[test]
+    fn test_list_prefix_deletable() {
+        let mut wl_storage = TestWlStorage::default();
+        let owner = crate::types::address::testing::established_address_1();
+        let prefix = storage::Key::from(DbKeySeg::AddressSeg(owner.clone()));
+        let vp_key = storage::Key::validity_predicate(&owner);
+        let regular = [
+            prefix.push(&"a".to_owned()).unwrap(),
+            prefix.push(&"b".to_owned()).unwrap(),
+        ];
+        wl_storage.write_bytes(&vp_key, [1_u8]).unwrap();
+        for key in &regular {
+            wl_storage.write(key, 1_u64).unwrap();
+        }
+
+        let deletable = wl_storage.list_prefix_deletable(&prefix).unwrap();
+        assert!(!deletable.contains(&vp_key));
+        assert_eq!(deletable, regular.to_vec());
+
+        wl_storage.delete_prefix(&prefix).unwrap();
+        assert!(wl_storage.has_key(&vp_key).unwrap());
+        assert!(
+            wl_storage
+                .list_prefix_deletable(&prefix)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    /// Test that writing a value identical to the stored one doesn't touch
+    /// the storage
+    #[test]
+    fn test_write_if_changed() {
+        let mut wl_storage = TestWlStorage::default();
+        let key = storage::Key::parse("changed").unwrap();
+
+        assert!(wl_storage.write_if_changed(&key, 1_u64).unwrap());
+        wl_storage.commit_tx();
+
+        assert!(!wl_storage.write_if_changed(&key, 1_u64).unwrap());
+        assert!(wl_storage.write_log.get_keys().is_empty());
+
+        assert!(wl_storage.write_if_changed(&key, 2_u64).unwrap());
+        assert_eq!(wl_storage.write_log.get_keys(), [key.clone()].into());
+        assert_eq!(wl_storage.read::<u64>(&key).unwrap(), Some(2));
+    }
+}
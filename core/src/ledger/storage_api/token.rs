@@ -1,6 +1,10 @@
 //! Token storage_api functions
 
-use super::{StorageRead, StorageWrite};
+use std::collections::BTreeMap;
+
+use thiserror::Error;
+
+use super::{OptionExt, StorageRead, StorageWrite};
 use crate::ledger::storage_api;
 use crate::types::address::{Address, InternalAddress};
 use crate::types::token;
@@ -9,6 +13,47 @@ pub use crate::types::token::{
     minter_key, Amount, Change,
 };
 
+/// Error of a transfer from a source with an insufficient balance. It can be
+/// recovered from a [`storage_api::Error`] with
+/// [`storage_api::Error::downcast`].
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Insufficient source balance")]
+pub struct InsufficientFunds {
+    pub token: Address,
+    pub owner: Address,
+    pub balance: token::Amount,
+    pub amount: token::Amount,
+}
+
+/// Error of an amount whose denomination doesn't match the one of its token.
+/// It can be recovered from a [`storage_api::Error`] with
+/// [`storage_api::Error::downcast`].
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error(
+    "The denomination {} of the amount doesn't match the denomination {} of \
+     the token {token}",
+    found.0,
+    expected.0
+)]
+pub struct DenominationMismatch {
+    pub token: Address,
+    pub expected: token::Denomination,
+    pub found: token::Denomination,
+}
+
+/// A change of the balance of a given token and owner, as recorded by
+/// [`write_balance_tracked`].
+#[allow(missing_docs)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BalanceChange {
+    pub token: Address,
+    pub owner: Address,
+    pub old: token::Amount,
+    pub new: token::Amount,
+}
+
 /// Read the balance of a given token and owner.
 pub fn read_balance<S>(
     storage: &S,
@@ -23,6 +68,27 @@ where
     Ok(balance)
 }
 
+/// Read the balances of the given tokens for an owner. The tokens for which the
+/// owner has no balance entry in storage are omitted from the result, so that
+/// the tokens that were never held are not listed with a zero balance.
+pub fn read_balances<S>(
+    storage: &S,
+    owner: &Address,
+    tokens: &[Address],
+) -> storage_api::Result<BTreeMap<Address, token::Amount>>
+where
+    S: StorageRead,
+{
+    let mut balances = BTreeMap::new();
+    for token in tokens {
+        let key = token::balance_key(token, owner);
+        if let Some(balance) = storage.read::<token::Amount>(&key)? {
+            balances.insert(token.clone(), balance);
+        }
+    }
+    Ok(balances)
+}
+
 /// Read the total network supply of a given token.
 pub fn read_total_supply<S>(
     storage: &S,
@@ -75,6 +141,70 @@ where
     })
 }
 
+/// Get the amount of a denominated amount of the given token, whose
+/// denomination must be the one of the token in storage. Unlike
+/// [`token::DenominatedAmount::to_amount`], an amount with another
+/// denomination is rejected rather than converted.
+pub fn denominated_amount_checked<S>(
+    storage: &S,
+    token: &Address,
+    amount: token::DenominatedAmount,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead,
+{
+    let denom = read_denom(storage, token)?.ok_or_else(|| {
+        storage_api::Error::new_const(
+            "No denomination found in storage for the given token",
+        )
+    })?;
+    if amount.denom() != denom {
+        return Err(storage_api::Error::new(DenominationMismatch {
+            token: token.clone(),
+            expected: denom,
+            found: amount.denom(),
+        }));
+    }
+    Ok(amount.amount())
+}
+
+/// Read the balance of a given token and owner, denominated with the token's
+/// decimal places as given by [`read_denom`].
+pub fn read_denominated_balance<S>(
+    storage: &S,
+    token: &Address,
+    owner: &Address,
+) -> storage_api::Result<token::DenominatedAmount>
+where
+    S: StorageRead,
+{
+    let balance = read_balance(storage, token, owner)?;
+    let denom = read_denom(storage, token)?
+        .ok_or_err_msg("No denomination found for the token")?;
+    Ok(token::DenominatedAmount::new(balance, denom))
+}
+
+/// Write a new balance of a given token and owner. Returns the change from the
+/// prior balance, so that the caller can emit it.
+pub fn write_balance_tracked<S>(
+    storage: &mut S,
+    token: &Address,
+    owner: &Address,
+    new: token::Amount,
+) -> storage_api::Result<BalanceChange>
+where
+    S: StorageRead + StorageWrite,
+{
+    let old = read_balance(storage, token, owner)?;
+    storage.write(&token::balance_key(token, owner), new)?;
+    Ok(BalanceChange {
+        token: token.clone(),
+        owner: owner.clone(),
+        old,
+        new,
+    })
+}
+
 /// Write the denomination of a given token.
 pub fn write_denom<S>(
     storage: &mut S,
@@ -88,9 +218,38 @@ where
     storage.write(&key, denom)
 }
 
+/// Check that the total minted supply of a given token equals the sum of all
+/// of its balances. This is a consistency check meant for tests and audits.
+pub fn verify_supply_invariant<S>(
+    storage: &S,
+    token: &Address,
+) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    let prefix = token::balance_prefix(token);
+    let balances = storage_api::iter_prefix_with_filter::<token::Amount, _>(
+        storage,
+        &prefix,
+        |key| token::is_balance_key(token, key).is_some(),
+    )?;
+    let mut sum = token::Amount::zero();
+    for balance in balances {
+        let (_key, balance) = balance?;
+        match sum.checked_add(balance) {
+            Some(new_sum) => sum = new_sum,
+            // The balances cannot add up to any valid supply
+            None => return Ok(false),
+        }
+    }
+    Ok(sum == read_total_supply(storage, token)?)
+}
+
 /// Transfer `token` from `src` to `dest`. Returns an `Err` if `src` has
-/// insufficient balance or if the transfer the `dest` would overflow (This can
-/// only happen if the total supply doesn't fit in `token::Amount`).
+/// insufficient balance (with an [`InsufficientFunds`] error) or if the
+/// transfer the `dest` would overflow (This can only happen if the total supply
+/// doesn't fit in `token::Amount`). A transfer to the source itself doesn't
+/// change any balance, but the source balance is still validated.
 pub fn transfer<S>(
     storage: &mut S,
     token: &Address,
@@ -106,26 +265,79 @@ where
     }
     let src_key = token::balance_key(token, src);
     let src_balance = read_balance(storage, token, src)?;
-    match src_balance.checked_sub(amount) {
-        Some(new_src_balance) => {
-            let dest_key = token::balance_key(token, dest);
-            let dest_balance = read_balance(storage, token, dest)?;
-            match dest_balance.checked_add(amount) {
-                Some(new_dest_balance) => {
-                    storage.write(&src_key, new_src_balance)?;
-                    storage.write(&dest_key, new_dest_balance)
-                }
-                None => Err(storage_api::Error::new_const(
-                    "The transfer would overflow destination balance",
-                )),
-            }
-        }
-        None => {
-            Err(storage_api::Error::new_const("Insufficient source balance"))
+    let new_src_balance = src_balance.checked_sub(amount).ok_or_else(|| {
+        storage_api::Error::new(InsufficientFunds {
+            token: token.clone(),
+            owner: src.clone(),
+            balance: src_balance,
+            amount,
+        })
+    })?;
+    if src == dest {
+        return Ok(());
+    }
+    let dest_key = token::balance_key(token, dest);
+    let dest_balance = read_balance(storage, token, dest)?;
+    match dest_balance.checked_add(amount) {
+        Some(new_dest_balance) => {
+            storage.write(&src_key, new_src_balance)?;
+            storage.write(&dest_key, new_dest_balance)
         }
+        None => Err(storage_api::Error::new_const(
+            "The transfer would overflow destination balance",
+        )),
     }
 }
 
+/// Transfer the full balance of `token` from `src` to `dest`. Returns the
+/// transferred amount, which is zero if `src` has no balance.
+pub fn transfer_all<S>(
+    storage: &mut S,
+    token: &Address,
+    src: &Address,
+    dest: &Address,
+) -> storage_api::Result<token::Amount>
+where
+    S: StorageRead + StorageWrite,
+{
+    let amount = read_balance(storage, token, src)?;
+    transfer(storage, token, src, dest, amount)?;
+    Ok(amount)
+}
+
+/// Escrow `amount` of `token` from `from` into the `escrow` internal account.
+/// Returns an [`InsufficientFunds`] error if `from` lacks the funds.
+pub fn escrow<S>(
+    storage: &mut S,
+    token: &Address,
+    from: &Address,
+    escrow: &InternalAddress,
+    amount: token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let escrow = Address::Internal(escrow.clone());
+    transfer(storage, token, from, &escrow, amount)
+}
+
+/// Release `amount` of `token` from the `escrow` internal account to `to`.
+/// Returns an [`InsufficientFunds`] error if more than the escrowed balance is
+/// released.
+pub fn unescrow<S>(
+    storage: &mut S,
+    token: &Address,
+    escrow: &InternalAddress,
+    to: &Address,
+    amount: token::Amount,
+) -> storage_api::Result<()>
+where
+    S: StorageRead + StorageWrite,
+{
+    let escrow = Address::Internal(escrow.clone());
+    transfer(storage, token, &escrow, to, amount)
+}
+
 /// Credit tokens to an account, to be used only by protocol. In transactions,
 /// this would get rejected by the default `vp_token`.
 pub fn credit_tokens<S>(
@@ -179,10 +391,403 @@ where
         }
     };
 
-    let total_supply = read_total_supply(&*storage, source)?;
+    let total_supply = read_total_supply(&*storage, token)?;
     let new_total_supply =
         total_supply.checked_sub(amount_to_burn).unwrap_or_default();
 
     let total_supply_key = token::minted_balance_key(token);
     storage.write(&total_supply_key, new_total_supply)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::types::address::nam;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+
+    #[test]
+    fn test_transfer_all() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let src = established_address_1();
+        let dest = established_address_2();
+
+        let amount = Amount::native_whole(100);
+        credit_tokens(&mut storage, &token, &src, amount).unwrap();
+
+        let moved = transfer_all(&mut storage, &token, &src, &dest).unwrap();
+        assert_eq!(moved, amount);
+        assert_eq!(
+            read_balance(&storage, &token, &src).unwrap(),
+            Amount::zero()
+        );
+        assert_eq!(read_balance(&storage, &token, &dest).unwrap(), amount);
+
+        // Sweeping an empty balance is a no-op
+        let moved = transfer_all(&mut storage, &token, &src, &dest).unwrap();
+        assert_eq!(moved, Amount::zero());
+        assert_eq!(read_balance(&storage, &token, &dest).unwrap(), amount);
+    }
+
+    #[test]
+    fn test_read_total_supply() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+
+        // The supply of a token that was never minted is zero
+        assert_eq!(
+            read_total_supply(&storage, &token).unwrap(),
+            Amount::zero()
+        );
+
+        let supply = Amount::native_whole(1_000);
+        storage
+            .write(&token::minted_balance_key(&token), supply)
+            .unwrap();
+        assert_eq!(read_total_supply(&storage, &token).unwrap(), supply);
+    }
+
+    #[test]
+    fn test_transfer() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let src = established_address_1();
+        let dest = established_address_2();
+
+        credit_tokens(&mut storage, &token, &src, Amount::native_whole(100))
+            .unwrap();
+        transfer(&mut storage, &token, &src, &dest, Amount::native_whole(40))
+            .unwrap();
+        assert_eq!(
+            read_balance(&storage, &token, &src).unwrap(),
+            Amount::native_whole(60)
+        );
+        assert_eq!(
+            read_balance(&storage, &token, &dest).unwrap(),
+            Amount::native_whole(40)
+        );
+    }
+
+    #[test]
+    fn test_transfer_insufficient_funds() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let src = established_address_1();
+        let dest = established_address_2();
+
+        let balance = Amount::native_whole(10);
+        credit_tokens(&mut storage, &token, &src, balance).unwrap();
+        let amount = Amount::native_whole(11);
+        let err = transfer(&mut storage, &token, &src, &dest, amount)
+            .unwrap_err()
+            .downcast::<InsufficientFunds>()
+            .unwrap();
+        assert_eq!(
+            *err,
+            InsufficientFunds {
+                token: token.clone(),
+                owner: src.clone(),
+                balance,
+                amount,
+            }
+        );
+        // No balance has changed
+        assert_eq!(read_balance(&storage, &token, &src).unwrap(), balance);
+        assert_eq!(
+            read_balance(&storage, &token, &dest).unwrap(),
+            Amount::zero()
+        );
+    }
+
+    #[test]
+    fn test_self_transfer() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let owner = established_address_1();
+
+        let balance = Amount::native_whole(10);
+        credit_tokens(&mut storage, &token, &owner, balance).unwrap();
+        transfer(&mut storage, &token, &owner, &owner, balance).unwrap();
+        assert_eq!(read_balance(&storage, &token, &owner).unwrap(), balance);
+
+        // The source balance is still validated
+        let err = transfer(
+            &mut storage,
+            &token,
+            &owner,
+            &owner,
+            Amount::native_whole(11),
+        )
+        .unwrap_err();
+        assert!(err.downcast::<InsufficientFunds>().is_ok());
+        assert_eq!(read_balance(&storage, &token, &owner).unwrap(), balance);
+    }
+
+    #[test]
+    fn test_read_balances() {
+        let mut storage = TestWlStorage::default();
+        let owner = established_address_1();
+        let tokens = [nam(), established_address_2(), established_address_3()];
+
+        credit_tokens(
+            &mut storage,
+            &tokens[0],
+            &owner,
+            Amount::native_whole(1),
+        )
+        .unwrap();
+        credit_tokens(
+            &mut storage,
+            &tokens[2],
+            &owner,
+            Amount::native_whole(3),
+        )
+        .unwrap();
+
+        let balances = read_balances(&storage, &owner, &tokens).unwrap();
+        assert_eq!(
+            balances,
+            BTreeMap::from([
+                (tokens[0].clone(), Amount::native_whole(1)),
+                (tokens[2].clone(), Amount::native_whole(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_escrow_unescrow_round_trip() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let owner = established_address_1();
+        let escrow_addr = InternalAddress::EthBridgePool;
+        let escrow_account = Address::Internal(escrow_addr.clone());
+
+        let balance = Amount::native_whole(10);
+        credit_tokens(&mut storage, &token, &owner, balance).unwrap();
+
+        let amount = Amount::native_whole(4);
+        escrow(&mut storage, &token, &owner, &escrow_addr, amount).unwrap();
+        assert_eq!(
+            read_balance(&storage, &token, &owner).unwrap(),
+            Amount::native_whole(6)
+        );
+        assert_eq!(
+            read_balance(&storage, &token, &escrow_account).unwrap(),
+            amount
+        );
+
+        unescrow(&mut storage, &token, &escrow_addr, &owner, amount).unwrap();
+        assert_eq!(read_balance(&storage, &token, &owner).unwrap(), balance);
+        assert_eq!(
+            read_balance(&storage, &token, &escrow_account).unwrap(),
+            Amount::zero()
+        );
+    }
+
+    #[test]
+    fn test_unescrow_more_than_escrowed() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let owner = established_address_1();
+        let escrow_addr = InternalAddress::EthBridgePool;
+
+        let amount = Amount::native_whole(4);
+        credit_tokens(&mut storage, &token, &owner, amount).unwrap();
+        escrow(&mut storage, &token, &owner, &escrow_addr, amount).unwrap();
+
+        let err = unescrow(
+            &mut storage,
+            &token,
+            &escrow_addr,
+            &owner,
+            Amount::native_whole(5),
+        )
+        .unwrap_err()
+        .downcast::<InsufficientFunds>()
+        .unwrap();
+        assert_eq!(err.owner, Address::Internal(escrow_addr));
+        assert_eq!(err.balance, amount);
+        assert_eq!(
+            read_balance(&storage, &token, &owner).unwrap(),
+            Amount::zero()
+        );
+    }
+
+    #[test]
+    fn test_read_denominated_balance() {
+        let mut storage = TestWlStorage::default();
+        let token = established_address_2();
+        let owner = established_address_1();
+
+        let balance = Amount::from_u64(1_500_000);
+        credit_tokens(&mut storage, &nam(), &owner, balance).unwrap();
+        credit_tokens(&mut storage, &token, &owner, balance).unwrap();
+
+        // The native token is denominated with its max decimal places
+        let denominated =
+            read_denominated_balance(&storage, &nam(), &owner).unwrap();
+        assert_eq!(
+            denominated,
+            token::DenominatedAmount::new(
+                balance,
+                token::NATIVE_MAX_DECIMAL_PLACES.into()
+            )
+        );
+        assert_eq!(denominated.to_string(), "1.5");
+
+        write_denom(&mut storage, &token, 3u8.into()).unwrap();
+        let denominated =
+            read_denominated_balance(&storage, &token, &owner).unwrap();
+        assert_eq!(
+            denominated,
+            token::DenominatedAmount::new(balance, 3u8.into())
+        );
+        assert_eq!(denominated.to_string(), "1500");
+    }
+
+    #[test]
+    fn test_write_balance_tracked() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let owner = established_address_1();
+
+        let credit = write_balance_tracked(
+            &mut storage,
+            &token,
+            &owner,
+            Amount::native_whole(10),
+        )
+        .unwrap();
+        let debit = write_balance_tracked(
+            &mut storage,
+            &token,
+            &owner,
+            Amount::native_whole(7),
+        )
+        .unwrap();
+        assert_eq!(
+            [credit, debit],
+            [
+                BalanceChange {
+                    token: token.clone(),
+                    owner: owner.clone(),
+                    old: Amount::zero(),
+                    new: Amount::native_whole(10),
+                },
+                BalanceChange {
+                    token: token.clone(),
+                    owner: owner.clone(),
+                    old: Amount::native_whole(10),
+                    new: Amount::native_whole(7),
+                },
+            ]
+        );
+        assert_eq!(
+            read_balance(&storage, &token, &owner).unwrap(),
+            Amount::native_whole(7)
+        );
+    }
+
+    #[test]
+    fn test_verify_supply_invariant() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let owner = established_address_1();
+        let other = established_address_2();
+
+        // A token without any balances nor supply is consistent
+        assert!(verify_supply_invariant(&storage, &token).unwrap());
+
+        credit_tokens(&mut storage, &token, &owner, Amount::native_whole(10))
+            .unwrap();
+        credit_tokens(&mut storage, &token, &other, Amount::native_whole(5))
+            .unwrap();
+        transfer(
+            &mut storage,
+            &token,
+            &owner,
+            &other,
+            Amount::native_whole(3),
+        )
+        .unwrap();
+        burn(&mut storage, &token, &other, Amount::native_whole(2)).unwrap();
+        assert!(verify_supply_invariant(&storage, &token).unwrap());
+
+        // Balances of other tokens are not counted
+        credit_tokens(
+            &mut storage,
+            &established_address_3(),
+            &owner,
+            Amount::native_whole(1),
+        )
+        .unwrap();
+        assert!(verify_supply_invariant(&storage, &token).unwrap());
+
+        // A balance written without minting breaks the invariant
+        storage
+            .write(
+                &token::balance_key(&token, &established_address_3()),
+                Amount::native_whole(1),
+            )
+            .unwrap();
+        assert!(!verify_supply_invariant(&storage, &token).unwrap());
+    }
+
+    #[test]
+    fn test_burn_keeps_remaining_supply() {
+        let mut storage = TestWlStorage::default();
+        let token = nam();
+        let owner = established_address_1();
+        let other = established_address_2();
+
+        credit_tokens(&mut storage, &token, &owner, Amount::native_whole(10))
+            .unwrap();
+        credit_tokens(&mut storage, &token, &other, Amount::native_whole(5))
+            .unwrap();
+        burn(&mut storage, &token, &owner, Amount::native_whole(4)).unwrap();
+
+        assert_eq!(
+            read_balance(&storage, &token, &owner).unwrap(),
+            Amount::native_whole(6)
+        );
+        assert_eq!(
+            read_total_supply(&storage, &token).unwrap(),
+            Amount::native_whole(11)
+        );
+    }
+
+    #[test]
+    fn test_denominated_amount_checked() {
+        let storage = TestWlStorage::default();
+        let token = nam();
+
+        let amount = Amount::native_whole(10);
+        assert_eq!(
+            denominated_amount_checked(
+                &storage,
+                &token,
+                amount.native_denominated()
+            )
+            .unwrap(),
+            amount
+        );
+
+        let mismatched =
+            token::DenominatedAmount::new(Amount::from(10_u64), 0.into());
+        let err = denominated_amount_checked(&storage, &token, mismatched)
+            .unwrap_err()
+            .downcast::<DenominationMismatch>()
+            .unwrap();
+        assert_eq!(
+            *err,
+            DenominationMismatch {
+                token,
+                expected: token::NATIVE_MAX_DECIMAL_PLACES.into(),
+                found: 0.into(),
+            }
+        );
+    }
+}
@@ -0,0 +1,119 @@
+//! Storage read adapter recording the keys that are read, for auditing
+
+use std::cell::RefCell;
+
+use super::{Result, StorageRead};
+use crate::types::address::Address;
+use crate::types::storage::{
+    self, BlockHash, BlockHeight, Epoch, Header, TxIndex,
+};
+
+/// Wraps a storage, delegating all the reads to it while recording every key
+/// passed to [`StorageRead::read_bytes`], [`StorageRead::has_key`] and
+/// [`StorageRead::iter_prefix`], in order.
+#[derive(Debug)]
+pub struct ReadTracer<'a, S: StorageRead> {
+    inner: &'a S,
+    reads: RefCell<Vec<storage::Key>>,
+}
+
+impl<'a, S: StorageRead> ReadTracer<'a, S> {
+    /// Wrap the given storage
+    pub fn new(inner: &'a S) -> Self {
+        Self {
+            inner,
+            reads: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// The keys read so far, in order
+    pub fn reads(&self) -> Vec<storage::Key> {
+        self.reads.borrow().clone()
+    }
+
+    fn record(&self, key: &storage::Key) {
+        self.reads.borrow_mut().push(key.clone());
+    }
+}
+
+impl<'a, S: StorageRead> StorageRead for ReadTracer<'a, S> {
+    type PrefixIter<'iter> = S::PrefixIter<'iter> where Self: 'iter;
+
+    fn read_bytes(&self, key: &storage::Key) -> Result<Option<Vec<u8>>> {
+        self.record(key);
+        self.inner.read_bytes(key)
+    }
+
+    fn has_key(&self, key: &storage::Key) -> Result<bool> {
+        self.record(key);
+        self.inner.has_key(key)
+    }
+
+    fn iter_prefix<'iter>(
+        &'iter self,
+        prefix: &storage::Key,
+    ) -> Result<Self::PrefixIter<'iter>> {
+        self.record(prefix);
+        self.inner.iter_prefix(prefix)
+    }
+
+    fn iter_next<'iter>(
+        &'iter self,
+        iter: &mut Self::PrefixIter<'iter>,
+    ) -> Result<Option<(String, Vec<u8>)>> {
+        self.inner.iter_next(iter)
+    }
+
+    fn get_chain_id(&self) -> Result<String> {
+        self.inner.get_chain_id()
+    }
+
+    fn get_block_height(&self) -> Result<BlockHeight> {
+        self.inner.get_block_height()
+    }
+
+    fn get_block_header(&self, height: BlockHeight) -> Result<Option<Header>> {
+        self.inner.get_block_header(height)
+    }
+
+    fn get_block_hash(&self) -> Result<BlockHash> {
+        self.inner.get_block_hash()
+    }
+
+    fn get_block_epoch(&self) -> Result<Epoch> {
+        self.inner.get_block_epoch()
+    }
+
+    fn get_tx_index(&self) -> Result<TxIndex> {
+        self.inner.get_tx_index()
+    }
+
+    fn get_native_token(&self) -> Result<Address> {
+        self.inner.get_native_token()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::ledger::storage_api::{iter_prefix_bytes, StorageWrite};
+
+    #[test]
+    fn test_read_tracer_records_reads() {
+        let mut wl_storage = TestWlStorage::default();
+        let prefix = storage::Key::parse("prefix").unwrap();
+        let present = prefix.push(&"present".to_owned()).unwrap();
+        let missing = prefix.push(&"missing".to_owned()).unwrap();
+        wl_storage.write(&present, 1_u64).unwrap();
+
+        let tracer = ReadTracer::new(&wl_storage);
+        assert_eq!(tracer.read::<u64>(&present).unwrap(), Some(1));
+        assert!(!tracer.has_key(&missing).unwrap());
+        assert_eq!(iter_prefix_bytes(&tracer, &prefix).unwrap().count(), 1);
+        // reading the epoch doesn't touch any key
+        tracer.get_block_epoch().unwrap();
+
+        assert_eq!(tracer.reads(), vec![present, missing, prefix]);
+    }
+}
@@ -1,19 +1,74 @@
 //! Tx storage_api functions
 
+use thiserror::Error;
+
 use super::StorageRead;
 use crate::ledger::parameters::storage::get_max_tx_bytes_key;
 use crate::ledger::storage_api;
 
+/// Error of a tx that is larger than the max tx bytes parameter
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("Tx too large: {size} bytes, but at most {max} bytes are allowed")]
+pub struct TxSizeExceeded {
+    /// The size of the tx in bytes
+    pub size: usize,
+    /// The max tx bytes parameter
+    pub max: usize,
+}
+
 /// Validate the size of a tx.
 pub fn validate_tx_bytes<S>(
     storage: &S,
     tx_size: usize,
 ) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    Ok(check_tx_bytes(storage, tx_size)?.is_ok())
+}
+
+/// Check the size of a tx against the max tx bytes parameter. The inner
+/// result holds the sizes of a tx that is too large.
+pub fn check_tx_bytes<S>(
+    storage: &S,
+    tx_size: usize,
+) -> storage_api::Result<Result<(), TxSizeExceeded>>
 where
     S: StorageRead,
 {
     let max_tx_bytes: u32 = storage
         .read(&get_max_tx_bytes_key())?
         .expect("The max tx bytes param should be present in storage");
-    Ok(tx_size <= max_tx_bytes as usize)
+    let max = max_tx_bytes as usize;
+    if tx_size <= max {
+        Ok(Ok(()))
+    } else {
+        Ok(Err(TxSizeExceeded { size: tx_size, max }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::ledger::storage_api::StorageWrite;
+
+    /// Test the tx size check at and over the max tx bytes
+    #[test]
+    fn test_check_tx_bytes() {
+        let mut storage = TestWlStorage::default();
+        storage.write(&get_max_tx_bytes_key(), 1024_u32).unwrap();
+
+        assert_eq!(check_tx_bytes(&storage, 1024).unwrap(), Ok(()));
+        assert!(validate_tx_bytes(&storage, 1024).unwrap());
+
+        assert_eq!(
+            check_tx_bytes(&storage, 1025).unwrap(),
+            Err(TxSizeExceeded {
+                size: 1025,
+                max: 1024
+            })
+        );
+        assert!(!validate_tx_bytes(&storage, 1025).unwrap());
+    }
 }
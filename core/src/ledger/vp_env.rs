@@ -1,9 +1,12 @@
 //! Validity predicate environment contains functions that can be called from
 //! inside validity predicates.
 
+use std::collections::BTreeSet;
+
 use borsh::BorshDeserialize;
 use masp_primitives::transaction::Transaction;
 
+use super::parameters::storage as parameters_storage;
 use super::storage_api::{self, OptionExt, ResultExt, StorageRead};
 use crate::proto::Tx;
 use crate::types::address::Address;
@@ -14,7 +17,8 @@ use crate::types::ibc::{
 use crate::types::storage::{
     BlockHash, BlockHeight, Epoch, Header, Key, TxIndex,
 };
-use crate::types::token::Transfer;
+use crate::types::token::{self, Transfer};
+use crate::types::transaction::Fee;
 
 /// Validity predicate's environment is available for native VPs and WASM VPs
 pub trait VpEnv<'view>
@@ -66,6 +70,24 @@ where
         height: BlockHeight,
     ) -> Result<Option<Header>, storage_api::Error>;
 
+    /// Getting the block headers in the inclusive range of heights. The
+    /// heights with no header are skipped and the result is empty when `from`
+    /// is greater than `to`.
+    fn get_block_headers(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<Vec<(BlockHeight, Header)>, storage_api::Error> {
+        let mut headers = vec![];
+        for height in from.0..=to.0 {
+            let height = BlockHeight(height);
+            if let Some(header) = self.get_block_header(height)? {
+                headers.push((height, header));
+            }
+        }
+        Ok(headers)
+    }
+
     /// Getting the block hash. The height is that of the block to which the
     /// current transaction is being applied.
     fn get_block_hash(&self) -> Result<BlockHash, storage_api::Error>;
@@ -105,17 +127,82 @@ where
         input_data: Tx,
     ) -> Result<bool, storage_api::Error>;
 
+    /// Convenience loop calling [`VpEnv::eval`] on each of the given validity
+    /// predicates in order. Nothing is shared between the evaluations: each
+    /// of them fetches its code and is charged for separately.
+    ///
+    /// The loop short-circuits: it stops at the first validity predicate that
+    /// returns `false`, without evaluating the remaining ones, and returns
+    /// `false`. Returns `true` if all of them accept, including when no
+    /// validity predicates are given.
+    fn eval_in_sequence(
+        &self,
+        vps: &[(Hash, Tx)],
+    ) -> Result<bool, storage_api::Error> {
+        for (vp_code, input_data) in vps {
+            if !self.eval(*vp_code, input_data.clone())? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
     /// Get a tx hash
     fn get_tx_code_hash(&self) -> Result<Option<Hash>, storage_api::Error>;
 
-    /// Get the shielded action including the transfer and the masp tx
+    /// Check if the given tx code hash is allowed by the `tx_whitelist`
+    /// parameter. An empty whitelist allows any tx.
+    fn is_tx_whitelisted(
+        &'view self,
+        code_hash: &Hash,
+    ) -> Result<bool, storage_api::Error> {
+        let key = parameters_storage::get_tx_whitelist_storage_key();
+        let whitelist: Vec<String> = self.read_pre(&key)?.unwrap_or_default();
+        Ok(is_whitelisted(&whitelist, code_hash))
+    }
+
+    /// Check if the given VP code hash is allowed by the `vp_whitelist`
+    /// parameter. An empty whitelist allows any VP.
+    fn is_vp_whitelisted(
+        &'view self,
+        vp_hash: &Hash,
+    ) -> Result<bool, storage_api::Error> {
+        let key = parameters_storage::get_vp_whitelist_storage_key();
+        let whitelist: Vec<String> = self.read_pre(&key)?.unwrap_or_default();
+        Ok(is_whitelisted(&whitelist, vp_hash))
+    }
+
+    /// Get the code hashes of all the code sections of the tx. The first one
+    /// is that returned by [`VpEnv::get_tx_code_hash`].
+    fn get_tx_code_hashes(&self) -> Result<Vec<Hash>, storage_api::Error>;
+
+    /// Get the shielded action including the transfer and the masp tx, i.e.
+    /// the first of [`VpEnv::get_shielded_actions`]
     fn get_shielded_action(
         &self,
         tx_data: &Tx,
     ) -> Result<(Transfer, Transaction), storage_api::Error> {
+        self.get_shielded_actions(tx_data)?
+            .into_iter()
+            .next()
+            .ok_or_err_msg("No shielded action in the transaction")
+    }
+
+    /// Get all the shielded actions including the transfers and the masp txs.
+    /// Only the main transaction data is considered, which is either a
+    /// shielded transfer, a batch of shielded transfers or an IBC shielded
+    /// transfer message. Otherwise, the actions are the shielded transfers of
+    /// the IBC packet events emitted by the transaction.
+    fn get_shielded_actions(
+        &self,
+        tx_data: &Tx,
+    ) -> Result<Vec<(Transfer, Transaction)>, storage_api::Error> {
         let signed = tx_data;
         let data = signed.data().ok_or_err_msg("No transaction data")?;
-        if let Ok(transfer) = Transfer::try_from_slice(&data) {
+        let with_masp_tx = |transfer: Transfer| -> Result<
+            (Transfer, Transaction),
+            storage_api::Error,
+        > {
             let shielded_hash = transfer
                 .shielded
                 .ok_or_err_msg("unable to find shielded hash")?;
@@ -123,37 +210,65 @@ where
                 .get_section(&shielded_hash)
                 .and_then(|x| x.as_ref().masp_tx())
                 .ok_or_err_msg("unable to find shielded section")?;
-            return Ok((transfer, masp_tx));
+            Ok((transfer, masp_tx))
+        };
+        if let Ok(transfer) = Transfer::try_from_slice(&data) {
+            return Ok(vec![with_masp_tx(transfer)?]);
+        }
+
+        if let Ok(transfers) = Vec::<Transfer>::try_from_slice(&data) {
+            return transfers.into_iter().map(with_masp_tx).collect();
         }
 
         if let Ok(message) = MsgShieldedTransfer::try_from_slice(&data) {
-            return Ok((
+            return Ok(vec![(
                 message.shielded_transfer.transfer,
                 message.shielded_transfer.masp_tx,
-            ));
+            )]);
         }
 
-        // Shielded transfer over IBC
+        // Shielded transfers over IBC
         let events = self.get_ibc_events(EVENT_TYPE_PACKET.to_string())?;
-        // The receiving event should be only one in the single IBC transaction
-        let event = events.first().ok_or_else(|| {
-            storage_api::Error::new_const(
-                "No IBC event for the shielded action",
-            )
-        })?;
-        get_shielded_transfer(event)
-            .into_storage_result()?
-            .map(|shielded| (shielded.transfer, shielded.masp_tx))
-            .ok_or_else(|| {
-                storage_api::Error::new_const(
-                    "No shielded transfer in the IBC event",
-                )
-            })
+        let mut actions = vec![];
+        for event in &events {
+            if let Some(shielded) =
+                get_shielded_transfer(event).into_storage_result()?
+            {
+                actions.push((shielded.transfer, shielded.masp_tx));
+            }
+        }
+        Ok(actions)
     }
 
+    /// Get the fee of the wrapper of the transaction, if it is wrapped
+    fn get_wrapper_fee(&self) -> Result<Option<Fee>, storage_api::Error>;
+
     /// Charge the provided gas for the current vp
     fn charge_gas(&self, used_gas: u64) -> Result<(), storage_api::Error>;
 
+    /// Get the storage keys changed by the transaction
+    fn get_keys_changed(&self) -> &BTreeSet<Key>;
+
+    /// Check if the given storage key has been changed by the transaction
+    fn key_changed(&self, key: &Key) -> bool {
+        self.get_keys_changed().contains(key)
+    }
+
+    /// Iterate the storage keys changed by the transaction that match the
+    /// given prefix, ordered by the storage keys
+    fn changed_keys_with_prefix<'iter>(
+        &'iter self,
+        prefix: &'iter Key,
+    ) -> Box<dyn Iterator<Item = &'iter Key> + 'iter> {
+        // The keys matching the prefix are contiguous in the ordered set and
+        // the first of them cannot be ordered before the prefix itself
+        Box::new(
+            self.get_keys_changed()
+                .range(prefix..)
+                .take_while(|key| key.segments.starts_with(&prefix.segments)),
+        )
+    }
+
     // ---- Methods below have default implementation via `pre/post` ----
 
     /// Storage read prior state Borsh encoded value (before tx execution). It
@@ -194,6 +309,32 @@ where
         self.post().read_bytes(key)
     }
 
+    /// Storage read prior state Borsh encoded value (before tx execution) and
+    /// charge gas proportional to the length of the key and of the bytes
+    /// read. The key lookup is charged even if the key is not found.
+    fn read_pre_metered<T: BorshDeserialize>(
+        &'view self,
+        key: &Key,
+        gas_per_byte: u64,
+    ) -> Result<Option<T>, storage_api::Error> {
+        let bytes = self.read_bytes_pre(key)?;
+        self.charge_gas(read_gas(key, bytes.as_ref(), gas_per_byte)?)?;
+        decode_read(bytes)
+    }
+
+    /// Storage read posterior state Borsh encoded value (after tx execution)
+    /// and charge gas proportional to the length of the key and of the bytes
+    /// read. The key lookup is charged even if the key is not found.
+    fn read_post_metered<T: BorshDeserialize>(
+        &'view self,
+        key: &Key,
+        gas_per_byte: u64,
+    ) -> Result<Option<T>, storage_api::Error> {
+        let bytes = self.read_bytes_post(key)?;
+        self.charge_gas(read_gas(key, bytes.as_ref(), gas_per_byte)?)?;
+        decode_read(bytes)
+    }
+
     /// Storage `has_key` in prior state (before tx execution). It will try to
     /// read from the storage.
     fn has_key_pre(&'view self, key: &Key) -> Result<bool, storage_api::Error> {
@@ -208,4 +349,100 @@ where
     ) -> Result<bool, storage_api::Error> {
         self.post().has_key(key)
     }
+
+    /// Read the balances of the given token owned by the given address before
+    /// and after the transaction, as `(pre, post)`. A missing balance is
+    /// zero, e.g. the prior balance of an account being credited for the
+    /// first time.
+    fn balance_delta(
+        &'view self,
+        token: &Address,
+        owner: &Address,
+    ) -> Result<(token::Amount, token::Amount), storage_api::Error> {
+        let pre = storage_api::token::read_balance(&self.pre(), token, owner)?;
+        let post =
+            storage_api::token::read_balance(&self.post(), token, owner)?;
+        Ok((pre, post))
+    }
+
+    /// Check that the given amount of a token was moved from one account to
+    /// another, given the keys changed by the transaction. The balance keys
+    /// of both accounts must have changed, unless the amount is nil in which
+    /// case neither of them can have changed. No other balance of the token
+    /// can have changed either.
+    fn verify_escrow(
+        &'view self,
+        token: &Address,
+        from: &Address,
+        to: &Address,
+        amount: &token::Amount,
+        changed_keys: &BTreeSet<Key>,
+    ) -> Result<bool, storage_api::Error> {
+        let from_key = token::balance_key(token, from);
+        let to_key = token::balance_key(token, to);
+        let other_balance_changed = changed_keys.iter().any(|key| {
+            key != &from_key
+                && key != &to_key
+                && token::is_balance_key(token, key).is_some()
+        });
+        if other_balance_changed
+            || !escrow_keys_valid(token, from, to, amount, changed_keys)
+        {
+            return Ok(false);
+        }
+        if amount.is_zero() {
+            return Ok(true);
+        }
+        let (from_pre, from_post) = self.balance_delta(token, from)?;
+        let (to_pre, to_post) = self.balance_delta(token, to)?;
+        Ok(from_pre.checked_sub(from_post) == Some(*amount)
+            && to_post.checked_sub(to_pre) == Some(*amount))
+    }
+}
+
+/// Check if a code hash is in a whitelist of lowercase hex-encoded hashes.
+/// An empty whitelist allows any code.
+fn is_whitelisted(whitelist: &[String], code_hash: &Hash) -> bool {
+    whitelist.is_empty()
+        || whitelist.contains(&code_hash.to_string().to_lowercase())
+}
+
+/// Check the balance keys of an escrow of the given amount of a token between
+/// two accounts. If the amount is nil, then neither balance key can have
+/// changed. Otherwise, both of them must have changed.
+pub fn escrow_keys_valid(
+    token: &Address,
+    from: &Address,
+    to: &Address,
+    amount: &token::Amount,
+    changed_keys: &BTreeSet<Key>,
+) -> bool {
+    let from_changed = changed_keys.contains(&token::balance_key(token, from));
+    let to_changed = changed_keys.contains(&token::balance_key(token, to));
+    if amount.is_zero() {
+        !from_changed && !to_changed
+    } else {
+        from_changed && to_changed
+    }
+}
+
+/// The gas for reading the given key and the optional bytes found under it
+fn read_gas(
+    key: &Key,
+    bytes: Option<&Vec<u8>>,
+    gas_per_byte: u64,
+) -> Result<u64, storage_api::Error> {
+    let len = key.len() + bytes.map(Vec::len).unwrap_or_default();
+    (len as u64)
+        .checked_mul(gas_per_byte)
+        .ok_or_err_msg("Overflow in the gas for a storage read")
+}
+
+/// Decode the optional bytes read from storage
+fn decode_read<T: BorshDeserialize>(
+    bytes: Option<Vec<u8>>,
+) -> Result<Option<T>, storage_api::Error> {
+    bytes
+        .map(|bytes| T::try_from_slice(&bytes).into_storage_result())
+        .transpose()
 }
@@ -4,10 +4,10 @@ pub mod generated;
 mod types;
 
 pub use types::{
-    standalone_signature, verify_standalone_sig, Code, Commitment,
-    CompressedSignature, Data, Error, Header, MaspBuilder, Section,
+    enforce_max_signatures, standalone_signature, verify_standalone_sig, Code,
+    Commitment, CompressedSignature, Data, Error, Header, MaspBuilder, Section,
     SerializeWithBorsh, Signable, SignableEthMessage, Signature,
-    SignatureIndex, Signed, Signer, Tx, TxError,
+    SignatureError, SignatureIndex, Signed, Signer, Tx, TxError,
 };
 
 #[cfg(test)]
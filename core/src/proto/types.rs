@@ -472,6 +472,32 @@ pub enum Signer {
     PubKeys(Vec<common::PublicKey>),
 }
 
+/// Error of a signature section that doesn't respect the allowed number of
+/// signatures
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SignatureError {
+    #[error(
+        "Too many signatures: {provided} provided, but at most {allowed} are \
+         allowed"
+    )]
+    TooManySignatures { provided: usize, allowed: u8 },
+}
+
+/// Check that the number of provided signatures doesn't exceed the maximum
+/// number of signatures per transaction. No maximum allows up to `u8::MAX`
+/// signatures.
+pub fn enforce_max_signatures(
+    provided: usize,
+    max: Option<u8>,
+) -> std::result::Result<(), SignatureError> {
+    let allowed = max.unwrap_or(u8::MAX);
+    if provided > allowed as usize {
+        return Err(SignatureError::TooManySignatures { provided, allowed });
+    }
+    Ok(())
+}
+
 /// A section representing a multisig over another section
 #[derive(
     Clone,
@@ -506,7 +532,10 @@ impl Signature {
             // Make sure the corresponding public keys can be represented by a
             // vector instead of a map
             assert!(
-                secret_keys.keys().cloned().eq(0..(secret_keys.len() as u8)),
+                secret_keys
+                    .keys()
+                    .map(|index| *index as usize)
+                    .eq(0..secret_keys.len()),
                 "secret keys must be enumerated when signer address is absent"
             );
             Signer::PubKeys(secret_keys.values().map(RefTo::ref_to).collect())
@@ -533,8 +562,8 @@ impl Signature {
         }
     }
 
-    pub fn total_signatures(&self) -> u8 {
-        self.signatures.len() as u8
+    pub fn total_signatures(&self) -> usize {
+        self.signatures.len()
     }
 
     /// Hash this signature section
@@ -566,54 +595,103 @@ impl Signature {
         public_keys_index_map: &AccountPublicKeysMap,
         signer: &Option<Address>,
         consume_verify_sig_gas: &mut F,
-    ) -> std::result::Result<u8, VerifySigError>
+    ) -> std::result::Result<usize, VerifySigError>
     where
         F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
     {
-        // Records whether there are any successful verifications
-        let mut verifications = 0;
-        match &self.signer {
+        let matched = self.verified_public_key_indices(
+            public_keys_index_map,
+            signer,
+            consume_verify_sig_gas,
+        )?;
+        // Records the number of successful verifications
+        let verifications = matched.len();
+        verified_pks.extend(matched);
+        Ok(verifications)
+    }
+
+    /// Verify the signatures contained in this section and return the indices
+    /// of the given public keys that have a valid signature. All the
+    /// signatures are checked, even after finding an invalid one, so that the
+    /// verification time doesn't depend on the position of the first invalid
+    /// signature. An error is returned at the end if any signature is invalid.
+    pub fn verified_public_key_indices<F>(
+        &self,
+        public_keys_index_map: &AccountPublicKeysMap,
+        signer: &Option<Address>,
+        consume_verify_sig_gas: &mut F,
+    ) -> std::result::Result<HashSet<u8>, VerifySigError>
+    where
+        F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
+    {
+        // Pairs of the index of a public key in the given map, the public key
+        // itself and its signature in this section
+        let candidates: Vec<(
+            u8,
+            common::PublicKey,
+            Option<&common::Signature>,
+        )> = match &self.signer {
             // Verify the signatures against the given public keys if the
             // account addresses match
-            Signer::Address(addr) if Some(addr) == signer.as_ref() => {
-                for (idx, sig) in &self.signatures {
-                    if let Some(pk) =
-                        public_keys_index_map.get_public_key_from_index(*idx)
-                    {
-                        consume_verify_sig_gas()?;
-                        common::SigScheme::verify_signature(
-                            &pk,
-                            &self.get_raw_hash(),
-                            sig,
-                        )?;
-                        verified_pks.insert(*idx);
-                        verifications += 1;
-                    }
+            Signer::Address(addr) if Some(addr) == signer.as_ref() => self
+                .signatures
+                .iter()
+                .filter_map(|(idx, sig)| {
+                    public_keys_index_map
+                        .get_public_key_from_index(*idx)
+                        .map(|pk| (*idx, pk, Some(sig)))
+                })
+                .collect(),
+            // If the account addresses do not match, then there is no
+            // efficient way to map signatures to the given public keys
+            Signer::Address(_) => vec![],
+            // Verify the signatures against the subset of this section's
+            // public keys that are also in the given map
+            Signer::PubKeys(pks) => pks
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, pk)| {
+                    public_keys_index_map.get_index_from_public_key(pk).map(
+                        |map_idx| {
+                            (
+                                map_idx,
+                                pk.clone(),
+                                self.signatures.get(&(idx as u8)),
+                            )
+                        },
+                    )
+                })
+                .collect(),
+        };
+
+        let raw_hash = self.get_raw_hash();
+        let mut matched = HashSet::new();
+        let mut first_error = None;
+        for (map_idx, pk, sig) in candidates {
+            consume_verify_sig_gas()?;
+            let result = match sig {
+                Some(sig) => {
+                    common::SigScheme::verify_signature(&pk, &raw_hash, sig)
                 }
-            }
-            // If the account addresses do not match, then there is no efficient
-            // way to map signatures to the given public keys
-            Signer::Address(_) => {}
-            // Verify the signatures against the subset of this section's public
-            // keys that are also in the given map
-            Signer::PubKeys(pks) => {
-                for (idx, pk) in pks.iter().enumerate() {
-                    if let Some(map_idx) =
-                        public_keys_index_map.get_index_from_public_key(pk)
-                    {
-                        consume_verify_sig_gas()?;
-                        common::SigScheme::verify_signature(
-                            pk,
-                            &self.get_raw_hash(),
-                            &self.signatures[&(idx as u8)],
-                        )?;
-                        verified_pks.insert(map_idx);
-                        verifications += 1;
-                    }
+                None => Err(VerifySigError::SigVerifyError(format!(
+                    "Missing signature for public key {pk}"
+                ))),
+            };
+            // Keep going on errors to not leak the position of the first
+            // invalid signature
+            match result {
+                Ok(()) => {
+                    matched.insert(map_idx);
+                }
+                Err(err) => {
+                    first_error.get_or_insert(err);
                 }
             }
         }
-        Ok(verifications)
+        match first_error {
+            Some(err) => Err(err),
+            None => Ok(matched),
+        }
     }
 }
 
@@ -1121,6 +1199,13 @@ impl Tx {
         self.header.clone()
     }
 
+    /// Check if this transaction requires a signature of its fee payer over
+    /// the wrapper header. Raw, decrypted and protocol transactions are never
+    /// wrapped.
+    pub fn requires_wrapper(&self) -> bool {
+        matches!(self.header.tx_type, TxType::Wrapper(_))
+    }
+
     /// Get the transaction header hash
     pub fn header_hash(&self) -> crate::types::hash::Hash {
         Section::Header(self.header.clone()).get_hash()
@@ -1235,6 +1320,17 @@ impl Tx {
         }
     }
 
+    /// Decode the data designated by the transaction data hash in the header
+    /// to the expected type. Useful to confirm the content of a transaction
+    /// before signing it.
+    pub fn expect_data_type<T: BorshDeserialize>(&self) -> Result<T, TxError> {
+        let data = self.data().ok_or_else(|| {
+            TxError::Deserialization("No transaction data".to_string())
+        })?;
+        T::try_from_slice(&data)
+            .map_err(|err| TxError::Deserialization(err.to_string()))
+    }
+
     /// Convert this transaction into protobufs
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut bytes = vec![];
@@ -1247,7 +1343,10 @@ impl Tx {
     }
 
     /// Verify that the section with the given hash has been signed by the given
-    /// public key
+    /// public key. All the signature sections covering the given hashes are
+    /// verified, even once the threshold is met, so that the verification time
+    /// doesn't depend on the position of an invalid section. The error lists
+    /// every section with an invalid signature.
     pub fn verify_signatures<F>(
         &self,
         hashes: &[crate::types::hash::Hash],
@@ -1260,11 +1359,12 @@ impl Tx {
     where
         F: FnMut() -> std::result::Result<(), crate::ledger::gas::Error>,
     {
-        let max_signatures = max_signatures.unwrap_or(u8::MAX);
         // Records the public key indices used in successful signatures
         let mut verified_pks = HashSet::new();
         // Records the sections instrumental in verifying signatures
         let mut witnesses = Vec::new();
+        // Records the sections with invalid signatures
+        let mut invalid_sections = Vec::new();
 
         for section in &self.sections {
             if let Section::Signature(signatures) = section {
@@ -1278,45 +1378,57 @@ impl Tx {
                     .iter()
                     .all(|x| self.get_section(x).is_some())
                 {
-                    if signatures.total_signatures() > max_signatures {
-                        return Err(Error::InvalidSectionSignature(
-                            "too many signatures.".to_string(),
+                    // Don't verify the signatures of a section carrying too
+                    // many of them
+                    if let Err(err) = enforce_max_signatures(
+                        signatures.signatures.len(),
+                        max_signatures,
+                    ) {
+                        invalid_sections.push(format!(
+                            "{}: {}",
+                            section.get_hash(),
+                            err
                         ));
+                        continue;
                     }
 
                     // Finally verify that the signature itself is valid
-                    let amt_verifieds = signatures
-                        .verify_signature(
-                            &mut verified_pks,
-                            &public_keys_index_map,
-                            signer,
-                            &mut consume_verify_sig_gas,
-                        )
-                        .map_err(|e| {
-                            if let VerifySigError::OutOfGas(inner) = e {
-                                Error::OutOfGas(inner)
-                            } else {
-                                Error::InvalidSectionSignature(
-                                    "found invalid signature.".to_string(),
-                                )
-                            }
-                        });
-                    // Record the section witnessing these signatures
-                    if amt_verifieds? > 0 {
-                        witnesses.push(signatures);
-                    }
-                    // Short-circuit these checks if the threshold is exceeded
-                    if verified_pks.len() >= threshold.into() {
-                        return Ok(witnesses);
+                    match signatures.verify_signature(
+                        &mut verified_pks,
+                        &public_keys_index_map,
+                        signer,
+                        &mut consume_verify_sig_gas,
+                    ) {
+                        Ok(0) => {}
+                        // Record the section witnessing these signatures
+                        Ok(_) => witnesses.push(signatures),
+                        Err(VerifySigError::OutOfGas(inner)) => {
+                            return Err(Error::OutOfGas(inner));
+                        }
+                        // Keep going to check the remaining sections
+                        Err(err) => invalid_sections.push(format!(
+                            "{}: {}",
+                            section.get_hash(),
+                            err
+                        )),
                     }
                 }
             }
         }
-        Err(Error::InvalidSectionSignature(format!(
-            "signature threshold not met: ({} < {})",
-            verified_pks.len(),
-            threshold
-        )))
+        if !invalid_sections.is_empty() {
+            return Err(Error::InvalidSectionSignature(format!(
+                "found invalid signatures in sections: {}",
+                invalid_sections.join(", ")
+            )));
+        }
+        if verified_pks.len() < threshold.into() {
+            return Err(Error::InvalidSectionSignature(format!(
+                "signature threshold not met: ({} < {})",
+                verified_pks.len(),
+                threshold
+            )));
+        }
+        Ok(witnesses)
     }
 
     /// Verify that the sections with the given hashes have been signed together
@@ -1627,3 +1739,212 @@ impl Tx {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::nam;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::types::key::testing::common_sk_from_simple_seed;
+    use crate::types::token::{self, Transfer};
+
+    /// Test that the tx data decodes to the type it was built from
+    #[test]
+    fn test_expect_data_type_transfer() {
+        let transfer = Transfer {
+            source: established_address_1(),
+            target: established_address_2(),
+            token: nam(),
+            amount: token::DenominatedAmount::native(
+                token::Amount::native_whole(10),
+            ),
+            key: None,
+            shielded: None,
+        };
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_data(transfer.clone());
+
+        let decoded: Transfer = tx.expect_data_type().unwrap();
+        assert_eq!(decoded, transfer);
+    }
+
+    /// Test that decoding the tx data to another type fails
+    #[test]
+    fn test_expect_data_type_mismatch() {
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_data(42_u64);
+
+        let result = tx.expect_data_type::<Transfer>();
+        assert!(matches!(result, Err(TxError::Deserialization(_))));
+
+        let tx = Tx::new(ChainId::default(), None);
+        let result = tx.expect_data_type::<u64>();
+        assert!(matches!(result, Err(TxError::Deserialization(_))));
+    }
+
+    /// Test the maximum number of signatures at and over the limit
+    #[test]
+    fn test_enforce_max_signatures() {
+        assert!(enforce_max_signatures(3, Some(3)).is_ok());
+        assert_eq!(
+            enforce_max_signatures(4, Some(3)),
+            Err(SignatureError::TooManySignatures {
+                provided: 4,
+                allowed: 3
+            })
+        );
+
+        // No maximum allows up to `u8::MAX` signatures
+        assert!(enforce_max_signatures(u8::MAX as usize, None).is_ok());
+        assert!(enforce_max_signatures(u8::MAX as usize + 1, None).is_err());
+    }
+
+    /// Test that all the signatures of a 3-of-5 account are matched to their
+    /// keys, regardless of the order in which the keys signed
+    #[test]
+    fn test_verified_public_key_indices_scrambled() {
+        let sk = common_sk_from_simple_seed;
+        let public_keys_map = AccountPublicKeysMap::from_iter(
+            (0..5).map(|seed| sk(seed).ref_to()),
+        );
+        let signer = established_address_1();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None).sign_raw(
+            vec![sk(4), sk(1), sk(3)],
+            public_keys_map.clone(),
+            Some(signer.clone()),
+        );
+        let hashes = [tx.raw_header_hash()];
+        let section = tx
+            .sections
+            .iter()
+            .find_map(|section| match section {
+                Section::Signature(signature) => Some(signature),
+                _ => None,
+            })
+            .unwrap();
+
+        let matched = section
+            .verified_public_key_indices(
+                &public_keys_map,
+                &Some(signer.clone()),
+                &mut || Ok(()),
+            )
+            .unwrap();
+        assert_eq!(matched, HashSet::from([1, 3, 4]));
+
+        let verify = |threshold| {
+            tx.verify_signatures(
+                &hashes,
+                public_keys_map.clone(),
+                &Some(signer.clone()),
+                threshold,
+                None,
+                || Ok(()),
+            )
+        };
+        assert!(verify(3).is_ok());
+        assert!(verify(4).is_err());
+    }
+
+    /// Test that only wrapper txs require a wrapper signature
+    #[test]
+    fn test_requires_wrapper() {
+        let raw = Tx::from_type(TxType::Raw);
+        assert!(!raw.requires_wrapper());
+
+        let decrypted =
+            Tx::from_type(TxType::Decrypted(DecryptedTx::Decrypted));
+        assert!(!decrypted.requires_wrapper());
+
+        let wrapper = Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: token::Amount::native_whole(1)
+                    .native_denominated(),
+                token: nam(),
+            },
+            common_sk_from_simple_seed(0).ref_to(),
+            Epoch(0),
+            GasLimit::from(20_000),
+            None,
+        ))));
+        assert!(wrapper.requires_wrapper());
+    }
+
+    /// Test that an invalid signature section is reported even when another
+    /// section already meets the threshold
+    #[test]
+    fn test_verify_signatures_reports_all_invalid_sections() {
+        let sk = common_sk_from_simple_seed;
+        let public_keys_map = AccountPublicKeysMap::from_iter(
+            (0..2).map(|seed| sk(seed).ref_to()),
+        );
+        let signer = established_address_1();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.add_code(vec![], None)
+            .sign_raw(
+                vec![sk(0)],
+                public_keys_map.clone(),
+                Some(signer.clone()),
+            )
+            .sign_raw(
+                vec![sk(1)],
+                public_keys_map.clone(),
+                Some(signer.clone()),
+            );
+        let hashes = [tx.raw_header_hash()];
+        let verify = |tx: &Tx, threshold| {
+            tx.verify_signatures(
+                &hashes,
+                public_keys_map.clone(),
+                &Some(signer.clone()),
+                threshold,
+                None,
+                || Ok(()),
+            )
+            .map(|witnesses| witnesses.len())
+        };
+        assert_eq!(verify(&tx, 1).unwrap(), 2);
+        assert_eq!(verify(&tx, 2).unwrap(), 2);
+
+        // Swap in the signature of the first key for the second one
+        let first_sig = tx
+            .sections
+            .iter()
+            .find_map(|section| match section {
+                Section::Signature(signature) => {
+                    signature.signatures.get(&0).cloned()
+                }
+                _ => None,
+            })
+            .unwrap();
+        for section in tx.sections.iter_mut() {
+            if let Section::Signature(signature) = section {
+                if let Some(sig) = signature.signatures.get_mut(&1) {
+                    *sig = first_sig.clone();
+                }
+            }
+        }
+        let invalid_hash = tx
+            .sections
+            .iter()
+            .find(|section| {
+                matches!(
+                    section,
+                    Section::Signature(signature)
+                        if signature.signatures.contains_key(&1)
+                )
+            })
+            .unwrap()
+            .get_hash();
+
+        // The first section meets the threshold, but the second one is
+        // still verified
+        let err = verify(&tx, 1).unwrap_err();
+        assert!(err.to_string().contains(&invalid_hash.to_string()));
+    }
+}
@@ -1,16 +1,66 @@
 //! Helper structures to manage accounts
 
-use std::collections::{BTreeMap, HashMap};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::Display;
 
-use borsh::{BorshDeserialize, BorshSerialize};
+use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
 use super::address::Address;
+use super::hash::Hash;
 use super::key::{common, RefTo};
 use crate::hints;
+use crate::proto::Tx;
+
+/// The maximum number of public keys in an account
+pub const MAX_ACCOUNT_KEYS: usize = u8::MAX as usize + 1;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AccountError {
+    #[error(
+        "An account can hold at most {MAX_ACCOUNT_KEYS} public keys, got {0}"
+    )]
+    TooManyKeys(usize),
+    #[error("The public key {0} is already present in the account")]
+    DuplicateKey(common::PublicKey),
+    #[error("The account threshold must be at least 1")]
+    ZeroThreshold,
+    #[error(
+        "The account threshold {threshold} cannot be met with only {keys} \
+         public keys"
+    )]
+    ThresholdAboveKeys { threshold: u8, keys: usize },
+}
+
+/// Check that an account with the given number of public keys and threshold
+/// can still sign transactions, i.e. that the threshold is between 1 and the
+/// number of keys.
+pub fn validate_threshold_change(
+    new_keys: usize,
+    new_threshold: u8,
+) -> Result<(), AccountError> {
+    if new_threshold == 0 {
+        return Err(AccountError::ZeroThreshold);
+    }
+    if new_threshold as usize > new_keys {
+        return Err(AccountError::ThresholdAboveKeys {
+            threshold: new_threshold,
+            keys: new_keys,
+        });
+    }
+    Ok(())
+}
 
 #[derive(
-    Debug, Clone, BorshSerialize, BorshDeserialize, Serialize, Deserialize,
+    Debug,
+    Clone,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+    Serialize,
+    Deserialize,
 )]
 /// Account data
 pub struct Account {
@@ -38,6 +88,74 @@ impl Account {
     ) -> Option<u8> {
         self.public_keys_map.get_index_from_public_key(public_key)
     }
+
+    /// Find a smallest set of the `available` keys that meets the threshold
+    /// of this account, ordered by the keys' indices. As every key has the
+    /// same weight, these are the first `threshold` available keys of the
+    /// account. Returns `None` if not enough of the account's keys are
+    /// available.
+    pub fn minimal_authorizing_set(
+        &self,
+        available: &[common::PublicKey],
+    ) -> Option<Vec<common::PublicKey>> {
+        let indexed = available
+            .iter()
+            .filter_map(|public_key| {
+                self.get_index_from_public_key(public_key)
+                    .map(|index| (index, public_key.clone()))
+            })
+            .collect::<BTreeMap<u8, common::PublicKey>>();
+        let threshold = self.threshold as usize;
+        (indexed.len() >= threshold)
+            .then(|| indexed.into_values().take(threshold).collect())
+    }
+}
+
+impl Display for Account {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Address: {}", self.address)?;
+        writeln!(
+            f,
+            "Threshold: {} of {}",
+            self.threshold,
+            self.public_keys_map.idx_to_pk.len()
+        )?;
+        write!(f, "Public keys:")?;
+        let public_keys = self
+            .public_keys_map
+            .idx_to_pk
+            .iter()
+            .collect::<BTreeMap<_, _>>();
+        for (index, public_key) in public_keys {
+            write!(f, "\n- {index}: {public_key}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Verify that the sections with the given hashes of the transaction are signed
+/// by enough of the account's keys to meet its threshold, with at most
+/// `max_signatures` signatures per signature section. The gas cost of every
+/// signature verification is charged with `consume_verify_sig_gas`.
+pub fn verify_account_signatures<F>(
+    account: &Account,
+    tx: &Tx,
+    section_hashes: &[Hash],
+    max_signatures: Option<u8>,
+    consume_verify_sig_gas: F,
+) -> Result<(), crate::proto::Error>
+where
+    F: FnMut() -> Result<(), crate::ledger::gas::Error>,
+{
+    tx.verify_signatures(
+        section_hashes,
+        account.public_keys_map.clone(),
+        &Some(account.address.clone()),
+        account.threshold,
+        max_signatures,
+        consume_verify_sig_gas,
+    )
+    .map(|_| ())
 }
 
 #[derive(
@@ -45,6 +163,7 @@ impl Account {
     Clone,
     BorshSerialize,
     BorshDeserialize,
+    BorshSchema,
     Serialize,
     Deserialize,
     Default,
@@ -63,9 +182,10 @@ impl FromIterator<common::PublicKey> for AccountPublicKeysMap {
         let mut idx_to_pk = HashMap::new();
 
         for (index, public_key) in iter.into_iter().enumerate() {
-            if hints::unlikely(index > u8::MAX as usize) {
+            if hints::unlikely(index >= MAX_ACCOUNT_KEYS) {
                 panic!(
-                    "Only up to 255 signers are allowed in a multisig account"
+                    "Only up to {MAX_ACCOUNT_KEYS} signers are allowed in a \
+                     multisig account"
                 );
             }
             pk_to_idx.insert(public_key.to_owned(), index as u8);
@@ -96,17 +216,329 @@ impl AccountPublicKeysMap {
         self.pk_to_idx.get(public_key).cloned()
     }
 
+    /// Check if another public key can be inserted without exceeding
+    /// [`MAX_ACCOUNT_KEYS`]
+    pub fn can_add_key(&self) -> bool {
+        self.idx_to_pk.len() < MAX_ACCOUNT_KEYS
+    }
+
     /// Index the given set of secret keys
     pub fn index_secret_keys(
         &self,
         secret_keys: Vec<common::SecretKey>,
     ) -> BTreeMap<u8, common::SecretKey> {
-        secret_keys
+        self.index_secret_keys_partitioned(secret_keys).0
+    }
+
+    /// Index the given set of secret keys and return the secret keys whose
+    /// public key is not in this map separately
+    pub fn index_secret_keys_partitioned(
+        &self,
+        secret_keys: Vec<common::SecretKey>,
+    ) -> (BTreeMap<u8, common::SecretKey>, Vec<common::SecretKey>) {
+        let mut indexed = BTreeMap::new();
+        let mut unmatched = vec![];
+        for secret_key in secret_keys {
+            match self.get_index_from_public_key(&secret_key.ref_to()) {
+                Some(index) => {
+                    indexed.insert(index, secret_key);
+                }
+                None => unmatched.push(secret_key),
+            }
+        }
+        (indexed, unmatched)
+    }
+
+    /// Insert the given public keys at the lowest free indices and return the
+    /// index assigned to each key. Nothing is inserted if any of the keys is
+    /// already present or if the map would exceed the maximum number of keys.
+    pub fn insert_keys(
+        &mut self,
+        keys: Vec<common::PublicKey>,
+    ) -> Result<Vec<(common::PublicKey, u8)>, AccountError> {
+        let mut new_keys = HashSet::new();
+        for key in &keys {
+            if self.pk_to_idx.contains_key(key) || !new_keys.insert(key) {
+                return Err(AccountError::DuplicateKey(key.clone()));
+            }
+        }
+        let total = self.idx_to_pk.len() + keys.len();
+        if total > MAX_ACCOUNT_KEYS {
+            return Err(AccountError::TooManyKeys(total));
+        }
+
+        let free_indices = (0..=u8::MAX)
+            .filter(|index| !self.idx_to_pk.contains_key(index))
+            .collect::<Vec<_>>();
+        let assignments = keys
+            .into_iter()
+            .zip(free_indices)
+            .collect::<Vec<(common::PublicKey, u8)>>();
+        for (key, index) in &assignments {
+            self.pk_to_idx.insert(key.clone(), *index);
+            self.idx_to_pk.insert(*index, key.clone());
+        }
+        Ok(assignments)
+    }
+
+    /// Merge the keys of `other` into this map. The keys of `other` that are
+    /// not already present are inserted in the order of their indices in
+    /// `other`, at the lowest free indices of this map. Fails if the merged
+    /// map would exceed the maximum number of keys.
+    pub fn merge(
+        mut self,
+        other: AccountPublicKeysMap,
+    ) -> Result<Self, AccountError> {
+        let mut other_keys = other.idx_to_pk.into_iter().collect::<Vec<_>>();
+        other_keys.sort_by_key(|(index, _)| *index);
+        let new_keys = other_keys
+            .into_iter()
+            .map(|(_, key)| key)
+            .filter(|key| !self.pk_to_idx.contains_key(key))
+            .collect();
+        self.insert_keys(new_keys)?;
+        Ok(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::established_address_1;
+    use crate::types::chain::ChainId;
+    use crate::types::key::testing::common_sk_from_simple_seed;
+
+    /// Test that the inserted keys take the lowest free indices, starting
+    /// with a freed one
+    #[test]
+    fn test_insert_keys_reuses_freed_index() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let mut map = AccountPublicKeysMap::from_iter([pk(0), pk(1), pk(2)]);
+        // Free the index 1
+        let removed = map.idx_to_pk.remove(&1).unwrap();
+        map.pk_to_idx.remove(&removed);
+
+        let assignments = map.insert_keys(vec![pk(3), pk(4), pk(5)]).unwrap();
+        assert_eq!(assignments, vec![(pk(3), 1), (pk(4), 3), (pk(5), 4)]);
+        for (key, index) in assignments {
+            assert_eq!(map.get_index_from_public_key(&key), Some(index));
+            assert_eq!(map.get_public_key_from_index(index), Some(key));
+        }
+
+        // Already present keys are rejected
+        let err = map.insert_keys(vec![pk(6), pk(0)]).unwrap_err();
+        assert!(matches!(err, AccountError::DuplicateKey(key) if key == pk(0)));
+        assert_eq!(map.get_index_from_public_key(&pk(6)), None);
+    }
+
+    /// Test the signature verification of a 2-of-3 account
+    #[test]
+    fn test_verify_account_signatures() {
+        let sk = common_sk_from_simple_seed;
+        let account = Account {
+            public_keys_map: AccountPublicKeysMap::from_iter([
+                sk(0).ref_to(),
+                sk(1).ref_to(),
+                sk(2).ref_to(),
+            ]),
+            threshold: 2,
+            address: established_address_1(),
+        };
+        let signed_tx = |keys: Vec<common::SecretKey>| {
+            let mut tx = Tx::new(ChainId::default(), None);
+            tx.add_code(vec![], None).sign_raw(
+                keys,
+                account.public_keys_map.clone(),
+                Some(account.address.clone()),
+            );
+            tx
+        };
+
+        let tx = signed_tx(vec![sk(0), sk(2)]);
+        let hashes = [tx.raw_header_hash()];
+        let mut verified = 0;
+        let consume_gas = || {
+            verified += 1;
+            Ok(())
+        };
+        assert!(
+            verify_account_signatures(
+                &account,
+                &tx,
+                &hashes,
+                None,
+                consume_gas
+            )
+            .is_ok()
+        );
+        assert_eq!(verified, 2);
+
+        let tx = signed_tx(vec![sk(1)]);
+        let hashes = [tx.raw_header_hash()];
+        assert!(
+            verify_account_signatures(&account, &tx, &hashes, None, || Ok(()))
+                .is_err()
+        );
+    }
+
+    /// Test that the secret keys that are not in the map are kept apart
+    #[test]
+    fn test_index_secret_keys_partitioned() {
+        let sk = common_sk_from_simple_seed;
+        let map =
+            AccountPublicKeysMap::from_iter([sk(0).ref_to(), sk(1).ref_to()]);
+
+        let (indexed, unmatched) =
+            map.index_secret_keys_partitioned(vec![sk(1), sk(2)]);
+        let indexed = indexed
             .into_iter()
-            .filter_map(|secret_key: common::SecretKey| {
-                self.get_index_from_public_key(&secret_key.ref_to())
-                    .map(|index| (index, secret_key))
+            .map(|(index, sk)| (index, sk.ref_to()))
+            .collect::<Vec<_>>();
+        assert_eq!(indexed, vec![(1, sk(1).ref_to())]);
+        let unmatched = unmatched
+            .iter()
+            .map(|sk| sk.ref_to())
+            .collect::<Vec<common::PublicKey>>();
+        assert_eq!(unmatched, vec![sk(2).ref_to()]);
+    }
+
+    /// Test that a threshold of 0 is rejected
+    #[test]
+    fn test_validate_threshold_change_zero() {
+        assert_eq!(
+            validate_threshold_change(2, 0),
+            Err(AccountError::ZeroThreshold)
+        );
+    }
+
+    /// Test that a threshold above the number of keys is rejected
+    #[test]
+    fn test_validate_threshold_change_above_keys() {
+        assert_eq!(
+            validate_threshold_change(2, 3),
+            Err(AccountError::ThresholdAboveKeys {
+                threshold: 3,
+                keys: 2
             })
-            .collect()
+        );
+    }
+
+    /// Test that reducing the threshold along with the keys is valid
+    #[test]
+    fn test_validate_threshold_change_reduction() {
+        assert_eq!(validate_threshold_change(3, 3), Ok(()));
+        assert_eq!(validate_threshold_change(2, 2), Ok(()));
+    }
+
+    /// Test that the keys present in both maps are only added once
+    #[test]
+    fn test_merge_skips_duplicate_keys() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let map = AccountPublicKeysMap::from_iter([pk(0), pk(1)]);
+        let other = AccountPublicKeysMap::from_iter([pk(2), pk(1), pk(3)]);
+
+        let merged = map.merge(other).unwrap();
+        assert_eq!(merged.idx_to_pk.len(), 4);
+        assert_eq!(merged.pk_to_idx.len(), 4);
+        for (key, index) in [(pk(0), 0), (pk(1), 1), (pk(2), 2), (pk(3), 3)] {
+            assert_eq!(merged.get_index_from_public_key(&key), Some(index));
+        }
+    }
+
+    /// Test the rendering of a 2-of-3 account
+    #[test]
+    fn test_account_display() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let account = Account {
+            public_keys_map: AccountPublicKeysMap::from_iter([
+                pk(0),
+                pk(1),
+                pk(2),
+            ]),
+            threshold: 2,
+            address: established_address_1(),
+        };
+        assert_eq!(
+            account.to_string(),
+            format!(
+                "Address: {}\nThreshold: 2 of 3\nPublic keys:\n- 0: {}\n- 1: \
+                 {}\n- 2: {}",
+                established_address_1(),
+                pk(0),
+                pk(1),
+                pk(2),
+            )
+        );
+    }
+
+    /// Test that the schema of an account, including its public keys bimap,
+    /// can be generated
+    #[test]
+    fn test_account_schema() {
+        let container = borsh::schema_container_of::<Account>();
+        assert!(container.validate().is_ok());
+        assert!(container.get_definition("AccountPublicKeysMap").is_some());
+    }
+
+    /// Test the selection of the signers of a 2-of-3 account
+    #[test]
+    fn test_minimal_authorizing_set() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let account = Account {
+            public_keys_map: AccountPublicKeysMap::from_iter([
+                pk(0),
+                pk(1),
+                pk(2),
+            ]),
+            threshold: 2,
+            address: established_address_1(),
+        };
+
+        // Keys foreign to the account and duplicates are ignored
+        assert_eq!(
+            account.minimal_authorizing_set(&[pk(2), pk(3), pk(2), pk(1)]),
+            Some(vec![pk(1), pk(2)])
+        );
+        assert_eq!(
+            account.minimal_authorizing_set(&[pk(2), pk(0), pk(1)]),
+            Some(vec![pk(0), pk(1)])
+        );
+        assert_eq!(account.minimal_authorizing_set(&[pk(1), pk(3)]), None);
+        assert_eq!(account.minimal_authorizing_set(&[]), None);
+    }
+
+    /// Test that no key can be added to a map at capacity
+    #[test]
+    fn test_can_add_key_at_capacity() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let mut map = AccountPublicKeysMap::from_iter(
+            (0..MAX_ACCOUNT_KEYS as u64 - 1).map(pk),
+        );
+        assert!(map.can_add_key());
+
+        map.insert_keys(vec![pk(MAX_ACCOUNT_KEYS as u64)]).unwrap();
+        assert!(!map.can_add_key());
+        assert_eq!(
+            map.insert_keys(vec![pk(MAX_ACCOUNT_KEYS as u64 + 1)]),
+            Err(AccountError::TooManyKeys(MAX_ACCOUNT_KEYS + 1))
+        );
+    }
+
+    /// Test that an account can hold exactly one key per `u8` index
+    #[test]
+    fn test_public_keys_map_from_max_keys() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let map = AccountPublicKeysMap::from_iter((0..256).map(pk));
+        assert_eq!(map.idx_to_pk.len(), 256);
+        assert_eq!(map.get_public_key_from_index(u8::MAX), Some(pk(255)));
+        assert!(!map.can_add_key());
+    }
+
+    /// Test that collecting more keys than there are `u8` indices panics
+    #[test]
+    #[should_panic(expected = "Only up to 256 signers are allowed")]
+    fn test_public_keys_map_from_too_many_keys() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let _ = AccountPublicKeysMap::from_iter((0..257).map(pk));
     }
 }
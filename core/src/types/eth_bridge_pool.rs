@@ -8,7 +8,9 @@ use borsh_ext::BorshSerializeExt;
 use ethabi::token::Token;
 use serde::{Deserialize, Serialize};
 
+use crate::ledger::eth_bridge::storage::bridge_pool::get_pending_key;
 use crate::ledger::eth_bridge::storage::wrapped_erc20s;
+use crate::ledger::storage_api::{self, StorageRead};
 use crate::types::address::Address;
 use crate::types::eth_abi::Encode;
 use crate::types::ethereum_events::{
@@ -24,6 +26,10 @@ const VERSION: u8 = 1;
 /// A namespace used in our Ethereuem smart contracts
 const NAMESPACE: &str = "transfer";
 
+/// The maximum number of transfers that can be added to the Bridge pool by a
+/// single transaction
+pub const MAX_PENDING_TRANSFERS_PER_TX: usize = 32;
+
 /// Transfer to Ethereum kinds.
 #[derive(
     Copy,
@@ -177,14 +183,7 @@ impl PendingTransfer {
     /// Get a token [`Address`] from this [`PendingTransfer`].
     #[inline]
     pub fn token_address(&self) -> Address {
-        match &self.transfer.kind {
-            TransferToEthereumKind::Erc20 => {
-                wrapped_erc20s::token(&self.transfer.asset)
-            }
-            TransferToEthereumKind::Nut => {
-                wrapped_erc20s::nut(&self.transfer.asset)
-            }
-        }
+        wrapped_erc20s::asset_address(self.transfer.kind, &self.transfer.asset)
     }
 
     /// Retrieve a reference to the appendix of this [`PendingTransfer`].
@@ -214,6 +213,97 @@ impl PendingTransfer {
         let gas_fee = (*appendix.gas_fee).clone();
         Self { transfer, gas_fee }
     }
+
+    /// Get the storage key of this [`PendingTransfer`] in the Bridge pool.
+    #[inline]
+    pub fn pending_key(&self) -> Key {
+        get_pending_key(self)
+    }
+
+    /// Check if this [`PendingTransfer`] is in the Bridge pool.
+    pub fn is_in_pool<S>(&self, storage: &S) -> storage_api::Result<bool>
+    where
+        S: StorageRead,
+    {
+        storage.has_key(&self.pending_key())
+    }
+}
+
+/// The tag prefixing the encoding of a [`BridgePoolTxData::Batch`].
+///
+/// An encoded [`PendingTransfer`] starts with the tag of its
+/// [`TransferToEthereumKind`], hence it can never start with this byte.
+const PENDING_TRANSFER_BATCH_TAG: u8 = 0xff;
+
+/// The data of a transaction adding transfers to the Bridge pool.
+///
+/// A single transfer is encoded as a bare [`PendingTransfer`], such
+/// that its encoding is left unchanged, whereas a batch of transfers
+/// is prefixed with a tag that disambiguates it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BridgePoolTxData {
+    /// A single transfer to add to the Bridge pool.
+    Single(PendingTransfer),
+    /// A batch of transfers to add to the Bridge pool.
+    Batch(Vec<PendingTransfer>),
+}
+
+impl BorshSerialize for BridgePoolTxData {
+    fn serialize<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        match self {
+            Self::Single(transfer) => transfer.serialize(writer),
+            Self::Batch(transfers) => {
+                PENDING_TRANSFER_BATCH_TAG.serialize(writer)?;
+                transfers.serialize(writer)
+            }
+        }
+    }
+}
+
+impl BorshDeserialize for BridgePoolTxData {
+    fn deserialize_reader<R: std::io::Read>(
+        reader: &mut R,
+    ) -> std::io::Result<Self> {
+        let tag = u8::deserialize_reader(reader)?;
+        if tag == PENDING_TRANSFER_BATCH_TAG {
+            Vec::deserialize_reader(reader).map(Self::Batch)
+        } else {
+            let mut reader = std::io::Read::chain(&[tag][..], reader);
+            PendingTransfer::deserialize_reader(&mut reader).map(Self::Single)
+        }
+    }
+}
+
+impl BridgePoolTxData {
+    /// Get the transfers to add to the Bridge pool.
+    pub fn into_transfers(self) -> Vec<PendingTransfer> {
+        match self {
+            Self::Single(transfer) => vec![transfer],
+            Self::Batch(transfers) => transfers,
+        }
+    }
+}
+
+/// Decode the transfers added to the Bridge pool from the data of a
+/// transaction, either a single [`PendingTransfer`] or a non-empty batch of
+/// at most [`MAX_PENDING_TRANSFERS_PER_TX`] of them.
+pub fn decode_pending_transfers(
+    data: &[u8],
+) -> std::io::Result<Vec<PendingTransfer>> {
+    let transfers = BridgePoolTxData::try_from_slice(data)?.into_transfers();
+    if transfers.is_empty() || transfers.len() > MAX_PENDING_TRANSFERS_PER_TX {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!(
+                "Invalid number of transfers to add to the Bridge pool: {}",
+                transfers.len()
+            ),
+        ));
+    }
+    Ok(transfers)
 }
 
 impl From<&PendingTransfer> for ethbridge_structs::Erc20Transfer {
@@ -379,8 +469,13 @@ pub mod testing {
 #[cfg(test)]
 mod test_eth_bridge_pool_types {
     use super::*;
+    use crate::ledger::eth_bridge::storage::bridge_pool::BridgePoolTree;
+    use crate::ledger::storage::testing::TestWlStorage;
+    use crate::ledger::storage_api::StorageWrite;
     use crate::types::address::nam;
     use crate::types::address::testing::established_address_1;
+    use crate::types::keccak::keccak_hash;
+    use crate::types::storage::BlockHeight;
 
     /// Test that [`PendingTransfer`] and [`TransferToEthereum`]
     /// have the same keccak hash, after being ABI encoded.
@@ -403,4 +498,121 @@ mod test_eth_bridge_pool_types {
         let event: TransferToEthereumEvent = (&pending).into();
         assert_eq!(pending.keccak256(), event.keccak256());
     }
+
+    /// Test that the leaf hash of a [`PendingTransfer`] in the Bridge pool's
+    /// Merkle tree is the keccak hash of its ABI encoding.
+    #[test]
+    fn test_bridge_pool_leaf_hash() {
+        let pending = PendingTransfer {
+            transfer: TransferToEthereum {
+                kind: TransferToEthereumKind::Erc20,
+                amount: 10u64.into(),
+                asset: EthAddress([0xaa; 20]),
+                recipient: EthAddress([0xbb; 20]),
+                sender: established_address_1(),
+            },
+            gas_fee: GasFee {
+                token: nam(),
+                amount: 10u64.into(),
+                payer: established_address_1(),
+            },
+        };
+        let expected = keccak_hash(ethabi::encode(&[
+            Token::Uint(1u8.into()),
+            Token::String("transfer".into()),
+            Token::Address([0xaa; 20].into()),
+            Token::Address([0xbb; 20].into()),
+            Token::Uint(10u64.into()),
+            Token::FixedBytes(pending.appendix().checksum().0.into()),
+        ]));
+        assert_eq!(pending.keccak256(), expected);
+
+        let mut tree = BridgePoolTree::default();
+        tree.insert_key(&Key::from(&pending), BlockHeight(1))
+            .expect("Test failed");
+        assert_eq!(tree.store().keys().collect::<Vec<_>>(), vec![&expected]);
+        assert_eq!(
+            tree.get_membership_proof(vec![pending])
+                .expect("Test failed")
+                .leaves
+                .iter()
+                .map(|transfer| transfer.keccak256())
+                .collect::<Vec<_>>(),
+            vec![expected]
+        );
+    }
+
+    /// Test checking if a [`PendingTransfer`] is in the Bridge pool.
+    #[test]
+    fn test_is_in_pool() {
+        let pending = PendingTransfer {
+            transfer: TransferToEthereum {
+                kind: TransferToEthereumKind::Erc20,
+                amount: 10u64.into(),
+                asset: EthAddress([0xaa; 20]),
+                recipient: EthAddress([0xbb; 20]),
+                sender: established_address_1(),
+            },
+            gas_fee: GasFee {
+                token: nam(),
+                amount: 10u64.into(),
+                payer: established_address_1(),
+            },
+        };
+        let mut storage = TestWlStorage::default();
+        assert_eq!(pending.pending_key(), get_pending_key(&pending));
+        assert!(!pending.is_in_pool(&storage).unwrap());
+
+        storage.write(&pending.pending_key(), &pending).unwrap();
+        assert!(pending.is_in_pool(&storage).unwrap());
+    }
+
+    /// Test decoding a single transfer and batches of transfers.
+    #[test]
+    fn test_decode_pending_transfers() {
+        let pending = |amount: u64| PendingTransfer {
+            transfer: TransferToEthereum {
+                kind: TransferToEthereumKind::Erc20,
+                amount: amount.into(),
+                asset: EthAddress([0xaa; 20]),
+                recipient: EthAddress([0xbb; 20]),
+                sender: established_address_1(),
+            },
+            gas_fee: GasFee {
+                token: nam(),
+                amount: 10u64.into(),
+                payer: established_address_1(),
+            },
+        };
+
+        let single = pending(1);
+        assert_eq!(
+            decode_pending_transfers(&single.serialize_to_vec()).unwrap(),
+            vec![single]
+        );
+        assert_eq!(
+            BridgePoolTxData::Single(single.clone()).serialize_to_vec(),
+            single.serialize_to_vec()
+        );
+        let batch = vec![pending(1), pending(2)];
+        assert_eq!(
+            decode_pending_transfers(
+                &BridgePoolTxData::Batch(batch.clone()).serialize_to_vec()
+            )
+            .unwrap(),
+            batch
+        );
+        // an untagged batch is not a valid encoding
+        assert!(decode_pending_transfers(&batch.serialize_to_vec()).is_err());
+        let empty = BridgePoolTxData::Batch(vec![]);
+        assert!(decode_pending_transfers(&empty.serialize_to_vec()).is_err());
+        let too_many = BridgePoolTxData::Batch(
+            (0..=MAX_PENDING_TRANSFERS_PER_TX as u64)
+                .map(pending)
+                .collect(),
+        );
+        assert!(
+            decode_pending_transfers(&too_many.serialize_to_vec()).is_err()
+        );
+    }
 }
@@ -27,6 +27,37 @@ pub const EVENT_TYPE_PACKET: &str = "fungible_token_packet";
 /// The event type defined in ibc-rs for IBC denom
 pub const EVENT_TYPE_DENOM_TRACE: &str = "denomination_trace";
 
+/// IBC event types which can be queried from the IBC context
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IbcEventType {
+    /// The event for receiving a token, i.e. [`EVENT_TYPE_PACKET`]
+    Packet,
+    /// The event for an IBC denom, i.e. [`EVENT_TYPE_DENOM_TRACE`]
+    DenomTrace,
+}
+
+impl IbcEventType {
+    /// Get the event type string defined in ibc-rs
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Packet => EVENT_TYPE_PACKET,
+            Self::DenomTrace => EVENT_TYPE_DENOM_TRACE,
+        }
+    }
+}
+
+impl AsRef<str> for IbcEventType {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Display for IbcEventType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
 /// IBC token hash derived from a denomination.
 #[derive(
     Debug,
@@ -48,6 +48,12 @@ pub fn pks_handle(owner: &Address) -> LazyMap<u8, common::PublicKey> {
     LazyMap::open(pks_key_prefix(owner))
 }
 
+/// Obtain the storage key of the public key revealed for an implicit account.
+/// The public key is revealed when this key is present in storage.
+pub fn is_pk_revealed_key(owner: &Address) -> storage::Key {
+    pks_handle(owner).get_data_key(&0)
+}
+
 /// Check if the given storage key is a public key. If it is, returns the owner.
 pub fn is_pks_key(key: &Key) -> Option<&Address> {
     match &key.segments[..] {
@@ -1087,6 +1087,15 @@ impl Epoch {
         }
     }
 
+    /// Checked epoch addition. Computes self + rhs, returning None if
+    /// overflow occurred.
+    #[must_use = "this returns the result of the operation, without modifying \
+                  the original"]
+    pub fn checked_add(self, rhs: impl Into<Epoch>) -> Option<Self> {
+        let Epoch(rhs) = rhs.into();
+        self.0.checked_add(rhs).map(Self)
+    }
+
     /// Checked epoch subtraction. Computes self - rhs, returning default
     /// `Epoch(0)` if overflow occurred.
     #[must_use = "this returns the result of the operation, without modifying \
@@ -1648,6 +1657,16 @@ pub mod tests {
         }
     }
 
+    #[test]
+    fn test_epoch_checked_arithmetic() {
+        assert_eq!(Epoch(3).checked_sub(Epoch(3)), Some(Epoch(0)));
+        assert_eq!(Epoch(3).checked_sub(4), None);
+        assert_eq!(Epoch(0).checked_sub(1), None);
+        assert_eq!(Epoch(u64::MAX - 1).checked_add(1), Some(Epoch(u64::MAX)));
+        assert_eq!(Epoch(u64::MAX).checked_add(1), None);
+        assert_eq!(Epoch(1).checked_add(Epoch(u64::MAX)), None);
+    }
+
     #[test]
     fn test_predecessor_epochs_and_heights() {
         let mut epochs = Epochs {
@@ -157,6 +157,12 @@ impl Amount {
             .map(|result| Self { raw: result })
     }
 
+    /// Saturating subtraction. Returns zero on underflow.
+    #[must_use]
+    pub fn saturating_sub(&self, amount: Amount) -> Self {
+        self.checked_sub(amount).unwrap_or_default()
+    }
+
     /// Create amount from the absolute value of `Change`.
     pub fn from_change(change: Change) -> Self {
         Self { raw: change.abs() }
@@ -244,6 +250,36 @@ impl Amount {
         DenominatedAmount::native(self)
     }
 
+    /// Parse a decimal string, such as `"1.5"`, into an amount with the
+    /// given number of decimal places. Either the integer or the fractional
+    /// part may be omitted (e.g. `"1."` or `".5"`), but not both. Trailing
+    /// zeros of the fractional part are ignored, whereas any other fractional
+    /// digits beyond `decimals` are rejected.
+    pub fn from_denominated_str(
+        string: &str,
+        decimals: u8,
+    ) -> Result<Self, AmountParseError> {
+        let (integer, fraction) =
+            string.split_once('.').unwrap_or((string, ""));
+        if integer.is_empty() && fraction.is_empty()
+            || !integer
+                .chars()
+                .chain(fraction.chars())
+                .all(|c| c.is_ascii_digit())
+        {
+            return Err(AmountParseError::NotNumeric);
+        }
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.len() > decimals as usize {
+            return Err(AmountParseError::ScaleTooLarge(
+                fraction.len() as u32,
+                decimals,
+            ));
+        }
+        DenominatedAmount::from_str(&format!("{integer}.{fraction}"))?
+            .scale(decimals)
+    }
+
     /// Convert to an [`Amount`] under the assumption that the input
     /// string encodes all necessary decimal places.
     pub fn from_string_precise(string: &str) -> Result<Self, AmountParseError> {
@@ -1570,6 +1606,15 @@ mod tests {
         assert_eq!(amount, Amount::from_uint(340, 0).expect("Test failed"));
     }
 
+    #[test]
+    fn test_amount_saturating_sub() {
+        let amount = Amount::from(10);
+        assert_eq!(amount.saturating_sub(Amount::from(4)), Amount::from(6));
+        assert_eq!(amount.saturating_sub(amount), Amount::zero());
+        assert_eq!(amount.saturating_sub(Amount::from(11)), Amount::zero());
+        assert_eq!(amount.checked_sub(Amount::from(11)), None);
+    }
+
     #[test]
     fn test_from_masp_denominated() {
         let uint = Uint([15u64, 16, 17, 18]);
@@ -1685,4 +1730,49 @@ mod tests {
             Ordering::Less
         );
     }
+
+    #[test]
+    fn test_amount_from_denominated_str() {
+        let parse =
+            |s: &str, decimals| Amount::from_denominated_str(s, decimals);
+        let uint = |v: u64| Amount::from_uint(v, 0).expect("Test failed");
+
+        assert_eq!(parse("1.5", 6).expect("Test failed"), uint(1_500_000));
+        assert_eq!(parse("1", 6).expect("Test failed"), uint(1_000_000));
+        assert_eq!(parse("12", 0).expect("Test failed"), uint(12));
+        // missing fractional part
+        assert_eq!(parse("1.", 6).expect("Test failed"), uint(1_000_000));
+        // missing integer part
+        assert_eq!(parse(".5", 6).expect("Test failed"), uint(500_000));
+        assert_eq!(parse(".5", 1).expect("Test failed"), uint(5));
+        // leading zeros
+        assert_eq!(parse("007.25", 2).expect("Test failed"), uint(725));
+        assert_eq!(parse("0.000001", 6).expect("Test failed"), uint(1));
+        // trailing zeros do not add precision
+        assert_eq!(parse("1.5000", 1).expect("Test failed"), uint(15));
+        assert_eq!(parse("3.000", 0).expect("Test failed"), uint(3));
+        assert_eq!(parse("0.0", 0).expect("Test failed"), Amount::zero());
+
+        // over-precise inputs
+        assert!(matches!(
+            parse("1.25", 1),
+            Err(AmountParseError::ScaleTooLarge(2, 1))
+        ));
+        assert!(matches!(
+            parse("0.0000001", 6),
+            Err(AmountParseError::ScaleTooLarge(7, 6))
+        ));
+        assert!(matches!(
+            parse("1.5", 0),
+            Err(AmountParseError::ScaleTooLarge(1, 0))
+        ));
+
+        // malformed inputs
+        for input in ["", ".", "1.5.0", "-1", "+1", "1,5", "1a", " 1", "1e6"] {
+            assert!(
+                matches!(parse(input, 6), Err(AmountParseError::NotNumeric)),
+                "{input:?} should not parse"
+            );
+        }
+    }
 }
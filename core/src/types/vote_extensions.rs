@@ -5,8 +5,10 @@ pub mod ethereum_events;
 pub mod validator_set_update;
 
 use borsh::{BorshDeserialize, BorshSchema, BorshSerialize};
+use thiserror::Error;
 
 use crate::proto::Signed;
+use crate::types::transaction::protocol::EthereumTxData;
 
 /// This type represents the data we pass to the extension of
 /// a vote at the PreCommit phase of Tendermint.
@@ -21,3 +23,71 @@ pub struct VoteExtension {
     /// Vote extension data related with validator set updates.
     pub validator_set_update: Option<validator_set_update::SignedVext>,
 }
+
+/// Error reassembling a [`VoteExtension`] from its protocol txs.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum VextReassemblyError {
+    /// More than one protocol tx of the same kind was given
+    #[error("Found more than one {0} vote extension")]
+    Duplicate(&'static str),
+    /// A protocol tx which is not part of a vote extension was given
+    #[error("The protocol tx {0} is not part of a vote extension")]
+    UnknownVariant(&'static str),
+}
+
+impl VoteExtension {
+    /// Reassemble a [`VoteExtension`] from the protocol txs it was
+    /// flattened into, slotting each one into the right field.
+    pub fn from_protocol_txs(
+        txs: impl IntoIterator<Item = EthereumTxData>,
+    ) -> Result<Self, VextReassemblyError> {
+        /// Fill in a field of the vote extension, unless it was already set.
+        fn fill<T>(
+            field: &mut Option<T>,
+            value: T,
+            kind: &'static str,
+        ) -> Result<(), VextReassemblyError> {
+            if field.replace(value).is_some() {
+                return Err(VextReassemblyError::Duplicate(kind));
+            }
+            Ok(())
+        }
+
+        let mut ext = Self {
+            ethereum_events: None,
+            bridge_pool_root: None,
+            validator_set_update: None,
+        };
+        for tx in txs {
+            match tx {
+                EthereumTxData::EthEventsVext(vext) => {
+                    fill(&mut ext.ethereum_events, vext, "Ethereum events")?
+                }
+                EthereumTxData::BridgePoolVext(vext) => {
+                    fill(&mut ext.bridge_pool_root, vext, "Bridge pool root")?
+                }
+                EthereumTxData::ValSetUpdateVext(vext) => fill(
+                    &mut ext.validator_set_update,
+                    vext,
+                    "validator set update",
+                )?,
+                EthereumTxData::EthereumEvents(_) => {
+                    return Err(VextReassemblyError::UnknownVariant(
+                        "EthereumEvents",
+                    ));
+                }
+                EthereumTxData::BridgePool(_) => {
+                    return Err(VextReassemblyError::UnknownVariant(
+                        "BridgePool",
+                    ));
+                }
+                EthereumTxData::ValidatorSetUpdate(_) => {
+                    return Err(VextReassemblyError::UnknownVariant(
+                        "ValidatorSetUpdate",
+                    ));
+                }
+            }
+        }
+        Ok(ext)
+    }
+}
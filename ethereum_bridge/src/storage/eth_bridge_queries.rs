@@ -5,7 +5,7 @@ use namada_core::ledger::eth_bridge::storage::{
 };
 use namada_core::ledger::storage;
 use namada_core::ledger::storage::{StoreType, WlStorage};
-use namada_core::ledger::storage_api::StorageRead;
+use namada_core::ledger::storage_api::{self, StorageRead};
 use namada_core::types::address::Address;
 use namada_core::types::eth_abi::Encode;
 use namada_core::types::eth_bridge_pool::PendingTransfer;
@@ -80,6 +80,32 @@ pub enum EthBridgeEnabled {
     ),
 }
 
+impl EthBridgeStatus {
+    /// Check if the Ethereum bridge is active at the given [`Epoch`].
+    pub fn is_active_at(&self, queried_epoch: Epoch) -> bool {
+        match self {
+            EthBridgeStatus::Disabled => false,
+            EthBridgeStatus::Enabled(EthBridgeEnabled::AtGenesis) => true,
+            EthBridgeStatus::Enabled(EthBridgeEnabled::AtEpoch(
+                enabled_epoch,
+            )) => queried_epoch >= *enabled_epoch,
+        }
+    }
+}
+
+/// Check if the Ethereum bridge is active at the epoch of the current block,
+/// from its status in storage. The bridge is inactive if its status is
+/// absent.
+pub fn is_bridge_active<S>(storage: &S) -> storage_api::Result<bool>
+where
+    S: StorageRead,
+{
+    let Some(status) = storage.read::<EthBridgeStatus>(&active_key())? else {
+        return Ok(false);
+    };
+    Ok(status.is_active_at(storage.get_block_epoch()?))
+}
+
 /// Methods used to query blockchain Ethereum bridge related state.
 pub trait EthBridgeQueries {
     /// The underlying storage type.
@@ -173,13 +199,7 @@ where
     /// Behaves exactly like [`Self::is_bridge_active`], but performs
     /// the check at the given [`Epoch`].
     pub fn is_bridge_active_at(self, queried_epoch: Epoch) -> bool {
-        match self.check_bridge_status() {
-            EthBridgeStatus::Disabled => false,
-            EthBridgeStatus::Enabled(EthBridgeEnabled::AtGenesis) => true,
-            EthBridgeStatus::Enabled(EthBridgeEnabled::AtEpoch(
-                enabled_epoch,
-            )) => queried_epoch >= enabled_epoch,
-        }
+        self.check_bridge_status().is_active_at(queried_epoch)
     }
 
     /// Get the nonce of the next transfers to Namada event to be processed.
@@ -344,6 +344,29 @@ where
     }
 }
 
+/// Get the minimum number of confirmations an Ethereum event must reach from
+/// storage, defaulting to that of [`MinimumConfirmations::default`] if the
+/// parameter is absent.
+pub fn read_min_confirmations<S>(storage: &S) -> Result<u64>
+where
+    S: StorageRead,
+{
+    let min_confirmations_key = bridge_storage::min_confirmations_key();
+    match StorageRead::read::<MinimumConfirmations>(
+        storage,
+        &min_confirmations_key,
+    ) {
+        Ok(min_confirmations) => {
+            Ok(NonZeroU64::from(min_confirmations.unwrap_or_default()).get())
+        }
+        Err(e) => Err(eyre!(
+            "Failed to read storage when fetching the minimum confirmations \
+             with: {}",
+            e.to_string()
+        )),
+    }
+}
+
 /// Reads the value of `key` from `storage` and deserializes it, or panics
 /// otherwise.
 fn must_read_key<DB, H, T: BorshDeserialize>(
@@ -480,4 +503,24 @@ mod tests {
         // This should panic as the other config values are not written
         EthereumOracleConfig::read(&wl_storage);
     }
+
+    /// Test reading the minimum confirmations when stored and when absent
+    #[test]
+    fn test_read_min_confirmations() {
+        let mut wl_storage = TestWlStorage::default();
+        assert_eq!(
+            read_min_confirmations(&wl_storage).unwrap(),
+            NonZeroU64::from(MinimumConfirmations::default()).get()
+        );
+
+        let min_confirmations =
+            MinimumConfirmations::from(NonZeroU64::new(42).unwrap());
+        wl_storage
+            .write_bytes(
+                &bridge_storage::min_confirmations_key(),
+                min_confirmations.serialize_to_vec(),
+            )
+            .unwrap();
+        assert_eq!(read_min_confirmations(&wl_storage).unwrap(), 42);
+    }
 }
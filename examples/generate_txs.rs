@@ -14,7 +14,7 @@ async fn main() -> Result<(), Reason> {
     let mut test_vectors = vec![];
     for i in 0..1000 {
         let (tx, tx_data) = arb_tx().new_tree(&mut runner)?.current();
-        let mut ledger_vector = to_ledger_vector(&wallet, &tx)
+        let mut ledger_vector = to_ledger_vector(&wallet, &tx, false)
             .await
             .expect("unable to construct test vector");
         ledger_vector.name = format!("{}_{}", i, ledger_vector.name);
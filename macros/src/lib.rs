@@ -101,7 +101,7 @@ pub fn transaction(attr: TokenStream, input: TokenStream) -> TokenStream {
 ///     ctx: &Ctx,
 ///     tx_data: Vec<u8>,
 ///     addr: Address,
-///     keys_changed: BTreeSet<storage::Key>,
+///     keys_changed: &BTreeSet<storage::Key>,
 ///     verifiers: BTreeSet<Address>
 /// ) -> VpResult
 /// ```
@@ -189,10 +189,11 @@ pub fn validity_predicate(
             // have interface identical with the native VPs, in which the
             // context is explicit, in here we're just using an empty `Ctx`
             // to "fake" it.
-            let ctx = unsafe { namada_vp_prelude::Ctx::new() };
+            let ctx = unsafe { namada_vp_prelude::Ctx::new() }
+                .with_keys_changed(keys_changed);
 
             // run validation with the concrete type(s)
-            match #ident(&ctx, tx_data, addr, keys_changed, verifiers)
+            match #ident(&ctx, tx_data, addr, ctx.keys_changed(), verifiers)
             {
                 Ok(true) => 1,
                 Ok(false) => 0,
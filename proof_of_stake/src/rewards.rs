@@ -3,7 +3,10 @@
 use std::collections::{HashMap, HashSet};
 
 use namada_core::ledger::inflation;
-use namada_core::ledger::parameters::storage as params_storage;
+use namada_core::ledger::parameters::{
+    storage as params_storage, write_pos_inflation_amount_at_epoch,
+    write_staked_ratio_at_epoch,
+};
 use namada_core::ledger::storage_api::collections::lazy_map::NestedSubKey;
 use namada_core::ledger::storage_api::token::credit_tokens;
 use namada_core::ledger::storage_api::{
@@ -324,10 +327,10 @@ where
     )?;
 
     // Write new rewards parameters that will be used for the inflation of
-    // the current new epoch
-    storage
-        .write(&params_storage::get_pos_inflation_amount_key(), inflation)?;
-    storage.write(&params_storage::get_staked_ratio_key(), locked_ratio)?;
+    // the current new epoch, keeping a snapshot of their values for it
+    let current_epoch = last_epoch.next();
+    write_pos_inflation_amount_at_epoch(storage, current_epoch, &inflation)?;
+    write_staked_ratio_at_epoch(storage, current_epoch, &locked_ratio)?;
 
     Ok(())
 }
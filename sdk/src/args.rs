@@ -1855,6 +1855,11 @@ pub struct Tx<C: NamadaTypes = SdkTypes> {
     pub fee_token: C::Address,
     /// The optional spending key for fee unshielding
     pub fee_unshield: Option<C::TransferSource>,
+    /// Never unshield funds for fee payment, even if `fee_unshield` is set.
+    /// If the transparent balance can't cover the fees, the tx fails with a
+    /// balance too low error, unless `force` is set, in which case it is built
+    /// without any unshielding.
+    pub no_fee_unshield: bool,
     /// The max amount of gas used to process tx
     pub gas_limit: GasLimit,
     /// The optional expiration of the transaction
@@ -1966,6 +1971,13 @@ pub trait TxBuilder<C: NamadaTypes>: Sized {
             ..x
         })
     }
+    /// Never unshield funds for fee payment
+    fn no_fee_unshield(self, no_fee_unshield: bool) -> Self {
+        self.tx(|x| Tx {
+            no_fee_unshield,
+            ..x
+        })
+    }
     /// The max amount of gas used to process tx
     fn gas_limit(self, gas_limit: GasLimit) -> Self {
         self.tx(|x| Tx { gas_limit, ..x })
@@ -1,6 +1,7 @@
 //! Generic Error Type for all of the Shared Crate
 
 use namada_core::proto::Tx;
+use namada_core::types::account::AccountError;
 use namada_core::types::address::Address;
 use namada_core::types::dec::Dec;
 use namada_core::types::ethereum_events::EthAddress;
@@ -98,6 +99,22 @@ pub enum QueryError {
     CatchingUp,
 }
 
+/// Errors of the local verification of a transaction's signatures
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum SignatureVerifyError {
+    /// A signature over the raw header is invalid
+    #[error("Invalid signature over the raw header: {0}")]
+    InvalidSignature(String),
+    /// Not enough valid signatures over the raw header
+    #[error("Signature threshold not met: {verified} < {threshold}")]
+    ThresholdNotMet {
+        /// The number of public keys with a valid signature
+        verified: usize,
+        /// The threshold of the signing account
+        threshold: u8,
+    },
+}
+
 /// Errors that deal with Decoding, Encoding, or Conversions
 #[derive(Error, Debug, Clone)]
 pub enum EncodingError {
@@ -293,12 +310,18 @@ pub enum TxError {
     /// Account threshold is not set
     #[error("Account threshold must be set.")]
     MissingAccountThreshold,
+    /// The account update would leave the account unable to sign
+    #[error("Invalid account update: {0}")]
+    InvalidAccountUpdate(AccountError),
     /// Not enough signature
     #[error("Account threshold is {0} but the valid signatures are {1}.")]
     MissingSigningKeys(u8, u8),
     /// Invalid owner account
     #[error("The source account {0} is not valid or doesn't exist.")]
     InvalidAccount(String),
+    /// An internal address has no signing keys
+    #[error("Internal address {0} doesn't have any signing keys.")]
+    InternalAddressHasNoKeys(Address),
     /// The redelegation amount is larger than the remaining bond amount
     #[error(
         "The redelegation amount is larger than the remaining bond amount. \
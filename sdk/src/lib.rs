@@ -133,6 +133,7 @@ pub trait Namada: Sized + MaybeSync + MaybeSend {
             wrapper_fee_payer: None,
             fee_token: self.native_token(),
             fee_unshield: None,
+            no_fee_unshield: false,
             gas_limit: GasLimit::from(20_000),
             expiration: None,
             disposable_signing_key: false,
@@ -653,6 +654,7 @@ where
                 wrapper_fee_payer: None,
                 fee_token: native_token,
                 fee_unshield: None,
+                no_fee_unshield: false,
                 gas_limit: GasLimit::from(20_000),
                 expiration: None,
                 disposable_signing_key: false,
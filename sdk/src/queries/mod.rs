@@ -97,7 +97,7 @@ pub fn require_no_data(request: &RequestQuery) -> storage_api::Result<()> {
 
 /// Queries testing helpers
 #[cfg(any(test, feature = "testing"))]
-mod testing {
+pub(crate) mod testing {
 
     use namada_core::ledger::storage::testing::TestWlStorage;
     use namada_core::types::storage::BlockHeight;
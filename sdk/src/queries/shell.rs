@@ -11,7 +11,7 @@ use namada_core::hints;
 use namada_core::ledger::storage::traits::StorageHasher;
 use namada_core::ledger::storage::{DBIter, LastBlock, DB};
 use namada_core::ledger::storage_api::{self, ResultExt, StorageRead};
-use namada_core::types::account::{Account, AccountPublicKeysMap};
+use namada_core::types::account::Account;
 use namada_core::types::address::Address;
 use namada_core::types::hash::Hash;
 use namada_core::types::storage::{
@@ -509,22 +509,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let account_exists = storage_api::account::exists(ctx.wl_storage, &owner)?;
-
-    if account_exists {
-        let public_keys =
-            storage_api::account::public_keys(ctx.wl_storage, &owner)?;
-        let threshold =
-            storage_api::account::threshold(ctx.wl_storage, &owner)?;
-
-        Ok(Some(Account {
-            public_keys_map: AccountPublicKeysMap::from_iter(public_keys),
-            address: owner,
-            threshold: threshold.unwrap_or(1),
-        }))
-    } else {
-        Ok(None)
-    }
+    storage_api::account::get_account(ctx.wl_storage, &owner)
 }
 
 fn revealed<D, H, V, T>(
@@ -535,10 +520,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let public_keys =
-        storage_api::account::public_keys(ctx.wl_storage, &owner)?;
-
-    Ok(!public_keys.is_empty())
+    storage_api::key::has_revealed_pk(ctx.wl_storage, &owner)
 }
 
 #[cfg(test)]
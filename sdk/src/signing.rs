@@ -10,14 +10,17 @@ use masp_primitives::asset_type::AssetType;
 use masp_primitives::transaction::components::sapling::fees::{
     InputView, OutputView,
 };
+use namada_core::ledger::ibc::ibc_denom_token;
 use namada_core::ledger::parameters::storage as parameter_storage;
-use namada_core::proto::SignatureIndex;
+use namada_core::proto::{enforce_max_signatures, SignatureIndex};
 use namada_core::types::account::AccountPublicKeysMap;
 use namada_core::types::address::{
     masp_tx_key, Address, ImplicitAddress, InternalAddress, MASP,
 };
 use namada_core::types::key::*;
-use namada_core::types::masp::{ExtendedViewingKey, PaymentAddress};
+use namada_core::types::masp::{
+    ExtendedViewingKey, PaymentAddress, TransferSource,
+};
 use namada_core::types::storage::Epoch;
 use namada_core::types::token;
 use namada_core::types::token::Transfer;
@@ -43,12 +46,12 @@ use crate::core::ledger::governance::storage::vote::{
     StorageProposalVote, VoteType,
 };
 use crate::core::types::eth_bridge_pool::PendingTransfer;
-use crate::error::{EncodingError, Error, TxError};
+use crate::error::{EncodingError, Error, SignatureVerifyError, TxError};
 use crate::ibc::apps::transfer::types::msgs::transfer::MsgTransfer;
 use crate::ibc::primitives::proto::Any;
 use crate::io::*;
 use crate::masp::make_asset_type;
-use crate::proto::{MaspBuilder, Section, Tx};
+use crate::proto::{MaspBuilder, Section, Signer, Tx};
 use crate::rpc::validate_amount;
 use crate::tx::{
     TX_BECOME_VALIDATOR_WASM, TX_BOND_WASM, TX_BRIDGE_POOL_WASM,
@@ -66,7 +69,7 @@ use crate::wallet::{Wallet, WalletIo};
 use crate::{args, display_line, rpc, MaybeSend, Namada};
 
 /// A structure holding the signing data to craft a transaction
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct SigningTxData {
     /// The address owning the transaction
     pub owner: Option<Address>,
@@ -78,6 +81,25 @@ pub struct SigningTxData {
     pub account_public_keys_map: Option<AccountPublicKeysMap>,
     /// The public keys of the fee payer
     pub fee_payer: common::PublicKey,
+    /// Whether the owner is an implicit account whose public key hasn't been
+    /// revealed yet, in which case a reveal-pk tx must be submitted first
+    #[serde(default)]
+    pub requires_pk_reveal: bool,
+}
+
+impl SigningTxData {
+    /// Serialize the signing data to JSON, to be passed to another machine
+    /// for offline signing. It only holds public data.
+    pub fn to_json(&self) -> Result<String, Error> {
+        serde_json::to_string(self)
+            .map_err(|err| Error::from(EncodingError::Serde(err.to_string())))
+    }
+
+    /// Deserialize the signing data from JSON
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        serde_json::from_str(json)
+            .map_err(|err| Error::from(EncodingError::Serde(err.to_string())))
+    }
 }
 
 /// Find the public key for the given address and try to load the keypair
@@ -114,10 +136,59 @@ pub async fn find_pk(
                     err
                 ))
             })?),
-        Address::Internal(_) => other_err(format!(
-            "Internal address {} doesn't have any signing keys.",
-            addr
-        )),
+        Address::Internal(_) => {
+            Err(Error::from(TxError::InternalAddressHasNoKeys(addr.clone())))
+        }
+    }
+}
+
+/// Find the public keys that can be used to sign for the given address,
+/// ordered by their index. The keys of an established account are looked up
+/// from the ledger, and those held in the wallet are used, up to the
+/// threshold of the account, so that a multisig account can be signed for
+/// with any of its keys. If the wallet holds none of them, the key at index 0
+/// is used, as for the other addresses.
+pub async fn find_pks_for_account(
+    context: &impl Namada,
+    addr: &Address,
+) -> Result<Vec<common::PublicKey>, Error> {
+    match addr {
+        Address::Established(_) => {
+            display_line!(
+                context.io(),
+                "Looking-up public keys of {} from the ledger...",
+                addr.encode()
+            );
+            let account = rpc::get_account_info(context.client(), addr)
+                .await?
+                .filter(|account| !account.public_keys_map.idx_to_pk.is_empty())
+                .ok_or(Error::Other(format!(
+                    "No public key found for the address {}",
+                    addr.encode()
+                )))?;
+            let public_keys: Vec<_> = account
+                .public_keys_map
+                .idx_to_pk
+                .into_iter()
+                .collect::<BTreeMap<_, _>>()
+                .into_values()
+                .collect();
+            let wallet = context.wallet().await;
+            let usable_keys: Vec<_> = public_keys
+                .iter()
+                .filter(|pk| {
+                    wallet.find_public_key_by_pkh(&(*pk).into()).is_ok()
+                })
+                .take(usize::from(account.threshold))
+                .cloned()
+                .collect();
+            if usable_keys.is_empty() {
+                Ok(public_keys.into_iter().take(1).collect())
+            } else {
+                Ok(usable_keys)
+            }
+        }
+        _ => Ok(vec![find_pk(context, addr).await?]),
     }
 }
 
@@ -156,7 +227,7 @@ pub async fn tx_signers(
     default: Option<Address>,
 ) -> Result<Vec<common::PublicKey>, Error> {
     let signer = if !&args.signing_keys.is_empty() {
-        return Ok(args.signing_keys.clone());
+        return Ok(dedup_public_keys(args.signing_keys.iter().cloned()));
     } else {
         // Otherwise use the signer determined by the caller
         default
@@ -166,7 +237,7 @@ pub async fn tx_signers(
     match signer {
         Some(signer) if signer == MASP => Ok(vec![masp_tx_key().ref_to()]),
 
-        Some(signer) => Ok(vec![find_pk(context, &signer).await?]),
+        Some(signer) => find_pks_for_account(context, &signer).await,
         None => other_err(
             "All transactions must be signed; please either specify the key \
              or the address from which to look up the signing key."
@@ -175,6 +246,17 @@ pub async fn tx_signers(
     }
 }
 
+/// Remove the duplicates from the given public keys, keeping the first
+/// occurrence of each key in order
+fn dedup_public_keys(
+    keys: impl IntoIterator<Item = common::PublicKey>,
+) -> Vec<common::PublicKey> {
+    let mut seen = HashSet::new();
+    keys.into_iter()
+        .filter(|key| seen.insert(key.clone()))
+        .collect()
+}
+
 /// The different parts of a transaction that can be signed
 #[derive(Eq, Hash, PartialEq)]
 pub enum Signable {
@@ -195,6 +277,115 @@ pub async fn default_sign(
     )))
 }
 
+/// Whether a public key required to sign a transaction can be used to sign
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyReadiness {
+    /// The public key required to sign
+    pub public_key: common::PublicKey,
+    /// Whether a signature or a secret key is available for it
+    pub available: bool,
+}
+
+/// The outcome of a signing dry run
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SigningReadiness {
+    /// The readiness of every public key of the signing account
+    pub keys: Vec<KeyReadiness>,
+    /// The threshold of the signing account
+    pub threshold: u8,
+    /// The readiness of the fee payer's key
+    pub fee_payer: KeyReadiness,
+}
+
+impl SigningReadiness {
+    /// The number of signatures that can be collected for the account
+    pub fn available_count(&self) -> usize {
+        self.keys.iter().filter(|key| key.available).count()
+    }
+
+    /// Whether the signatures that can be collected meet the threshold
+    pub fn meets_threshold(&self) -> bool {
+        self.available_count() >= usize::from(self.threshold)
+    }
+}
+
+/// Check which of the keys required to sign a transaction are available,
+/// either as a supplied signature or as a secret key in the software wallet,
+/// without decrypting any key or producing any signature. The keys held by a
+/// hardware wallet are reported as unavailable.
+pub async fn sign_tx_dry_run<U: WalletIo>(
+    wallet: &RwLock<Wallet<U>>,
+    args: &args::Tx,
+    signing_data: &SigningTxData,
+) -> Result<SigningReadiness, Error> {
+    let mut signed_pubkeys = HashSet::new();
+    for bytes in &args.signatures {
+        let sigidx = SignatureIndex::deserialize(bytes).map_err(|err| {
+            Error::Encode(EncodingError::Decoding(err.to_string()))
+        })?;
+        signed_pubkeys.insert(sigidx.pubkey);
+    }
+
+    let wallet = wallet.read().await;
+    let readiness = |public_key: &common::PublicKey| KeyReadiness {
+        public_key: public_key.clone(),
+        available: signed_pubkeys.contains(public_key)
+            || *public_key == masp_tx_key().ref_to()
+            || wallet.store().find_key_by_pk(public_key).is_some(),
+    };
+
+    Ok(SigningReadiness {
+        keys: signing_data.public_keys.iter().map(readiness).collect(),
+        threshold: signing_data.threshold,
+        fee_payer: readiness(&signing_data.fee_payer),
+    })
+}
+
+/// Verify the signatures attached to the given transaction over its raw header
+/// against the indexed public keys of an account, and check that they meet the
+/// account's threshold. Meant to catch invalid signatures before submitting
+/// the transaction.
+pub fn verify_signatures(
+    tx: &Tx,
+    account_public_keys_map: &AccountPublicKeysMap,
+    threshold: u8,
+) -> Result<(), SignatureVerifyError> {
+    let raw_header_hash = tx.raw_header_hash();
+    let mut verified_pks = HashSet::new();
+    for section in &tx.sections {
+        let Section::Signature(signatures) = section else {
+            continue;
+        };
+        if !signatures.targets.contains(&raw_header_hash) {
+            continue;
+        }
+        // The signatures are checked against the given public keys map,
+        // whichever account the section claims to sign for
+        let signer = match &signatures.signer {
+            Signer::Address(addr) => Some(addr.clone()),
+            Signer::PubKeys(_) => None,
+        };
+        let matched = signatures
+            .verified_public_key_indices(
+                account_public_keys_map,
+                &signer,
+                &mut || Ok(()),
+            )
+            .map_err(|err| {
+                SignatureVerifyError::InvalidSignature(err.to_string())
+            })?;
+        verified_pks.extend(matched);
+    }
+    if verified_pks.len() >= usize::from(threshold) {
+        Ok(())
+    } else {
+        Err(SignatureVerifyError::ThresholdNotMet {
+            verified: verified_pks.len(),
+            threshold,
+        })
+    }
+}
+
 /// Sign a transaction with a given signing key or public key of a given signer.
 /// If no explicit signer given, use the `default`. If no `default` is given,
 /// Error.
@@ -267,7 +458,11 @@ where
 
     // Then try to sign the raw header using the hardware wallet
     for pubkey in signing_data.public_keys {
-        if !used_pubkeys.contains(&pubkey) && pubkey != signing_data.fee_payer {
+        // The fee payer signs the raw header along with the wrapper one,
+        // unless there is no wrapper to sign
+        if !used_pubkeys.contains(&pubkey)
+            && (pubkey != signing_data.fee_payer || !tx.requires_wrapper())
+        {
             if let Ok(ntx) = sign(
                 tx.clone(),
                 pubkey.clone(),
@@ -282,6 +477,11 @@ where
         }
     }
 
+    // Raw transactions, e.g. for dry-runs, have no fee header to sign
+    if !tx.requires_wrapper() {
+        return Ok(());
+    }
+
     // Then try signing the fee header with the software wallet otherwise use
     // the fallback
     let key = {
@@ -321,6 +521,7 @@ pub async fn aux_signing_data(
         vec![]
     };
 
+    let mut requires_pk_reveal = false;
     let (account_public_keys_map, threshold) = match &owner {
         Some(owner @ Address::Established(_)) => {
             let account =
@@ -333,10 +534,14 @@ pub async fn aux_signing_data(
                 )));
             }
         }
-        Some(Address::Implicit(_)) => (
-            Some(AccountPublicKeysMap::from_iter(public_keys.clone())),
-            1u8,
-        ),
+        Some(owner @ Address::Implicit(_)) => {
+            requires_pk_reveal =
+                !rpc::is_public_key_revealed(context.client(), owner).await?;
+            (
+                Some(AccountPublicKeysMap::from_iter(public_keys.clone())),
+                1u8,
+            )
+        }
         Some(owner @ Address::Internal(internal)) => match internal {
             InternalAddress::Masp => (None, 0u8),
             _ => {
@@ -348,6 +553,29 @@ pub async fn aux_signing_data(
         None => (None, 0u8),
     };
 
+    if owner.is_some() {
+        // Reject before submission a tx that would carry too many
+        // signatures, i.e. the supplied signatures along with those of the
+        // signing keys that will be used
+        let max_signatures = rpc::query_storage_value::<_, u8>(
+            context.client(),
+            &parameter_storage::get_max_signatures_per_transaction_key(),
+        )
+        .await?;
+        let signers = args
+            .signatures
+            .iter()
+            .filter_map(|bytes| SignatureIndex::deserialize(bytes).ok())
+            .map(|sigidx| sigidx.pubkey)
+            .chain(public_keys.iter().cloned())
+            .collect::<HashSet<_>>();
+        if let Err(err) =
+            enforce_max_signatures(signers.len(), Some(max_signatures))
+        {
+            other_err(err.to_string())?;
+        }
+    }
+
     let fee_payer = if args.disposable_signing_key {
         context
             .wallet_mut()
@@ -375,6 +603,7 @@ pub async fn aux_signing_data(
         threshold,
         account_public_keys_map,
         fee_payer,
+        requires_pk_reveal,
     })
 }
 
@@ -420,6 +649,7 @@ pub async fn init_validator_signing_data(
         threshold: 0,
         account_public_keys_map,
         fee_payer,
+        requires_pk_reveal: false,
     })
 }
 
@@ -434,6 +664,16 @@ pub struct TxSourcePostBalance {
     pub token: Address,
 }
 
+/// Get the spending key to unshield the fees from, if any. Setting
+/// `no_fee_unshield` disables fee unshielding regardless of `fee_unshield`.
+fn fee_unshield_source(args: &args::Tx<SdkTypes>) -> Option<TransferSource> {
+    if args.no_fee_unshield {
+        None
+    } else {
+        args.fee_unshield.clone()
+    }
+}
+
 /// Create a wrapper tx from a normal tx. Get the hash of the
 /// wrapper and its payload which is needed for monitoring its
 /// progress on chain.
@@ -525,7 +765,7 @@ pub async fn wrap_tx<N: Namada>(
 
     let unshield = match total_fee.checked_sub(updated_balance) {
         Some(diff) if !diff.is_zero() => {
-            if let Some(spending_key) = args.fee_unshield.clone() {
+            if let Some(spending_key) = fee_unshield_source(args) {
                 // Unshield funds for fee payment
                 let target = namada_core::types::masp::TransferTarget::Address(
                     fee_payer_address.clone(),
@@ -641,7 +881,7 @@ pub async fn wrap_tx<N: Namada>(
             }
         }
         _ => {
-            if args.fee_unshield.is_some() {
+            if fee_unshield_source(args).is_some() {
                 display_line!(
                     context.io(),
                     "Enough transparent balance to pay fees: the fee \
@@ -691,6 +931,28 @@ pub struct LedgerVector {
     pub valid: bool,
 }
 
+impl LedgerVector {
+    /// The number of fields displayed on the device, a field being possibly
+    /// split over several indexed lines
+    pub fn field_count(&self) -> usize {
+        count_indexed_fields(&self.output)
+    }
+
+    /// The number of fields displayed on the device in expert mode
+    pub fn expert_field_count(&self) -> usize {
+        count_indexed_fields(&self.output_expert)
+    }
+}
+
+/// Count the distinct field indices of lines formatted by [`format_outputs`]
+fn count_indexed_fields(output: &[String]) -> usize {
+    output
+        .iter()
+        .filter_map(|line| line.split_once(" | ").map(|(idx, _)| idx))
+        .dedup()
+        .count()
+}
+
 /// Adds a Ledger output line describing a given transaction amount and address
 fn make_ledger_amount_addr(
     tokens: &HashMap<Address, String>,
@@ -758,10 +1020,28 @@ async fn make_ledger_amount_asset(
 
 /// Split the lines in the vector that are longer than the Ledger device's
 /// character width
-fn format_outputs(output: &mut Vec<String>) {
+fn format_outputs(output: &mut Vec<String>) -> Result<(), Error> {
     const MAX_KEY_LEN: usize = 39;
     const MAX_VALUE_LEN: usize = 39;
 
+    format_outputs_with_width(output, MAX_KEY_LEN, MAX_VALUE_LEN)
+}
+
+/// Split the lines in the vector whose keys or values are longer than the
+/// given character widths, for devices with a non-default line width. Fails
+/// if the key width is zero or the value width is less than two.
+pub fn format_outputs_with_width(
+    output: &mut Vec<String>,
+    key_len: usize,
+    value_len: usize,
+) -> Result<(), Error> {
+    if key_len == 0 || value_len <= 1 {
+        return Err(Error::Other(format!(
+            "Invalid Ledger line widths: the key width must be at least 1 and \
+             the value width at least 2, got {} and {}",
+            key_len, value_len
+        )));
+    }
     let mut i = 0;
     let mut pos = 0;
     // Break down each line that is too long one-by-one
@@ -770,26 +1050,23 @@ fn format_outputs(output: &mut Vec<String>) {
         let (key, mut value) =
             curr_line.split_once(':').unwrap_or(("", &curr_line));
         // Truncate the key length to the declared maximum
-        let key = key.trim().chars().take(MAX_KEY_LEN - 1).collect::<String>();
+        let key = key.trim().chars().take(key_len - 1).collect::<String>();
         // Trim value because we will insert spaces later
         value = value.trim();
         if value.is_empty() {
             value = "(none)"
         }
-        if value.chars().count() < MAX_VALUE_LEN {
+        if value.chars().count() < value_len {
             // No need to split the line in this case
             output[pos] = format!("{} | {} : {}", i, key, value);
             pos += 1;
         } else {
             // Line is too long so split it up. Repeat the key on each line
             output.remove(pos);
-            let part_count = (value.chars().count() + MAX_VALUE_LEN - 2)
-                / (MAX_VALUE_LEN - 1);
-            for (idx, part) in value
-                .chars()
-                .chunks(MAX_VALUE_LEN - 1)
-                .into_iter()
-                .enumerate()
+            let part_count =
+                (value.chars().count() + value_len - 2) / (value_len - 1);
+            for (idx, part) in
+                value.chars().chunks(value_len - 1).into_iter().enumerate()
             {
                 let line = format!(
                     "{} | {} [{}/{}] : {}",
@@ -805,6 +1082,7 @@ fn format_outputs(output: &mut Vec<String>) {
         }
         i += 1;
     }
+    Ok(())
 }
 
 /// Adds a Ledger output for the sender and destination for transparent and MASP
@@ -939,10 +1217,12 @@ impl<'a> Display for LedgerProposalType<'a> {
 }
 
 /// Converts the given transaction to the form that is displayed on the Ledger
-/// device
+/// device. If the fee payer is a disposable key, it is flagged as such in the
+/// expert output.
 pub async fn to_ledger_vector(
     wallet: &Wallet<impl WalletIo>,
     tx: &Tx,
+    disposable_fee_payer: bool,
 ) -> Result<LedgerVector, Error> {
     // To facilitate lookups of human-readable token names
     let tokens: HashMap<Address, String> = wallet
@@ -1204,11 +1484,21 @@ pub async fn to_ledger_vector(
                 .iter()
                 .map(|k| format!("Public key : {}", k)),
         );
-        if update_account.threshold.is_some() {
-            tv.output.extend(vec![format!(
-                "Threshold : {}",
-                update_account.threshold.unwrap()
-            )])
+        // When the keys are replaced, show how many of the new keys the
+        // threshold requires
+        let threshold = update_account.threshold.map(|threshold| {
+            if update_account.public_keys.is_empty() {
+                format!("Threshold : {}", threshold)
+            } else {
+                format!(
+                    "Threshold : {} of {} keys",
+                    threshold,
+                    update_account.public_keys.len()
+                )
+            }
+        });
+        if let Some(threshold) = &threshold {
+            tv.output.push(threshold.clone());
         }
 
         let vp_code_data = match &update_account.vp_code_hash {
@@ -1239,9 +1529,8 @@ pub async fn to_ledger_vector(
                 .iter()
                 .map(|k| format!("Public key : {}", k)),
         );
-        if let Some(threshold) = update_account.threshold {
-            tv.output_expert
-                .extend(vec![format!("Threshold : {}", threshold,)])
+        if let Some(threshold) = threshold {
+            tv.output_expert.push(threshold);
         }
         if let Some((_, extra_code_hash)) = vp_code_data {
             tv.output_expert.extend(vec![format!(
@@ -1316,10 +1605,16 @@ pub async fn to_ledger_vector(
 
         match MsgTransfer::try_from(any_msg.clone()) {
             Ok(transfer) => {
+                // Show the token that the denom resolves to on this chain by
+                // its alias, if the wallet knows it
+                let denom = &transfer.packet_data.token.denom;
                 let transfer_token = format!(
                     "{} {}",
                     transfer.packet_data.token.amount,
-                    transfer.packet_data.token.denom
+                    tokens
+                        .get(&ibc_denom_token(denom))
+                        .cloned()
+                        .unwrap_or_else(|| denom.to_string())
                 );
                 tv.output.extend(vec![
                     format!("Source port : {}", transfer.port_id_on_a),
@@ -1736,6 +2031,12 @@ pub async fn to_ledger_vector(
         tv.output_expert.extend(vec![
             format!("Timestamp : {}", tx.header.timestamp.0),
             format!("Pubkey : {}", wrapper.pk),
+        ]);
+        if disposable_fee_payer {
+            tv.output_expert
+                .push("Fee payer : Disposable key".to_string());
+        }
+        tv.output_expert.extend(vec![
             format!("Epoch : {}", wrapper.epoch),
             format!("Gas limit : {}", u64::from(wrapper.gas_limit)),
         ]);
@@ -1754,7 +2055,645 @@ pub async fn to_ledger_vector(
     }
 
     // Finally, index each line and break those that are too long
-    format_outputs(&mut tv.output);
-    format_outputs(&mut tv.output_expert);
+    format_outputs(&mut tv.output)?;
+    format_outputs(&mut tv.output_expert)?;
     Ok(tv)
 }
+
+#[cfg(test)]
+mod test_signing {
+    use borsh::BorshSerialize;
+    use masp_proofs::prover::LocalTxProver;
+    use namada_core::ledger::storage_api::{self, StorageWrite};
+    use namada_core::types::address::nam;
+    use namada_core::types::address::testing::established_address_1;
+    use namada_core::types::key::testing::common_sk_from_simple_seed;
+    use namada_core::types::storage;
+    use namada_core::types::transaction::{GasLimit, TxType, WrapperTx};
+
+    use super::*;
+    use crate::ibc::apps::transfer::types::packet::PacketData;
+    use crate::ibc::apps::transfer::types::PrefixedCoin;
+    use crate::ibc::core::channel::types::timeout::TimeoutHeight;
+    use crate::ibc::core::host::types::identifiers::{ChannelId, PortId};
+    use crate::ibc::primitives::{Msg, Timestamp as IbcTimestamp};
+    use crate::io::NullIo;
+    use crate::masp::ShieldedUtils;
+    use crate::queries::testing::TestClient;
+    use crate::queries::{Rpc, RPC};
+    use crate::wallet::store::Store;
+    use crate::wallet::{LoadStoreError, WalletStorage};
+    use crate::{MaybeSync, NamadaImpl};
+
+    /// Wallet utilities that keep the wallet in memory only
+    #[derive(Clone)]
+    struct TestWalletUtils;
+
+    impl WalletIo for TestWalletUtils {
+        type Rng = OsRng;
+    }
+
+    impl WalletStorage for TestWalletUtils {
+        fn save<U>(&self, _wallet: &Wallet<U>) -> Result<(), LoadStoreError> {
+            Ok(())
+        }
+
+        fn load<U>(
+            &self,
+            _wallet: &mut Wallet<U>,
+        ) -> Result<(), LoadStoreError> {
+            Ok(())
+        }
+    }
+
+    /// Shielded utilities that keep the shielded context in memory only
+    #[derive(Clone, Default, BorshSerialize, BorshDeserialize)]
+    struct TestShieldedUtils;
+
+    #[cfg_attr(feature = "async-send", async_trait::async_trait)]
+    #[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+    impl ShieldedUtils for TestShieldedUtils {
+        fn local_tx_prover(&self) -> LocalTxProver {
+            unimplemented!("The signing tests don't generate any proof")
+        }
+
+        async fn load<U: ShieldedUtils + MaybeSend>(
+            &self,
+            _ctx: &mut ShieldedContext<U>,
+        ) -> std::io::Result<()> {
+            Ok(())
+        }
+
+        async fn save<U: ShieldedUtils + MaybeSync>(
+            &self,
+            _ctx: &ShieldedContext<U>,
+        ) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    type TestContext =
+        NamadaImpl<TestClient<Rpc>, TestWalletUtils, TestShieldedUtils, NullIo>;
+
+    /// Make a context whose client reads from the given storage
+    fn test_context(client: TestClient<Rpc>) -> TestContext {
+        NamadaImpl::native_new(
+            client,
+            Wallet::new(TestWalletUtils, Store::default()),
+            ShieldedContext::default(),
+            NullIo,
+            nam(),
+        )
+    }
+
+    fn tx_args() -> args::Tx<SdkTypes> {
+        args::Tx {
+            dry_run: false,
+            dry_run_wrapper: false,
+            dump_tx: false,
+            output_folder: None,
+            force: false,
+            broadcast_only: false,
+            ledger_address: (),
+            initialized_account_alias: None,
+            wallet_alias_force: false,
+            fee_amount: None,
+            wrapper_fee_payer: None,
+            fee_token: nam(),
+            fee_unshield: Some(
+                TransferSource::Address(established_address_1()),
+            ),
+            no_fee_unshield: false,
+            gas_limit: Default::default(),
+            expiration: None,
+            disposable_signing_key: false,
+            chain_id: None,
+            signing_keys: vec![],
+            signatures: vec![],
+            tx_reveal_code_path: Default::default(),
+            password: None,
+            use_device: false,
+        }
+    }
+
+    /// Test that `no_fee_unshield` skips fee unshielding even when a spending
+    /// key is given, so that an insufficient balance goes straight to the
+    /// balance error without querying the MASP
+    #[test]
+    fn test_no_fee_unshield() {
+        let args = tx_args();
+        assert!(fee_unshield_source(&args).is_some());
+
+        let args = args::Tx {
+            no_fee_unshield: true,
+            ..args
+        };
+        assert!(fee_unshield_source(&args).is_none());
+    }
+
+    /// Test that wrapping a tx whose fee payer can't cover the fees with its
+    /// transparent balance fails with a balance error when fee unshielding is
+    /// disabled. The fee unshielding parameters aren't in storage, so any
+    /// attempt to unshield would fail on them instead.
+    #[tokio::test]
+    async fn test_wrap_tx_insufficient_balance_no_fee_unshield() {
+        let fee_payer = common_sk_from_simple_seed(0).ref_to();
+        let mut client = TestClient::new(RPC);
+        client
+            .wl_storage
+            .write(
+                &parameter_storage::get_gas_cost_key(),
+                BTreeMap::from([(nam(), token::Amount::from(1))]),
+            )
+            .unwrap();
+        client
+            .wl_storage
+            .write(
+                &token::balance_key(&nam(), &Address::from(&fee_payer)),
+                token::Amount::from(100),
+            )
+            .unwrap();
+        let context = test_context(client);
+        let args = args::Tx {
+            no_fee_unshield: true,
+            gas_limit: GasLimit::from(20_000),
+            ..tx_args()
+        };
+
+        let mut tx = Tx::from_type(TxType::Raw);
+        let err = wrap_tx(&context, &mut tx, &args, None, Epoch(0), fee_payer)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Tx(TxError::BalanceTooLowForFees(..))));
+        assert!(tx.header().wrapper().is_none());
+    }
+
+    /// Test that a duplicated signing key is only kept once, in the order it
+    /// was first given
+    #[test]
+    fn test_dedup_public_keys() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let keys = vec![pk(1), pk(0), pk(1), pk(2), pk(0)];
+        assert_eq!(dedup_public_keys(keys), vec![pk(1), pk(0), pk(2)]);
+    }
+
+    /// Test that the signing keys are deduplicated by `tx_signers` and that
+    /// a fee payer among them isn't added again to the signing data
+    #[tokio::test]
+    async fn test_tx_signers_dedup_with_fee_payer() {
+        let pk = |seed| common_sk_from_simple_seed(seed).ref_to();
+        let owner = Address::from(&pk(1));
+        let mut client = TestClient::new(RPC);
+        client
+            .wl_storage
+            .write(
+                &parameter_storage::get_max_signatures_per_transaction_key(),
+                15_u8,
+            )
+            .unwrap();
+        let context = test_context(client);
+        let args = args::Tx {
+            signing_keys: vec![pk(1), pk(0), pk(1), pk(0)],
+            wrapper_fee_payer: Some(pk(0)),
+            ..tx_args()
+        };
+
+        let signers = tx_signers(&context, &args, None).await.unwrap();
+        assert_eq!(signers, vec![pk(1), pk(0)]);
+
+        let signing_data = aux_signing_data(&context, &args, Some(owner), None)
+            .await
+            .unwrap();
+        assert_eq!(signing_data.public_keys, vec![pk(1), pk(0)]);
+        assert_eq!(signing_data.fee_payer, pk(0));
+        let map = signing_data.account_public_keys_map.unwrap();
+        assert_eq!(map.idx_to_pk.len(), 2);
+        assert_eq!(map.get_index_from_public_key(&pk(0)), Some(1));
+    }
+
+    /// Test that looking up the key of an internal address fails with a typed
+    /// error
+    #[tokio::test]
+    async fn test_find_pk_internal_address() {
+        let context = test_context(TestClient::new(RPC));
+        let addr = Address::Internal(InternalAddress::Governance);
+
+        let result = find_pk(&context, &addr).await;
+        assert!(matches!(
+            result,
+            Err(Error::Tx(TxError::InternalAddressHasNoKeys(internal)))
+                if internal == addr
+        ));
+    }
+
+    /// Test that the keys of a multisig established account held in the
+    /// wallet are found, including the one at index 2, up to the threshold
+    /// of the account
+    #[tokio::test]
+    async fn test_find_pks_for_account() {
+        let mut client = TestClient::new(RPC);
+        let owner = established_address_1();
+        let secret_keys =
+            (0..4).map(common_sk_from_simple_seed).collect::<Vec<_>>();
+        let public_keys =
+            secret_keys.iter().map(|sk| sk.ref_to()).collect::<Vec<_>>();
+        storage_api::account::init_account_storage(
+            &mut client.wl_storage,
+            &owner,
+            &public_keys,
+            2,
+        )
+        .unwrap();
+        client
+            .wl_storage
+            .write_bytes(&storage::Key::validity_predicate(&owner), [])
+            .unwrap();
+        let context = test_context(client);
+
+        // without any of the keys in the wallet, fall back to the first one
+        let found = find_pks_for_account(&context, &owner).await.unwrap();
+        assert_eq!(found, vec![public_keys[0].clone()]);
+
+        for idx in [2, 3] {
+            context
+                .wallet_mut()
+                .await
+                .insert_keypair(
+                    format!("key-{idx}"),
+                    false,
+                    secret_keys[idx].clone(),
+                    None,
+                    None,
+                    None,
+                )
+                .unwrap();
+        }
+        let found = find_pks_for_account(&context, &owner).await.unwrap();
+        assert_eq!(found, public_keys[2..].to_vec());
+
+        context
+            .wallet_mut()
+            .await
+            .insert_keypair(
+                "key-1".to_string(),
+                false,
+                secret_keys[1].clone(),
+                None,
+                None,
+                None,
+            )
+            .unwrap();
+        let signers =
+            tx_signers(&context, &tx_args(), Some(owner)).await.unwrap();
+        assert_eq!(signers, public_keys[1..3].to_vec());
+    }
+
+    /// Test that signing from an implicit account whose public key hasn't
+    /// been revealed requires a reveal-pk tx
+    #[tokio::test]
+    async fn test_aux_signing_data_unrevealed_pk() {
+        let public_key = common_sk_from_simple_seed(0).ref_to();
+        let owner = Address::from(&public_key);
+        let mut client = TestClient::new(RPC);
+        client
+            .wl_storage
+            .write(
+                &parameter_storage::get_max_signatures_per_transaction_key(),
+                15_u8,
+            )
+            .unwrap();
+        let args = args::Tx {
+            signing_keys: vec![public_key.clone()],
+            ..tx_args()
+        };
+
+        let context = test_context(client);
+        let signing_data =
+            aux_signing_data(&context, &args, Some(owner.clone()), None)
+                .await
+                .unwrap();
+        assert!(signing_data.requires_pk_reveal);
+        assert_eq!(signing_data.threshold, 1);
+
+        let mut client = TestClient::new(RPC);
+        client
+            .wl_storage
+            .write(
+                &parameter_storage::get_max_signatures_per_transaction_key(),
+                15_u8,
+            )
+            .unwrap();
+        storage_api::key::reveal_pk(&mut client.wl_storage, &public_key)
+            .unwrap();
+        let context = test_context(client);
+        let signing_data = aux_signing_data(&context, &args, Some(owner), None)
+            .await
+            .unwrap();
+        assert!(!signing_data.requires_pk_reveal);
+    }
+
+    /// Test that a dry run reports the key missing from the wallet and the
+    /// threshold that can no longer be met
+    #[tokio::test]
+    async fn test_sign_tx_dry_run_missing_key() {
+        let secret_keys =
+            (0..3).map(common_sk_from_simple_seed).collect::<Vec<_>>();
+        let public_keys =
+            secret_keys.iter().map(|sk| sk.ref_to()).collect::<Vec<_>>();
+        let mut wallet = Wallet::new(TestWalletUtils, Store::default());
+        for (i, sk) in secret_keys.into_iter().take(2).enumerate() {
+            wallet
+                .insert_keypair(format!("key-{i}"), false, sk, None, None, None)
+                .unwrap();
+        }
+        let wallet = RwLock::new(wallet);
+        let mut signing_data = SigningTxData {
+            owner: Some(established_address_1()),
+            public_keys: public_keys.clone(),
+            threshold: 3,
+            account_public_keys_map: Some(AccountPublicKeysMap::from_iter(
+                public_keys.clone(),
+            )),
+            fee_payer: public_keys[0].clone(),
+            requires_pk_reveal: false,
+        };
+
+        let readiness = sign_tx_dry_run(&wallet, &tx_args(), &signing_data)
+            .await
+            .unwrap();
+        let available = readiness
+            .keys
+            .iter()
+            .map(|key| key.available)
+            .collect::<Vec<_>>();
+        assert_eq!(available, vec![true, true, false]);
+        assert!(readiness.fee_payer.available);
+        assert_eq!(readiness.available_count(), 2);
+        assert!(!readiness.meets_threshold());
+
+        signing_data.threshold = 2;
+        let readiness = sign_tx_dry_run(&wallet, &tx_args(), &signing_data)
+            .await
+            .unwrap();
+        assert!(readiness.meets_threshold());
+    }
+
+    /// Test that a tampered signature fails the local verification
+    #[test]
+    fn test_verify_signatures_tampered() {
+        let owner = established_address_1();
+        let secret_keys =
+            (0..2).map(common_sk_from_simple_seed).collect::<Vec<_>>();
+        let public_keys_map = AccountPublicKeysMap::from_iter(
+            secret_keys.iter().map(|sk| sk.ref_to()),
+        );
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.sign_raw(secret_keys, public_keys_map.clone(), Some(owner));
+
+        assert!(verify_signatures(&tx, &public_keys_map, 2).is_ok());
+        assert_eq!(
+            verify_signatures(&tx, &public_keys_map, 3),
+            Err(SignatureVerifyError::ThresholdNotMet {
+                verified: 2,
+                threshold: 3,
+            })
+        );
+
+        // Replace the signature of the key at index 0 by one from another key
+        let other_sk = common_sk_from_simple_seed(2);
+        for section in &mut tx.sections {
+            if let Section::Signature(signatures) = section {
+                let forged = common::SigScheme::sign(
+                    &other_sk,
+                    signatures.get_raw_hash(),
+                );
+                signatures.signatures.insert(0, forged);
+            }
+        }
+        assert!(matches!(
+            verify_signatures(&tx, &public_keys_map, 1),
+            Err(SignatureVerifyError::InvalidSignature(_))
+        ));
+    }
+
+    /// Test that a narrower line width splits the values into more parts
+    #[test]
+    fn test_format_outputs_with_width() {
+        let value = "a".repeat(30);
+        let lines = vec![format!("Source : {value}"), "Code : abc".to_string()];
+
+        let mut output = lines.clone();
+        format_outputs(&mut output).expect("Test failed");
+        assert_eq!(
+            output,
+            vec![
+                format!("0 | Source : {value}"),
+                "1 | Code : abc".to_string()
+            ]
+        );
+
+        let mut output = lines.clone();
+        format_outputs_with_width(&mut output, 20, 20).expect("Test failed");
+        assert_eq!(
+            output,
+            vec![
+                format!("0 | Source [1/2] : {}", "a".repeat(19)),
+                format!("0 | Source [2/2] : {}", "a".repeat(11)),
+                "1 | Code : abc".to_string(),
+            ]
+        );
+
+        // Widths that cannot fit any content are rejected and the output is
+        // left untouched
+        for (key_len, value_len) in [(0, 20), (20, 1), (20, 0)] {
+            let mut output = lines.clone();
+            assert!(matches!(
+                format_outputs_with_width(&mut output, key_len, value_len),
+                Err(Error::Other(_))
+            ));
+            assert_eq!(output, lines);
+        }
+    }
+
+    /// Test the number of fields displayed for a bond, with a long validator
+    /// address that is split over several lines
+    #[tokio::test]
+    async fn test_ledger_vector_bond_field_count() {
+        let wallet = Wallet::new(TestWalletUtils, Store::default());
+        let bond = pos::Bond {
+            validator: established_address_1(),
+            amount: token::Amount::native_whole(100),
+            source: Some(established_address_1()),
+        };
+        let mut tx = Tx::from_type(TxType::Raw);
+        tx.add_code(vec![], Some(TX_BOND_WASM.to_string()))
+            .add_data(bond);
+
+        let tv = to_ledger_vector(&wallet, &tx, false).await.unwrap();
+        assert_eq!(tv.name, "Bond_0");
+        // Type, source, validator and amount
+        assert_eq!(tv.field_count(), 4);
+        assert!(tv.output.len() > tv.field_count());
+        // Code hash, source, validator and amount
+        assert_eq!(tv.expert_field_count(), 4);
+    }
+
+    /// Test that the threshold of an update replacing the account keys is
+    /// shown along with the new number of keys
+    #[tokio::test]
+    async fn test_ledger_vector_update_account_threshold() {
+        let wallet = Wallet::new(TestWalletUtils, Store::default());
+        let update_account = |public_keys, threshold| {
+            let update = UpdateAccount {
+                addr: established_address_1(),
+                vp_code_hash: None,
+                public_keys,
+                threshold,
+            };
+            let mut tx = Tx::from_type(TxType::Raw);
+            tx.add_code(vec![], Some(TX_UPDATE_ACCOUNT_WASM.to_string()))
+                .add_data(update);
+            tx
+        };
+        let has_line = |lines: &[String], expected: &str| {
+            lines.iter().any(|line| line.ends_with(expected))
+        };
+
+        // A 2-of-3 account rotated to 2-of-4
+        let keys = (0..4)
+            .map(|seed| common_sk_from_simple_seed(seed).ref_to())
+            .collect();
+        let tx = update_account(keys, Some(2));
+        let tv = to_ledger_vector(&wallet, &tx, false).await.unwrap();
+        assert!(has_line(&tv.output, "Threshold : 2 of 4 keys"));
+        assert!(has_line(&tv.output_expert, "Threshold : 2 of 4 keys"));
+
+        // The keys are unchanged
+        let tx = update_account(vec![], Some(2));
+        let tv = to_ledger_vector(&wallet, &tx, false).await.unwrap();
+        assert!(has_line(&tv.output, "Threshold : 2"));
+        assert!(!tv.output.iter().any(|line| line.contains("2 of")));
+    }
+
+    /// Test that a disposable fee payer is flagged in the expert output only
+    #[tokio::test]
+    async fn test_ledger_vector_disposable_fee_payer() {
+        let wallet = Wallet::new(TestWalletUtils, Store::default());
+        let mut tx = Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+            Fee {
+                amount_per_gas_unit: token::Amount::native_whole(1)
+                    .native_denominated(),
+                token: nam(),
+            },
+            common_sk_from_simple_seed(0).ref_to(),
+            Epoch(0),
+            GasLimit::from(20_000),
+            None,
+        ))));
+        tx.add_code(vec![], None);
+        let disposable_line = "Fee payer : Disposable key";
+
+        let tv = to_ledger_vector(&wallet, &tx, true).await.unwrap();
+        // The public key may be split over several lines
+        let pubkey_pos = tv
+            .output_expert
+            .iter()
+            .rposition(|line| line.contains("| Pubkey "))
+            .unwrap();
+        assert!(tv.output_expert[pubkey_pos + 1].ends_with(disposable_line));
+
+        let tv = to_ledger_vector(&wallet, &tx, false).await.unwrap();
+        assert!(
+            !tv.output_expert
+                .iter()
+                .any(|line| line.ends_with(disposable_line))
+        );
+    }
+
+    /// Test that the token of an IBC transfer is shown by the wallet alias of
+    /// the token its denom resolves to
+    #[tokio::test]
+    async fn test_ledger_vector_ibc_transfer_token() {
+        let mut wallet = Wallet::new(TestWalletUtils, Store::default());
+        wallet.insert_address("nam", nam(), true);
+        let ibc_transfer = |denom: String| {
+            let message = MsgTransfer {
+                port_id_on_a: PortId::transfer(),
+                chan_id_on_a: ChannelId::new(0),
+                packet_data: PacketData {
+                    token: PrefixedCoin {
+                        denom: denom.parse().unwrap(),
+                        amount: "10".parse().unwrap(),
+                    },
+                    sender: "sender".to_string().into(),
+                    receiver: "receiver".to_string().into(),
+                    memo: String::new().into(),
+                },
+                timeout_height_on_b: TimeoutHeight::Never,
+                timeout_timestamp_on_b: IbcTimestamp::none(),
+            };
+            let mut data = vec![];
+            prost::Message::encode(&message.to_any(), &mut data).unwrap();
+            let mut tx = Tx::from_type(TxType::Raw);
+            tx.add_code(vec![], Some(TX_IBC_WASM.to_string()))
+                .add_serialized_data(data);
+            tx
+        };
+
+        let tv =
+            to_ledger_vector(&wallet, &ibc_transfer(nam().to_string()), false)
+                .await
+                .unwrap();
+        assert_eq!(tv.name, "IBC_0");
+        assert!(
+            tv.output
+                .iter()
+                .any(|line| line.ends_with("Token : 10 nam"))
+        );
+
+        // A token unknown to the wallet is shown by its denom
+        let tv = to_ledger_vector(
+            &wallet,
+            &ibc_transfer("uatom".to_string()),
+            false,
+        )
+        .await
+        .unwrap();
+        assert!(
+            tv.output
+                .iter()
+                .any(|line| line.ends_with("Token : 10 uatom"))
+        );
+    }
+
+    /// Test the JSON round-trip of the signing data
+    #[test]
+    fn test_signing_tx_data_json_roundtrip() {
+        let public_keys = (0..3)
+            .map(|seed| common_sk_from_simple_seed(seed).ref_to())
+            .collect::<Vec<_>>();
+        let signing_data = SigningTxData {
+            owner: Some(established_address_1()),
+            public_keys: public_keys.clone(),
+            threshold: 2,
+            account_public_keys_map: Some(AccountPublicKeysMap::from_iter(
+                public_keys.clone(),
+            )),
+            fee_payer: public_keys[1].clone(),
+            requires_pk_reveal: false,
+        };
+
+        let json = signing_data.to_json().unwrap();
+        let decoded = SigningTxData::from_json(&json).unwrap();
+        assert_eq!(decoded.owner, signing_data.owner);
+        assert_eq!(decoded.public_keys, signing_data.public_keys);
+        assert_eq!(decoded.threshold, signing_data.threshold);
+        assert_eq!(decoded.fee_payer, signing_data.fee_payer);
+        let (decoded_map, map) = (
+            decoded.account_public_keys_map.unwrap(),
+            signing_data.account_public_keys_map.unwrap(),
+        );
+        assert_eq!(decoded_map.idx_to_pk, map.idx_to_pk);
+        assert_eq!(decoded_map.pk_to_idx, map.pk_to_idx);
+    }
+}
@@ -32,6 +32,7 @@ use namada_core::ledger::governance::storage::proposal::ProposalType;
 use namada_core::ledger::governance::storage::vote::StorageProposalVote;
 use namada_core::ledger::ibc::storage::channel_key;
 use namada_core::ledger::pgf::cli::steward::Commission;
+use namada_core::types::account::validate_threshold_change;
 use namada_core::types::address::{Address, InternalAddress, MASP};
 use namada_core::types::dec::Dec;
 use namada_core::types::hash::Hash;
@@ -2529,6 +2530,19 @@ pub async fn build_update_account(
     let addr = if let Some(account) =
         rpc::get_account_info(context.client(), addr).await?
     {
+        // Reject updates after which the account couldn't meet its threshold
+        let new_keys = if public_keys.is_empty() {
+            account.public_keys_map.idx_to_pk.len()
+        } else {
+            public_keys.len()
+        };
+        let new_threshold = threshold.unwrap_or(account.threshold);
+        if let Err(err) = validate_threshold_change(new_keys, new_threshold) {
+            edisplay_line!(context.io(), "Invalid account update: {err}");
+            if !tx_args.force {
+                return Err(Error::from(TxError::InvalidAccountUpdate(err)));
+            }
+        }
         account.address
     } else if tx_args.force {
         addr.clone()
@@ -13,7 +13,9 @@ use namada_core::ledger::governance::storage::vote::StorageProposalVote;
 use namada_core::ledger::governance::utils::is_valid_validator_voting_period;
 use namada_core::ledger::storage;
 use namada_core::ledger::storage_api::account;
-use namada_core::ledger::storage_api::governance::is_proposal_accepted;
+use namada_core::ledger::storage_api::governance::{
+    get_parameters, is_proposal_accepted,
+};
 use namada_core::ledger::vp_env::VpEnv;
 use namada_core::proto::Tx;
 use namada_proof_of_stake::is_validator;
@@ -468,8 +470,7 @@ where
         let start_epoch_key =
             gov_storage::get_voting_start_epoch_key(proposal_id);
         let end_epoch_key = gov_storage::get_voting_end_epoch_key(proposal_id);
-        let min_period_parameter_key =
-            gov_storage::get_min_proposal_voting_period_key();
+        let proposal_type_key = gov_storage::get_proposal_type_key(proposal_id);
         let max_period_parameter_key =
             gov_storage::get_max_proposal_period_key();
 
@@ -486,8 +487,10 @@ where
             self.force_read(&start_epoch_key, ReadType::Post)?;
         let end_epoch: Epoch =
             self.force_read(&end_epoch_key, ReadType::Post)?;
-        let min_period: u64 =
-            self.force_read(&min_period_parameter_key, ReadType::Pre)?;
+        let proposal_type: ProposalType =
+            self.force_read(&proposal_type_key, ReadType::Post)?;
+        let params = get_parameters(&self.ctx.pre())?;
+        let min_period = proposal_type.min_voting_period(&params);
         let max_period: u64 =
             self.force_read(&max_period_parameter_key, ReadType::Pre)?;
 
@@ -12,10 +12,9 @@
 //! and that tokens to be transferred are escrowed.
 
 use std::borrow::Cow;
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::marker::PhantomData;
 
-use borsh::BorshDeserialize;
 use eyre::eyre;
 use namada_core::hints;
 use namada_core::ledger::eth_bridge::storage::bridge_pool::{
@@ -23,15 +22,19 @@ use namada_core::ledger::eth_bridge::storage::bridge_pool::{
 };
 use namada_core::ledger::eth_bridge::storage::whitelist;
 use namada_core::ledger::eth_bridge::ADDRESS as BRIDGE_ADDRESS;
+use namada_core::ledger::vp_env::escrow_keys_valid;
+use namada_ethereum_bridge::storage::eth_bridge_queries::is_bridge_active;
 use namada_ethereum_bridge::storage::parameters::read_native_erc20_address;
 use namada_ethereum_bridge::storage::wrapped_erc20s;
 
-use crate::ledger::native_vp::{Ctx, NativeVp, StorageReader};
+use crate::ledger::native_vp::{Ctx, NativeVp, StorageReader, VpEnv};
 use crate::ledger::storage::traits::StorageHasher;
 use crate::ledger::storage::{DBIter, DB};
 use crate::proto::Tx;
 use crate::types::address::{Address, InternalAddress};
-use crate::types::eth_bridge_pool::{PendingTransfer, TransferToEthereumKind};
+use crate::types::eth_bridge_pool::{
+    decode_pending_transfers, PendingTransfer, TransferToEthereumKind,
+};
 use crate::types::ethereum_events::EthAddress;
 use crate::types::storage::Key;
 use crate::types::token::{balance_key, Amount};
@@ -42,6 +45,50 @@ use crate::vm::WasmCacheAccess;
 /// Generic error that may be returned by the validity predicate
 pub struct Error(#[from] eyre::Error);
 
+/// Errors specific to the validation of transfers to the Bridge pool
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum BridgePoolError {
+    /// Adding the gas fee to the transferred amount overflowed
+    #[error(
+        "Addition overflowed adding gas fee {gas} + transfer amount {token}"
+    )]
+    AmountOverflow {
+        /// The gas fee of the transfer
+        gas: Amount,
+        /// The transferred amount
+        token: Amount,
+    },
+    /// Applying a balance delta went out of the range of an [`Amount`]
+    #[error(
+        "Applying a balance delta of {delta} to a base balance of {base} is \
+         out of range"
+    )]
+    BalanceOverflow {
+        /// The balance before applying the delta
+        base: Amount,
+        /// The signed delta, as a string
+        delta: String,
+    },
+}
+
+/// Reasons for the Bridge pool VP to reject a transaction
+#[derive(thiserror::Error, Debug, Clone, PartialEq, Eq)]
+pub enum BridgePoolRejection {
+    /// A key of the Bridge pool other than those of the pending transfers
+    /// was changed
+    #[error(
+        "Attempted to change an incorrect key in the Ethereum bridge pool: \
+         {changed}. Expected keys: {}",
+        expected.iter().map(Key::to_string).collect::<Vec<_>>().join(", ")
+    )]
+    UnexpectedKeyChanged {
+        /// The offending key
+        changed: Key,
+        /// The keys of the pending transfers
+        expected: BTreeSet<Key>,
+    },
+}
+
 /// A positive or negative amount
 #[derive(Copy, Clone)]
 enum SignedAmount {
@@ -59,12 +106,45 @@ struct AmountDelta {
 }
 
 impl AmountDelta {
-    /// Resolve the updated amount by applying the delta value.
+    /// Resolve the updated amount by applying the delta value, checking
+    /// for overflows and underflows.
     #[inline]
-    fn resolve(self) -> Amount {
-        match self.delta {
-            SignedAmount::Positive(delta) => self.base + delta,
-            SignedAmount::Negative(delta) => self.base - delta,
+    fn resolve(self) -> Result<Amount, Error> {
+        let (resolved, delta) = match self.delta {
+            SignedAmount::Positive(delta) => {
+                (self.base.checked_add(delta), format!("+{delta}"))
+            }
+            SignedAmount::Negative(delta) => {
+                (self.base.checked_sub(delta), format!("-{delta}"))
+            }
+        };
+        resolved.ok_or_else(|| {
+            Error(
+                BridgePoolError::BalanceOverflow {
+                    base: self.base,
+                    delta,
+                }
+                .into(),
+            )
+        })
+    }
+}
+
+/// The accounts to which the Bridge pool VP expects tokens to be escrowed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EscrowConfig {
+    /// The account escrowing gas fees, as well as wrapped ERC20 and NUT
+    /// tokens
+    pub bridge_pool: Address,
+    /// The account escrowing NAM to be minted as wrapped NAM on Ethereum
+    pub bridge: Address,
+}
+
+impl Default for EscrowConfig {
+    fn default() -> Self {
+        Self {
+            bridge_pool: BRIDGE_POOL_ADDRESS,
+            bridge: BRIDGE_ADDRESS,
         }
     }
 }
@@ -93,30 +173,42 @@ where
         token: &Address,
         address: &Address,
     ) -> Option<AmountDelta> {
+        // NB: the posterior balance of the given account must be
+        // present, otherwise it was never written or was deleted
         let account_key = balance_key(token, address);
-        let before: Amount = (&self.ctx)
-            .read_pre_value(&account_key)
+        if !self
+            .ctx
+            .has_key_post(&account_key)
             .map_err(|error| {
-                tracing::warn!(?error, %account_key, "reading pre value");
+                tracing::warn!(?error, %account_key, "reading post value");
             })
             .ok()?
-            // NB: the previous balance of the given account might
-            // have been null. this is valid if the account is
-            // being credited, such as when we escrow gas under
-            // the Bridge pool
-            .unwrap_or_default();
-        let after: Amount = (&self.ctx)
-            .read_post_value(&account_key)
-            .unwrap_or_else(|error| {
-                tracing::warn!(?error, %account_key, "reading post value");
-                None
-            })?;
+        {
+            return None;
+        }
+        // NB: the previous balance of the given account might
+        // have been null. this is valid if the account is
+        // being credited, such as when we escrow gas under
+        // the Bridge pool
+        let (before, after) = self
+            .ctx
+            .balance_delta(token, address)
+            .map_err(|error| {
+                tracing::warn!(
+                    ?error,
+                    %token,
+                    %address,
+                    "reading balance delta"
+                );
+            })
+            .ok()?;
         Some(AmountDelta {
             base: before,
-            delta: if before > after {
-                SignedAmount::Negative(before - after)
-            } else {
-                SignedAmount::Positive(after - before)
+            delta: match before.checked_sub(after) {
+                Some(debit) if !debit.is_zero() => {
+                    SignedAmount::Negative(debit)
+                }
+                _ => SignedAmount::Positive(after.checked_sub(before)?),
             },
         })
     }
@@ -203,15 +295,14 @@ where
         }
     }
 
-    /// Check that the gas was correctly escrowed.
-    fn check_gas_escrow(
+    /// Check that the gas fees of a transfer are paid in a valid token.
+    fn is_valid_gas_token(
         &self,
         wnam_address: &EthAddress,
         transfer: &PendingTransfer,
-        gas_check: EscrowDelta<'_, GasCheck>,
-    ) -> Result<bool, Error> {
+    ) -> bool {
         if hints::unlikely(
-            *gas_check.token == wrapped_erc20s::token(wnam_address),
+            transfer.gas_fee.token == wrapped_erc20s::token(wnam_address),
         ) {
             // NB: this should never be possible: protocol tx state updates
             // never result in wNAM ERC20s being minted
@@ -219,16 +310,29 @@ where
                 ?transfer,
                 "Attempted to pay Bridge pool fees with wrapped NAM."
             );
-            return Ok(false);
+            return false;
         }
         if matches!(
-            &*gas_check.token,
+            &transfer.gas_fee.token,
             Address::Internal(InternalAddress::Nut(_))
         ) {
             tracing::debug!(
                 ?transfer,
                 "The gas fees of the transfer cannot be paid in NUTs."
             );
+            return false;
+        }
+        true
+    }
+
+    /// Check that the gas was correctly escrowed.
+    fn check_gas_escrow(
+        &self,
+        wnam_address: &EthAddress,
+        transfer: &PendingTransfer,
+        gas_check: EscrowDelta<'_, GasCheck>,
+    ) -> Result<bool, Error> {
+        if !self.is_valid_gas_token(wnam_address, transfer) {
             return Ok(false);
         }
         if !self.check_escrowed_toks(gas_check)? {
@@ -242,13 +346,13 @@ where
         Ok(true)
     }
 
-    /// Validate a wrapped NAM transfer to Ethereum.
-    fn check_wnam_escrow(
+    /// Check that a wrapped NAM transfer to Ethereum is allowed, returning
+    /// the cap on the amount of NAM that may be escrowed if so.
+    fn allowed_wnam_cap(
         &self,
         &wnam_address: &EthAddress,
         transfer: &PendingTransfer,
-        token_check: EscrowDelta<'_, TokenCheck>,
-    ) -> Result<bool, Error> {
+    ) -> Result<Option<Amount>, Error> {
         if hints::unlikely(matches!(
             &transfer.transfer.kind,
             TransferToEthereumKind::Nut
@@ -261,42 +365,45 @@ where
                 ?transfer,
                 "Attempted to add a wNAM NUT transfer to the Bridge pool"
             );
-            return Ok(false);
+            return Ok(None);
         }
 
-        let wnam_whitelisted = {
-            let key = whitelist::Key {
-                asset: wnam_address,
-                suffix: whitelist::KeyType::Whitelisted,
-            }
-            .into();
-            (&self.ctx).read_pre_value(&key)?.unwrap_or(false)
-        };
-        if !wnam_whitelisted {
+        let wnam_status = whitelist::read_wnam_whitelist_status(
+            &self.ctx.pre(),
+            wnam_address,
+        )
+        .map_err(|e| Error(e.into()))?;
+        if !wnam_status.whitelisted {
             tracing::debug!(
                 ?transfer,
                 "Wrapped NAM transfers are currently disabled"
             );
-            return Ok(false);
+            return Ok(None);
         }
+        Ok(Some(wnam_status.cap))
+    }
+
+    /// Validate a wrapped NAM transfer to Ethereum.
+    fn check_wnam_escrow(
+        &self,
+        wnam_address: &EthAddress,
+        transfer: &PendingTransfer,
+        token_check: EscrowDelta<'_, TokenCheck>,
+    ) -> Result<bool, Error> {
+        let Some(wnam_cap) = self.allowed_wnam_cap(wnam_address, transfer)?
+        else {
+            return Ok(false);
+        };
 
         // if we are going to mint wNam on Ethereum, the appropriate
         // amount of Nam must be escrowed in the Ethereum bridge VP's
         // storage.
         let escrowed_balance =
             match self.check_escrowed_toks_balance(token_check)? {
-                Some(balance) => balance.resolve(),
+                Some(balance) => balance.resolve()?,
                 None => return Ok(false),
             };
 
-        let wnam_cap = {
-            let key = whitelist::Key {
-                asset: wnam_address,
-                suffix: whitelist::KeyType::Cap,
-            }
-            .into();
-            (&self.ctx).read_pre_value(&key)?.unwrap_or_default()
-        };
         if escrowed_balance > wnam_cap {
             tracing::debug!(
                 ?transfer,
@@ -311,9 +418,183 @@ where
         Ok(true)
     }
 
+    /// Check that a transfer was newly added to the Bridge pool.
+    fn check_pending_transfer(
+        &self,
+        transfer: &PendingTransfer,
+    ) -> Result<bool, Error> {
+        let pending_key = get_pending_key(transfer);
+        // check that transfer is not already in the pool
+        match (&self.ctx).read_pre_value::<PendingTransfer>(&pending_key) {
+            Ok(Some(_)) => {
+                tracing::debug!(
+                    ?transfer,
+                    "Rejecting transaction as the transfer is already in the \
+                     Ethereum bridge pool."
+                );
+                return Ok(false);
+            }
+            Err(e) => {
+                return Err(eyre!(
+                    "Could not read the storage key associated with the \
+                     transfer: {:?}",
+                    e
+                )
+                .into());
+            }
+            _ => {}
+        }
+        let pending: PendingTransfer =
+            (&self.ctx).read_post_value(&pending_key)?.ok_or(eyre!(
+                "Rejecting transaction as the transfer wasn't added to the \
+                 pool of pending transfers"
+            ))?;
+        if pending != *transfer {
+            tracing::debug!(
+                "An incorrect transfer was added to the Ethereum bridge pool: \
+                 {:?}.\n Expected: {:?}",
+                transfer,
+                pending
+            );
+            return Ok(false);
+        }
+        Ok(true)
+    }
+
+    /// Validate the transfers added to the Bridge pool by a transaction.
+    ///
+    /// The transfers of a batch may share their payers and assets, hence
+    /// the escrow checks of each transfer expect the total amounts debited
+    /// from and credited to each balance by the whole batch.
+    fn validate_transfers(
+        &self,
+        transfers: &[PendingTransfer],
+        keys_changed: &BTreeSet<Key>,
+    ) -> Result<bool, Error> {
+        let pending_keys: BTreeSet<Key> =
+            transfers.iter().map(get_pending_key).collect();
+        if pending_keys.len() != transfers.len() {
+            tracing::debug!(
+                "Rejecting transaction as it adds the same transfer to the \
+                 Ethereum bridge pool more than once."
+            );
+            return Ok(false);
+        }
+        if let Err(rejection) =
+            assert_only_pending_keys_changed(keys_changed, &pending_keys)
+        {
+            tracing::debug!("Rejecting transaction: {rejection}");
+            return Ok(false);
+        }
+        for transfer in transfers {
+            if !self.check_pending_transfer(transfer)? {
+                return Ok(false);
+            }
+        }
+        // The deltas in the escrowed amounts we must check.
+        let wnam_address = read_native_erc20_address(&self.ctx.pre())?;
+        let escrow_config = EscrowConfig::default();
+        let mut totals = EscrowTotals::default();
+        let mut escrow_checks = Vec::with_capacity(transfers.len());
+        for transfer in transfers {
+            let checks = self.determine_escrow_checks(
+                &escrow_config,
+                &wnam_address,
+                transfer,
+            )?;
+            totals.add(&checks)?;
+            escrow_checks.push(checks);
+        }
+        for (transfer, mut checks) in transfers.iter().zip(escrow_checks) {
+            totals.apply(&mut checks);
+            if !self.check_escrows(
+                &wnam_address,
+                transfer,
+                checks,
+                keys_changed,
+            )? {
+                return Ok(false);
+            }
+        }
+        tracing::info!(
+            "The Ethereum bridge pool VP accepted the transfers {:?}.",
+            transfers
+        );
+        Ok(true)
+    }
+
+    /// Check that the gas fees and assets of a transfer were escrowed.
+    fn check_escrows(
+        &self,
+        wnam_address: &EthAddress,
+        transfer: &PendingTransfer,
+        escrow_checks: EscrowCheck<'_>,
+        keys_changed: &BTreeSet<Key>,
+    ) -> Result<bool, Error> {
+        if !escrow_checks.validate(keys_changed) {
+            tracing::debug!(
+                ?transfer,
+                "Missing storage modifications in the Bridge pool"
+            );
+            return Ok(false);
+        }
+        // check that gas was correctly escrowed.
+        if !self.check_gas_escrow(
+            wnam_address,
+            transfer,
+            escrow_checks.gas_check,
+        )? {
+            return Ok(false);
+        }
+        // check the escrowed assets
+        if &transfer.transfer.asset == wnam_address {
+            self.check_wnam_escrow(
+                wnam_address,
+                transfer,
+                escrow_checks.token_check,
+            )
+        } else {
+            self.check_escrowed_toks(escrow_checks.token_check)
+        }
+        .map(|ok| {
+            if !ok {
+                tracing::debug!(
+                    ?transfer,
+                    "The assets of the transfer were not properly escrowed \
+                     into the Ethereum bridge pool."
+                );
+            }
+            ok
+        })
+    }
+
+    /// Determine the token drawn from the sender of a transfer, and the
+    /// account it is escrowed to.
+    fn escrowed_token_and_account<'trans, 'this: 'trans>(
+        &'this self,
+        escrow_config: &'trans EscrowConfig,
+        wnam_address: &EthAddress,
+        transfer: &'trans PendingTransfer,
+    ) -> (Cow<'trans, Address>, &'trans Address) {
+        if &transfer.transfer.asset == wnam_address {
+            // when minting wrapped NAM on Ethereum, escrow to the Ethereum
+            // bridge address, and draw from NAM token accounts
+            let token = Cow::Borrowed(&self.ctx.storage.native_token);
+            let escrow_account = &escrow_config.bridge;
+            (token, escrow_account)
+        } else {
+            // otherwise, draw from ERC20/NUT wrapped asset token accounts,
+            // and escrow to the Bridge pool address
+            let token = Cow::Owned(transfer.token_address());
+            let escrow_account = &escrow_config.bridge_pool;
+            (token, escrow_account)
+        }
+    }
+
     /// Determine the debit and credit amounts that should be checked.
     fn determine_escrow_checks<'trans, 'this: 'trans>(
         &'this self,
+        escrow_config: &'trans EscrowConfig,
         wnam_address: &EthAddress,
         transfer: &'trans PendingTransfer,
     ) -> Result<EscrowCheck<'trans>, Error> {
@@ -362,20 +643,8 @@ where
                 (transfer.gas_fee.amount, transfer.transfer.amount)
             }
         };
-        let (token_check_addr, token_check_escrow_acc) = if tok_is_native_asset
-        {
-            // when minting wrapped NAM on Ethereum, escrow to the Ethereum
-            // bridge address, and draw from NAM token accounts
-            let token = Cow::Borrowed(&self.ctx.storage.native_token);
-            let escrow_account = &BRIDGE_ADDRESS;
-            (token, escrow_account)
-        } else {
-            // otherwise, draw from ERC20/NUT wrapped asset token accounts,
-            // and escrow to the Bridge pool address
-            let token = Cow::Owned(transfer.token_address());
-            let escrow_account = &BRIDGE_POOL_ADDRESS;
-            (token, escrow_account)
-        };
+        let (token_check_addr, token_check_escrow_acc) = self
+            .escrowed_token_and_account(escrow_config, wnam_address, transfer);
 
         Ok(EscrowCheck {
             gas_check: EscrowDelta {
@@ -384,7 +653,7 @@ where
                 // anyway
                 token: Cow::Borrowed(&transfer.gas_fee.token),
                 payer_account: &transfer.gas_fee.payer,
-                escrow_account: &BRIDGE_POOL_ADDRESS,
+                escrow_account: &escrow_config.bridge_pool,
                 expected_debit: expected_gas_debit,
                 expected_credit: expected_gas_credit,
                 transferred_amount: &transfer.gas_fee.amount,
@@ -426,56 +695,34 @@ impl<KIND> EscrowDelta<'_, KIND> {
     /// keys must have been written to by some wasm tx.
     #[inline]
     fn validate(&self, changed_keys: &BTreeSet<Key>) -> bool {
-        if hints::unlikely(self.transferred_amount_is_nil()) {
-            self.check_escrow_keys_unchanged(changed_keys)
-        } else {
-            self.check_escrow_keys_changed(changed_keys)
-        }
-    }
-
-    /// Check if all required escrow keys in `changed_keys` were modified.
-    #[inline]
-    fn check_escrow_keys_changed(&self, changed_keys: &BTreeSet<Key>) -> bool {
         let EscrowDelta {
             token,
             payer_account,
             escrow_account,
+            transferred_amount,
             ..
         } = self;
-
-        let owner_key = balance_key(token, payer_account);
-        let escrow_key = balance_key(token, escrow_account);
-
-        changed_keys.contains(&owner_key) && changed_keys.contains(&escrow_key)
-    }
-
-    /// Check if no escrow keys in `changed_keys` were modified.
-    #[inline]
-    fn check_escrow_keys_unchanged(
-        &self,
-        changed_keys: &BTreeSet<Key>,
-    ) -> bool {
-        let EscrowDelta {
+        escrow_keys_valid(
             token,
             payer_account,
             escrow_account,
-            ..
-        } = self;
-
-        let owner_key = balance_key(token, payer_account);
-        let escrow_key = balance_key(token, escrow_account);
+            transferred_amount,
+            changed_keys,
+        )
+    }
 
-        !changed_keys.contains(&owner_key)
-            && !changed_keys.contains(&escrow_key)
+    /// The balance debited by this [`EscrowDelta`], keyed by token and
+    /// owner.
+    #[inline]
+    fn debited_balance(&self) -> (Address, Address) {
+        (self.token.clone().into_owned(), self.payer_account.clone())
     }
 
-    /// Check if the amount transferred to escrow is nil.
+    /// The balance credited by this [`EscrowDelta`], keyed by token and
+    /// owner.
     #[inline]
-    fn transferred_amount_is_nil(&self) -> bool {
-        let EscrowDelta {
-            transferred_amount, ..
-        } = self;
-        transferred_amount.is_zero()
+    fn credited_balance(&self) -> (Address, Address) {
+        (self.token.clone().into_owned(), self.escrow_account.clone())
     }
 }
 
@@ -496,26 +743,118 @@ impl EscrowCheck<'_> {
     }
 }
 
+/// The total amounts expected to be debited from and credited to each
+/// balance, keyed by token and owner, by the transfers of a transaction.
+#[derive(Default)]
+struct EscrowTotals {
+    debits: BTreeMap<(Address, Address), Amount>,
+    credits: BTreeMap<(Address, Address), Amount>,
+}
+
+impl EscrowTotals {
+    /// Add the amounts expected by the [`EscrowCheck`] of a transfer.
+    fn add(&mut self, check: &EscrowCheck<'_>) -> Result<(), Error> {
+        // NB: when the gas fees and the tokens of a transfer are debited
+        // from (or credited to) the same balance, both of its checks
+        // expect their summed amount, which must only be counted once
+        let debits = BTreeMap::from([
+            (
+                check.gas_check.debited_balance(),
+                check.gas_check.expected_debit,
+            ),
+            (
+                check.token_check.debited_balance(),
+                check.token_check.expected_debit,
+            ),
+        ]);
+        let credits = BTreeMap::from([
+            (
+                check.gas_check.credited_balance(),
+                check.gas_check.expected_credit,
+            ),
+            (
+                check.token_check.credited_balance(),
+                check.token_check.expected_credit,
+            ),
+        ]);
+        for (balance, amount) in debits {
+            add_expected_amount(&mut self.debits, balance, amount)?;
+        }
+        for (balance, amount) in credits {
+            add_expected_amount(&mut self.credits, balance, amount)?;
+        }
+        Ok(())
+    }
+
+    /// Update the [`EscrowCheck`] of a transfer to expect the total
+    /// amounts debited and credited by all the transfers.
+    fn apply(&self, check: &mut EscrowCheck<'_>) {
+        self.apply_to_delta(&mut check.gas_check);
+        self.apply_to_delta(&mut check.token_check);
+    }
+
+    fn apply_to_delta<KIND>(&self, delta: &mut EscrowDelta<'_, KIND>) {
+        if let Some(&debit) = self.debits.get(&delta.debited_balance()) {
+            delta.expected_debit = debit;
+        }
+        if let Some(&credit) = self.credits.get(&delta.credited_balance()) {
+            delta.expected_credit = credit;
+        }
+    }
+}
+
 /// Perform a gas check.
 enum GasCheck {}
 
 /// Perform a token check.
 enum TokenCheck {}
 
+/// Check that the only keys of the Bridge pool changed by a transaction are
+/// those of the pending transfers it adds, or return the first offending key.
+fn assert_only_pending_keys_changed(
+    changed: &BTreeSet<Key>,
+    pending_keys: &BTreeSet<Key>,
+) -> Result<(), BridgePoolRejection> {
+    match changed
+        .iter()
+        .find(|key| is_bridge_pool_key(key) && !pending_keys.contains(key))
+    {
+        Some(key) => Err(BridgePoolRejection::UnexpectedKeyChanged {
+            changed: key.clone(),
+            expected: pending_keys.clone(),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Add an amount expected to be debited from or credited to the balance
+/// of some account, checking for overflows.
+fn add_expected_amount(
+    expected: &mut BTreeMap<(Address, Address), Amount>,
+    balance: (Address, Address),
+    amount: Amount,
+) -> Result<(), Error> {
+    let total = expected.entry(balance).or_default();
+    *total = total.checked_add(amount).ok_or_else(|| {
+        Error(eyre!(
+            "Addition overflowed summing up the escrowed amounts {} + {}",
+            total.to_string_native(),
+            amount.to_string_native()
+        ))
+    })?;
+    Ok(())
+}
+
 /// Sum gas and token amounts on a pending transfer, checking for overflows.
 #[inline]
 fn sum_gas_and_token_amounts(
     transfer: &PendingTransfer,
 ) -> Result<Amount, Error> {
-    transfer
-        .gas_fee
-        .amount
-        .checked_add(transfer.transfer.amount)
-        .ok_or_else(|| {
-            Error(eyre!(
-                "Addition overflowed adding gas fee + transfer amount."
-            ))
-        })
+    let gas = transfer.gas_fee.amount;
+    let token = transfer.transfer.amount;
+    gas.checked_add(token).ok_or_else(|| {
+        Error(BridgePoolError::AmountOverflow { gas, token }.into())
+    })
 }
 
 impl<'a, D, H, CA> NativeVp for BridgePoolVp<'a, D, H, CA>
@@ -537,103 +876,18 @@ where
             verifiers_len = _verifiers.len(),
             "Ethereum Bridge Pool VP triggered",
         );
-        let Some(tx_data) = tx.data() else {
-            return Err(eyre!("No transaction data found").into());
-        };
-        let transfer: PendingTransfer =
-            BorshDeserialize::try_from_slice(&tx_data[..])
-                .map_err(|e| Error(e.into()))?;
-
-        let pending_key = get_pending_key(&transfer);
-        // check that transfer is not already in the pool
-        match (&self.ctx).read_pre_value::<PendingTransfer>(&pending_key) {
-            Ok(Some(_)) => {
-                tracing::debug!(
-                    "Rejecting transaction as the transfer is already in the \
-                     Ethereum bridge pool."
-                );
-                return Ok(false);
-            }
-            Err(e) => {
-                return Err(eyre!(
-                    "Could not read the storage key associated with the \
-                     transfer: {:?}",
-                    e
-                )
-                .into());
-            }
-            _ => {}
-        }
-        for key in keys_changed.iter().filter(|k| is_bridge_pool_key(k)) {
-            if *key != pending_key {
-                tracing::debug!(
-                    "Rejecting transaction as it is attempting to change an \
-                     incorrect key in the Ethereum bridge pool: {}.\n \
-                     Expected key: {}",
-                    key,
-                    pending_key
-                );
-                return Ok(false);
-            }
-        }
-        let pending: PendingTransfer =
-            (&self.ctx).read_post_value(&pending_key)?.ok_or(eyre!(
-                "Rejecting transaction as the transfer wasn't added to the \
-                 pool of pending transfers"
-            ))?;
-        if pending != transfer {
+        if !is_bridge_active(&self.ctx.pre()).map_err(|e| Error(e.into()))? {
             tracing::debug!(
-                "An incorrect transfer was added to the Ethereum bridge pool: \
-                 {:?}.\n Expected: {:?}",
-                transfer,
-                pending
+                "Rejecting transaction, since the Ethereum bridge is disabled."
             );
             return Ok(false);
         }
-        // The deltas in the escrowed amounts we must check.
-        let wnam_address = read_native_erc20_address(&self.ctx.pre())?;
-        let escrow_checks =
-            self.determine_escrow_checks(&wnam_address, &transfer)?;
-        if !escrow_checks.validate(keys_changed) {
-            tracing::debug!(
-                ?transfer,
-                "Missing storage modifications in the Bridge pool"
-            );
-            return Ok(false);
-        }
-        // check that gas was correctly escrowed.
-        if !self.check_gas_escrow(
-            &wnam_address,
-            &transfer,
-            escrow_checks.gas_check,
-        )? {
-            return Ok(false);
-        }
-        // check the escrowed assets
-        if transfer.transfer.asset == wnam_address {
-            self.check_wnam_escrow(
-                &wnam_address,
-                &transfer,
-                escrow_checks.token_check,
-            )
-        } else {
-            self.check_escrowed_toks(escrow_checks.token_check)
-        }
-        .map(|ok| {
-            if ok {
-                tracing::info!(
-                    "The Ethereum bridge pool VP accepted the transfer {:?}.",
-                    transfer
-                );
-            } else {
-                tracing::debug!(
-                    ?transfer,
-                    "The assets of the transfer were not properly escrowed \
-                     into the Ethereum bridge pool."
-                );
-            }
-            ok
-        })
+        let Some(tx_data) = tx.data() else {
+            return Err(eyre!("No transaction data found").into());
+        };
+        let transfers = decode_pending_transfers(&tx_data[..])
+            .map_err(|e| Error(e.into()))?;
+        self.validate_transfers(&transfers, keys_changed)
     }
 }
 
@@ -643,9 +897,13 @@ mod test_bridge_pool_vp {
 
     use borsh::BorshDeserialize;
     use borsh_ext::BorshSerializeExt;
+    use namada_core::ledger::eth_bridge::storage::active_key;
     use namada_core::ledger::eth_bridge::storage::bridge_pool::get_signed_root_key;
     use namada_core::ledger::gas::TxGasMeter;
     use namada_core::types::address;
+    use namada_ethereum_bridge::storage::eth_bridge_queries::{
+        EthBridgeEnabled, EthBridgeStatus,
+    };
     use namada_ethereum_bridge::storage::parameters::{
         Contracts, EthereumBridgeParams, UpgradeableContract,
     };
@@ -659,7 +917,9 @@ mod test_bridge_pool_vp {
     use crate::ledger::storage_api::StorageWrite;
     use crate::types::address::{nam, wnam, InternalAddress};
     use crate::types::chain::ChainId;
-    use crate::types::eth_bridge_pool::{GasFee, TransferToEthereum};
+    use crate::types::eth_bridge_pool::{
+        BridgePoolTxData, GasFee, TransferToEthereum,
+    };
     use crate::types::hash::Hash;
     use crate::types::storage::TxIndex;
     use crate::types::transaction::TxType;
@@ -860,14 +1120,10 @@ mod test_bridge_pool_vp {
                 balance_key(&nam(), &BRIDGE_ADDRESS)
             } else {
                 balance_key(
-                    &match balance.kind {
-                        TransferToEthereumKind::Erc20 => {
-                            wrapped_erc20s::token(&balance.asset)
-                        }
-                        TransferToEthereumKind::Nut => {
-                            wrapped_erc20s::nut(&balance.asset)
-                        }
-                    },
+                    &wrapped_erc20s::asset_address(
+                        balance.kind,
+                        &balance.asset,
+                    ),
                     &balance.owner,
                 )
             };
@@ -1316,6 +1572,28 @@ mod test_bridge_pool_vp {
         );
     }
 
+    /// Test that the signed Merkle root key is reported as the offending
+    /// key changed in the Bridge pool.
+    #[test]
+    fn test_signed_merkle_root_change_offender() {
+        let pending_keys = BTreeSet::from([get_pending_key(&initial_pool())]);
+        let mut changed = pending_keys.clone();
+        changed.insert(get_signed_root_key());
+
+        assert_eq!(
+            assert_only_pending_keys_changed(&changed, &pending_keys),
+            Err(BridgePoolRejection::UnexpectedKeyChanged {
+                changed: get_signed_root_key(),
+                expected: pending_keys.clone(),
+            })
+        );
+        let changed = pending_keys.clone();
+        assert_eq!(
+            assert_only_pending_keys_changed(&changed, &pending_keys),
+            Ok(())
+        );
+    }
+
     /// Test that adding a transfer to the pool
     /// that is already in the pool fails.
     #[test]
@@ -1385,6 +1663,246 @@ mod test_bridge_pool_vp {
         assert!(!res.expect("Test failed"));
     }
 
+    /// Test that the overflow of the gas fee and the transferred amount is
+    /// reported with both operands.
+    #[test]
+    fn test_sum_gas_and_token_amounts_overflow() {
+        let transfer = PendingTransfer {
+            transfer: TransferToEthereum {
+                kind: TransferToEthereumKind::Erc20,
+                asset: wnam(),
+                sender: bertha_address(),
+                recipient: EthAddress([1; 20]),
+                amount: 1.into(),
+            },
+            gas_fee: GasFee {
+                token: nam(),
+                amount: Amount::max(),
+                payer: bertha_address(),
+            },
+        };
+
+        let Error(err) = sum_gas_and_token_amounts(&transfer)
+            .expect_err("The sum should overflow");
+        assert_eq!(
+            err.downcast_ref::<BridgePoolError>(),
+            Some(&BridgePoolError::AmountOverflow {
+                gas: Amount::max(),
+                token: 1.into(),
+            })
+        );
+    }
+
+    /// Test that an otherwise valid transfer is rejected if the bridge
+    /// is inactive, and accepted if it is active.
+    #[test]
+    fn test_transfer_rejected_if_bridge_inactive() {
+        for (status, expected) in [
+            (EthBridgeStatus::Disabled, false),
+            (EthBridgeStatus::Enabled(EthBridgeEnabled::AtGenesis), true),
+        ] {
+            // setup
+            let mut wl_storage = setup_storage();
+            wl_storage
+                .storage
+                .write(&active_key(), status.serialize_to_vec())
+                .expect("Test failed");
+            let tx = Tx::from_type(TxType::Raw);
+
+            // the transfer to be added to the pool
+            let transfer = PendingTransfer {
+                transfer: TransferToEthereum {
+                    kind: TransferToEthereumKind::Erc20,
+                    asset: ASSET,
+                    sender: bertha_address(),
+                    recipient: EthAddress([1; 20]),
+                    amount: TOKENS.into(),
+                },
+                gas_fee: GasFee {
+                    token: nam(),
+                    amount: GAS_FEE.into(),
+                    payer: bertha_address(),
+                },
+            };
+
+            // add transfer to pool
+            let mut keys_changed = {
+                wl_storage
+                    .write_log
+                    .write(
+                        &get_pending_key(&transfer),
+                        transfer.serialize_to_vec(),
+                    )
+                    .unwrap();
+                BTreeSet::from([get_pending_key(&transfer)])
+            };
+
+            // update Bertha's balances
+            let mut new_keys_changed = update_balances(
+                &mut wl_storage.write_log,
+                Balance {
+                    asset: ASSET,
+                    kind: TransferToEthereumKind::Erc20,
+                    owner: bertha_address(),
+                    gas: BERTHA_WEALTH.into(),
+                    token: BERTHA_TOKENS.into(),
+                },
+                SignedAmount::Negative(GAS_FEE.into()),
+                SignedAmount::Negative(TOKENS.into()),
+            );
+            keys_changed.append(&mut new_keys_changed);
+
+            // update the bridge pool balances
+            let mut new_keys_changed = update_balances(
+                &mut wl_storage.write_log,
+                Balance {
+                    asset: ASSET,
+                    kind: TransferToEthereumKind::Erc20,
+                    owner: BRIDGE_POOL_ADDRESS,
+                    gas: ESCROWED_AMOUNT.into(),
+                    token: ESCROWED_TOKENS.into(),
+                },
+                SignedAmount::Positive(GAS_FEE.into()),
+                SignedAmount::Positive(TOKENS.into()),
+            );
+            keys_changed.append(&mut new_keys_changed);
+            let verifiers = BTreeSet::default();
+
+            // create the data to be given to the vp
+            let vp = BridgePoolVp {
+                ctx: setup_ctx(
+                    &tx,
+                    &wl_storage.storage,
+                    &wl_storage.write_log,
+                    &keys_changed,
+                    &verifiers,
+                ),
+            };
+
+            let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+            tx.add_data(transfer);
+
+            let res = vp.validate_tx(&tx, &keys_changed, &verifiers);
+            assert_eq!(res.expect("Test failed"), expected);
+        }
+    }
+
+    /// Add a batch of two transfers to the pool, escrowing the assets of
+    /// the given number of them, and check the vp's verdict. If
+    /// `malformed_second` is set, the second transfer written to the pool
+    /// differs from the one in the tx data.
+    fn assert_transfer_batch(
+        escrowed_transfers: u64,
+        malformed_second: bool,
+        expected: bool,
+    ) {
+        // setup
+        let mut wl_storage = setup_storage();
+        let tx = Tx::from_type(TxType::Raw);
+
+        // the transfers to be added to the pool
+        let transfers: Vec<_> = (1..=2)
+            .map(|i| PendingTransfer {
+                transfer: TransferToEthereum {
+                    kind: TransferToEthereumKind::Erc20,
+                    asset: ASSET,
+                    sender: bertha_address(),
+                    recipient: EthAddress([i; 20]),
+                    amount: TOKENS.into(),
+                },
+                gas_fee: GasFee {
+                    token: nam(),
+                    amount: GAS_FEE.into(),
+                    payer: bertha_address(),
+                },
+            })
+            .collect();
+
+        // add the transfers to pool
+        let mut keys_changed = BTreeSet::new();
+        for (i, transfer) in transfers.iter().enumerate() {
+            let mut written = transfer.clone();
+            if malformed_second && i == 1 {
+                written.transfer.amount = (2 * TOKENS).into();
+            }
+            wl_storage
+                .write_log
+                .write(&get_pending_key(transfer), written.serialize_to_vec())
+                .unwrap();
+            keys_changed.insert(get_pending_key(transfer));
+        }
+
+        // update Bertha's balances
+        let mut new_keys_changed = update_balances(
+            &mut wl_storage.write_log,
+            Balance {
+                asset: ASSET,
+                kind: TransferToEthereumKind::Erc20,
+                owner: bertha_address(),
+                gas: BERTHA_WEALTH.into(),
+                token: BERTHA_TOKENS.into(),
+            },
+            SignedAmount::Negative((2 * GAS_FEE).into()),
+            SignedAmount::Negative((escrowed_transfers * TOKENS).into()),
+        );
+        keys_changed.append(&mut new_keys_changed);
+
+        // update the bridge pool balances
+        let mut new_keys_changed = update_balances(
+            &mut wl_storage.write_log,
+            Balance {
+                asset: ASSET,
+                kind: TransferToEthereumKind::Erc20,
+                owner: BRIDGE_POOL_ADDRESS,
+                gas: ESCROWED_AMOUNT.into(),
+                token: ESCROWED_TOKENS.into(),
+            },
+            SignedAmount::Positive((2 * GAS_FEE).into()),
+            SignedAmount::Positive((escrowed_transfers * TOKENS).into()),
+        );
+        keys_changed.append(&mut new_keys_changed);
+        let verifiers = BTreeSet::default();
+
+        // create the data to be given to the vp
+        let vp = BridgePoolVp {
+            ctx: setup_ctx(
+                &tx,
+                &wl_storage.storage,
+                &wl_storage.write_log,
+                &keys_changed,
+                &verifiers,
+            ),
+        };
+
+        let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        tx.add_data(BridgePoolTxData::Batch(transfers));
+
+        let res = vp.validate_tx(&tx, &keys_changed, &verifiers);
+        assert_eq!(res.expect("Test failed"), expected);
+    }
+
+    /// Test that a batch of transfers whose assets were all escrowed
+    /// is accepted.
+    #[test]
+    fn test_transfer_batch_happy_flow() {
+        assert_transfer_batch(2, false, true);
+    }
+
+    /// Test that a batch of transfers is rejected if the assets of its
+    /// second transfer were not escrowed.
+    #[test]
+    fn test_transfer_batch_second_transfer_not_escrowed() {
+        assert_transfer_batch(1, false, false);
+    }
+
+    /// Test that a batch of transfers is rejected if its second transfer
+    /// was not correctly added to the pool, even though the assets of
+    /// both transfers were escrowed.
+    #[test]
+    fn test_transfer_batch_second_transfer_malformed() {
+        assert_transfer_batch(2, true, false);
+    }
+
     /// Test that a transfer added to the pool with zero gas fees
     /// is rejected.
     #[test]
@@ -1941,4 +2459,137 @@ mod test_bridge_pool_vp {
 
         assert!(!delta.validate(&some_changed_keys));
     }
+
+    /// Test that a custom escrow config routes a transfer of the native
+    /// asset to the configured bridge address, and its gas fees to the
+    /// configured Bridge pool address.
+    #[test]
+    fn test_custom_escrow_config() {
+        let wl_storage = setup_storage();
+        let tx = Tx::from_type(TxType::Raw);
+        let keys_changed = BTreeSet::default();
+        let verifiers = BTreeSet::default();
+        let vp = BridgePoolVp {
+            ctx: setup_ctx(
+                &tx,
+                &wl_storage.storage,
+                &wl_storage.write_log,
+                &keys_changed,
+                &verifiers,
+            ),
+        };
+
+        let escrow_config = EscrowConfig {
+            bridge_pool: established_address_1(),
+            bridge: address::testing::established_address_2(),
+        };
+        let transfer = PendingTransfer {
+            transfer: TransferToEthereum {
+                kind: TransferToEthereumKind::Erc20,
+                asset: wnam(),
+                sender: bertha_address(),
+                recipient: EthAddress([1; 20]),
+                amount: 100.into(),
+            },
+            gas_fee: GasFee {
+                token: nam(),
+                amount: 10.into(),
+                payer: bertha_address(),
+            },
+        };
+
+        let checks = vp
+            .determine_escrow_checks(&escrow_config, &wnam(), &transfer)
+            .expect("Test failed");
+        assert_eq!(checks.token_check.escrow_account, &escrow_config.bridge);
+        assert_eq!(*checks.token_check.token, nam());
+        assert_eq!(checks.gas_check.escrow_account, &escrow_config.bridge_pool);
+
+        let default_checks = vp
+            .determine_escrow_checks(
+                &EscrowConfig::default(),
+                &wnam(),
+                &transfer,
+            )
+            .expect("Test failed");
+        assert_eq!(default_checks.token_check.escrow_account, &BRIDGE_ADDRESS);
+        assert_eq!(
+            default_checks.gas_check.escrow_account,
+            &BRIDGE_POOL_ADDRESS
+        );
+    }
+
+    /// Test that resolving a balance delta which would underflow or
+    /// overflow the base balance, e.g. due to an inconsistent write, returns
+    /// an error instead of panicking.
+    #[test]
+    fn test_amount_delta_resolve_out_of_range() {
+        let delta = AmountDelta {
+            base: Amount::from(10),
+            delta: SignedAmount::Negative(Amount::from(4)),
+        };
+        assert_eq!(delta.resolve().expect("Test failed"), Amount::from(6));
+
+        let underflow = AmountDelta {
+            base: Amount::from(10),
+            delta: SignedAmount::Negative(Amount::from(11)),
+        };
+        let error = underflow.resolve().expect_err("Test failed");
+        assert_eq!(
+            error.0.downcast_ref::<BridgePoolError>(),
+            Some(&BridgePoolError::BalanceOverflow {
+                base: Amount::from(10),
+                delta: format!("-{}", Amount::from(11)),
+            })
+        );
+
+        let overflow = AmountDelta {
+            base: Amount::max(),
+            delta: SignedAmount::Positive(Amount::from(1)),
+        };
+        assert!(overflow.resolve().is_err());
+    }
+
+    /// Test that the balance delta of an account whose balance is missing
+    /// after the tx is rejected, whereas a missing prior balance is zero.
+    #[test]
+    fn test_account_balance_delta_missing_post_balance() {
+        let mut wl_storage = setup_storage();
+        let tx = Tx::from_type(TxType::Raw);
+        wl_storage
+            .write_log
+            .delete(&balance_key(&nam(), &bertha_address()))
+            .expect("Test failed");
+        wl_storage
+            .write_log
+            .write(
+                &balance_key(&nam(), &established_address_1()),
+                Amount::from(GAS_FEE).serialize_to_vec(),
+            )
+            .expect("Test failed");
+        let keys_changed = BTreeSet::default();
+        let verifiers = BTreeSet::default();
+        let vp = BridgePoolVp {
+            ctx: setup_ctx(
+                &tx,
+                &wl_storage.storage,
+                &wl_storage.write_log,
+                &keys_changed,
+                &verifiers,
+            ),
+        };
+
+        assert!(
+            vp.account_balance_delta(&nam(), &bertha_address())
+                .is_none()
+        );
+        let delta = vp
+            .account_balance_delta(&nam(), &established_address_1())
+            .expect("Test failed");
+        assert_eq!(delta.base, Amount::zero());
+        assert!(matches!(
+            delta.delta,
+            SignedAmount::Positive(amount) if amount == Amount::from(GAS_FEE)
+        ));
+    }
 }
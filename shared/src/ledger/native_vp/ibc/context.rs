@@ -10,6 +10,9 @@ use crate::ledger::ibc::storage::is_ibc_key;
 use crate::ledger::native_vp::CtxPreStorageRead;
 use crate::ledger::storage::write_log::StorageModification;
 use crate::ledger::storage::{self as ledger_storage, StorageHasher};
+use crate::ledger::storage_api::token::{
+    denominated_amount_checked, InsufficientFunds,
+};
 use crate::ledger::storage_api::{self, StorageRead, StorageWrite};
 use crate::types::address::{Address, InternalAddress};
 use crate::types::ibc::{IbcEvent, IbcShieldedTransfer};
@@ -22,6 +25,24 @@ use crate::vm::WasmCacheAccess;
 /// Result of a storage API call.
 pub type Result<T> = std::result::Result<T, storage_api::Error>;
 
+/// A storage modification made by the pseudo execution
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeKind {
+    /// The given value was written
+    Written(Vec<u8>),
+    /// The value was deleted
+    Deleted,
+}
+
+/// Whether a token supply change minted or burned tokens
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MintOrBurn {
+    /// Tokens were minted
+    Mint,
+    /// Tokens were burned
+    Burn,
+}
+
 /// Pseudo execution environment context for ibc native vp
 #[derive(Debug)]
 pub struct PseudoExecutionContext<'view, 'a, DB, H, CA>
@@ -36,6 +57,8 @@ where
     ctx: CtxPreStorageRead<'view, 'a, DB, H, CA>,
     /// IBC event
     pub event: BTreeSet<IbcEvent>,
+    /// Tokens minted or burned by the pseudo execution, in order
+    supply_changes: Vec<(Address, Amount, MintOrBurn)>,
 }
 
 impl<'view, 'a, DB, H, CA> PseudoExecutionContext<'view, 'a, DB, H, CA>
@@ -50,14 +73,53 @@ where
             store: HashMap::new(),
             ctx,
             event: BTreeSet::new(),
+            supply_changes: Vec::new(),
         }
     }
 
+    /// Get the tokens minted or burned by the pseudo execution, in order,
+    /// from which the total supply delta of each token can be reconstructed
+    pub fn token_supply_changes(&self) -> &[(Address, Amount, MintOrBurn)] {
+        &self.supply_changes
+    }
+
     /// Get the set of changed keys
     pub(crate) fn get_changed_keys(&self) -> HashSet<&Key> {
         self.store.keys().filter(|k| is_ibc_key(k)).collect()
     }
 
+    /// Emit an IBC event, returning `false` if an identical event had
+    /// already been emitted, e.g. when the same packet is processed twice
+    pub fn emit_ibc_event_checked(&mut self, event: IbcEvent) -> Result<bool> {
+        Ok(self.event.insert(event))
+    }
+
+    /// Dump all the storage modifications made by the pseudo execution,
+    /// including those of non-IBC keys, ordered by their keys
+    pub fn dump_changes(&self) -> Vec<(Key, ChangeKind)> {
+        let mut changes: Vec<_> = self
+            .store
+            .iter()
+            .map(|(key, modification)| {
+                let change = match modification {
+                    StorageModification::Write { value } => {
+                        ChangeKind::Written(value.clone())
+                    }
+                    StorageModification::Delete => ChangeKind::Deleted,
+                    StorageModification::Temp { .. } => {
+                        unreachable!("Temp shouldn't be inserted")
+                    }
+                    StorageModification::InitAccount { .. } => {
+                        unreachable!("InitAccount shouldn't be inserted")
+                    }
+                };
+                (key.clone(), change)
+            })
+            .collect();
+        changes.sort_by(|(a, _), (b, _)| a.cmp(b));
+        changes
+    }
+
     /// Get the changed value
     pub(crate) fn get_changed_value(
         &self,
@@ -176,7 +238,7 @@ where
     CA: 'static + WasmCacheAccess,
 {
     fn emit_ibc_event(&mut self, event: IbcEvent) -> Result<()> {
-        self.event.insert(event);
+        self.emit_ibc_event_checked(event)?;
         Ok(())
     }
 
@@ -199,9 +261,23 @@ where
         token: &Address,
         amount: DenominatedAmount,
     ) -> Result<()> {
-        let amount = amount.to_amount(token, self)?;
+        let amount = denominated_amount_checked(self, token, amount)?;
         let src_key = token::balance_key(token, src);
         let dest_key = token::balance_key(token, dest);
+        if src == dest {
+            // a transfer to oneself leaves the balance untouched, as long as
+            // the source has sufficient funds
+            let src_bal: Amount = self.ctx.read(&src_key)?.unwrap_or_default();
+            return match src_bal.checked_sub(amount) {
+                Some(_) => Ok(()),
+                None => Err(storage_api::Error::new(InsufficientFunds {
+                    token: token.clone(),
+                    owner: src.clone(),
+                    balance: src_bal,
+                    amount,
+                })),
+            };
+        }
         let src_bal: Option<Amount> = self.ctx.read(&src_key)?;
         let mut src_bal = src_bal.expect("The source has no balance");
         src_bal.spend(&amount);
@@ -246,7 +322,11 @@ where
         self.write(
             &minter_key,
             Address::Internal(InternalAddress::Ibc).serialize_to_vec(),
-        )
+        )?;
+
+        self.supply_changes
+            .push((token.clone(), amount, MintOrBurn::Mint));
+        Ok(())
     }
 
     fn burn_token(
@@ -267,7 +347,11 @@ where
         minted_bal.spend(&amount);
 
         self.write(&target_key, target_bal.serialize_to_vec())?;
-        self.write(&minted_key, minted_bal.serialize_to_vec())
+        self.write(&minted_key, minted_bal.serialize_to_vec())?;
+
+        self.supply_changes
+            .push((token.clone(), amount, MintOrBurn::Burn));
+        Ok(())
     }
 
     fn log_string(&self, message: String) {
@@ -308,6 +308,7 @@ pub fn get_dummy_genesis_validator()
 #[cfg(test)]
 mod tests {
     use core::time::Duration;
+    use std::collections::HashMap;
     use std::convert::TryFrom;
     use std::str::FromStr;
 
@@ -320,9 +321,12 @@ mod tests {
     use ibc_testkit::testapp::ibc::clients::mock::header::MockHeader;
     use namada_core::ledger::gas::TxGasMeter;
     use namada_core::ledger::governance::parameters::GovernanceParameters;
+    use namada_core::ledger::ibc::IbcStorageContext;
     use prost::Message;
     use sha2::Digest;
+    use tempfile::TempDir;
 
+    use super::context::{ChangeKind, MintOrBurn};
     use super::*;
     use crate::core::ledger::ibc::storage::{
         ack_key, calc_hash, channel_counter_key, channel_key,
@@ -332,7 +336,9 @@ mod tests {
         ibc_denom_key, next_sequence_ack_key, next_sequence_recv_key,
         next_sequence_send_key, receipt_key,
     };
+    use crate::core::ledger::storage::mockdb::MockDB;
     use crate::core::ledger::storage::testing::TestWlStorage;
+    use crate::core::ledger::storage::traits::Sha256Hasher;
     use crate::core::types::address::testing::{
         established_address_1, established_address_2,
     };
@@ -401,16 +407,19 @@ mod tests {
         get_epoch_duration_storage_key, get_max_expected_time_per_block_key,
     };
     use crate::ledger::parameters::EpochDuration;
-    use crate::ledger::storage_api::StorageRead;
+    use crate::ledger::storage_api::{StorageRead, StorageWrite};
     use crate::ledger::{ibc, pos};
     use crate::proto::{Code, Data, Section, Signature, Tx};
     use crate::tendermint::time::Time as TmTime;
+    use crate::types::ibc::{
+        IbcEvent, IbcEventType, EVENT_TYPE_DENOM_TRACE, EVENT_TYPE_PACKET,
+    };
     use crate::types::key::testing::keypair_1;
     use crate::types::storage::{BlockHash, BlockHeight, TxIndex};
     use crate::types::time::DurationSecs;
-    use crate::types::token::{balance_key, Amount};
+    use crate::types::token::{balance_key, Amount, Change, DenominatedAmount};
     use crate::types::transaction::TxType;
-    use crate::vm::wasm;
+    use crate::vm::{wasm, WasmCacheRwAccess};
 
     const ADDRESS: Address = Address::Internal(InternalAddress::Ibc);
     const COMMITMENT_PREFIX: &[u8] = b"ibc";
@@ -657,6 +666,43 @@ mod tests {
         sha2::Sha256::digest(&input).to_vec().into()
     }
 
+    /// No changed keys for the test contexts
+    static NO_KEYS_CHANGED: BTreeSet<Key> = BTreeSet::new();
+    /// No verifiers for the test contexts
+    static NO_VERIFIERS: BTreeSet<Address> = BTreeSet::new();
+
+    fn dummy_tx(wl_storage: &TestWlStorage) -> Tx {
+        let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        tx.add_code(vec![], None).sign_wrapper(keypair_1());
+        tx
+    }
+
+    /// Set up the context of the IBC VP with no changed keys nor verifiers.
+    /// The returned directory holds the VP wasm cache and must outlive the
+    /// context.
+    fn setup_ctx<'a>(
+        wl_storage: &'a TestWlStorage,
+        tx: &'a Tx,
+    ) -> (Ctx<'a, MockDB, Sha256Hasher, WasmCacheRwAccess>, TempDir) {
+        let gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+        );
+        let (vp_wasm_cache, vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let ctx = Ctx::new(
+            &ADDRESS,
+            &wl_storage.storage,
+            &wl_storage.write_log,
+            tx,
+            &TxIndex(0),
+            gas_meter,
+            &NO_KEYS_CHANGED,
+            &NO_VERIFIERS,
+            vp_wasm_cache,
+        );
+        (ctx, vp_cache_dir)
+    }
+
     #[test]
     fn test_create_client() {
         let mut wl_storage = init_storage();
@@ -2739,4 +2785,231 @@ mod tests {
                 .expect("validation failed")
         );
     }
+
+    #[test]
+    fn test_get_ibc_events_typed() {
+        let wl_storage = init_storage();
+
+        let tx = dummy_tx(&wl_storage);
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx);
+        let mut exec_ctx = PseudoExecutionContext::new(ctx.pre());
+
+        let packet_event = IbcEvent {
+            event_type: EVENT_TYPE_PACKET.to_string(),
+            attributes: HashMap::from([(
+                "receiver".to_string(),
+                "receiver".to_string(),
+            )]),
+        };
+        let denom_event = IbcEvent {
+            event_type: EVENT_TYPE_DENOM_TRACE.to_string(),
+            attributes: HashMap::from([(
+                "denom".to_string(),
+                "denom".to_string(),
+            )]),
+        };
+        exec_ctx.emit_ibc_event(packet_event.clone()).unwrap();
+        exec_ctx.emit_ibc_event(denom_event).unwrap();
+
+        let typed = exec_ctx
+            .get_ibc_events_typed(IbcEventType::Packet)
+            .expect("getting events failed");
+        let untyped = exec_ctx
+            .get_ibc_events(EVENT_TYPE_PACKET)
+            .expect("getting events failed");
+        assert_eq!(typed, untyped);
+        assert_eq!(typed, vec![packet_event]);
+    }
+
+    #[test]
+    fn test_dump_changes() {
+        let wl_storage = init_storage();
+
+        let tx = dummy_tx(&wl_storage);
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx);
+        let mut exec_ctx = PseudoExecutionContext::new(ctx.pre());
+
+        // an IBC key and a non-IBC one
+        let written_key = client_counter_key();
+        let deleted_key = balance_key(&nam(), &established_address_1());
+        exec_ctx.write_bytes(&written_key, [1, 2, 3]).unwrap();
+        exec_ctx.delete(&deleted_key).unwrap();
+
+        let mut expected = vec![
+            (written_key, ChangeKind::Written(vec![1, 2, 3])),
+            (deleted_key, ChangeKind::Deleted),
+        ];
+        expected.sort_by(|(a, _), (b, _)| a.cmp(b));
+        assert_eq!(exec_ctx.dump_changes(), expected);
+    }
+
+    #[test]
+    fn test_emit_ibc_event_checked() {
+        let wl_storage = init_storage();
+
+        let tx = dummy_tx(&wl_storage);
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx);
+        let mut exec_ctx = PseudoExecutionContext::new(ctx.pre());
+
+        let packet_event = IbcEvent {
+            event_type: EVENT_TYPE_PACKET.to_string(),
+            attributes: HashMap::from([(
+                "receiver".to_string(),
+                "receiver".to_string(),
+            )]),
+        };
+        assert!(
+            exec_ctx
+                .emit_ibc_event_checked(packet_event.clone())
+                .unwrap()
+        );
+        assert!(
+            !exec_ctx
+                .emit_ibc_event_checked(packet_event.clone())
+                .unwrap()
+        );
+        assert_eq!(
+            exec_ctx.get_ibc_events(EVENT_TYPE_PACKET).unwrap(),
+            vec![packet_event]
+        );
+    }
+
+    #[test]
+    fn test_transfer_token_denom_mismatch() {
+        let mut wl_storage = init_storage();
+        let sender = established_address_1();
+        let receiver = established_address_2();
+        wl_storage
+            .write_log
+            .write(
+                &balance_key(&nam(), &sender),
+                Amount::native_whole(100).serialize_to_vec(),
+            )
+            .expect("write failed");
+        wl_storage.write_log.commit_tx();
+
+        let tx = dummy_tx(&wl_storage);
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx);
+        let mut exec_ctx = PseudoExecutionContext::new(ctx.pre());
+
+        // NAM has 6 decimal places
+        let mismatched = DenominatedAmount::new(Amount::from(10), 0.into());
+        let err = exec_ctx
+            .transfer_token(&sender, &receiver, &nam(), mismatched)
+            .expect_err("the denomination should mismatch");
+        assert!(err.to_string().contains("doesn't match"));
+        assert!(exec_ctx.dump_changes().is_empty());
+
+        let amount = Amount::native_whole(10).native_denominated();
+        exec_ctx
+            .transfer_token(&sender, &receiver, &nam(), amount)
+            .expect("the transfer should succeed");
+        assert_eq!(
+            exec_ctx
+                .read::<Amount>(&balance_key(&nam(), &receiver))
+                .unwrap(),
+            Some(Amount::native_whole(10))
+        );
+    }
+
+    #[test]
+    fn test_token_supply_changes() {
+        let mut wl_storage = init_storage();
+        let sender = established_address_1();
+        let receiver = established_address_2();
+        wl_storage
+            .write_log
+            .write(
+                &balance_key(&nam(), &sender),
+                Amount::native_whole(100).serialize_to_vec(),
+            )
+            .expect("write failed");
+        wl_storage.write_log.commit_tx();
+
+        let tx = dummy_tx(&wl_storage);
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx);
+        let mut exec_ctx = PseudoExecutionContext::new(ctx.pre());
+        assert!(exec_ctx.token_supply_changes().is_empty());
+
+        let minted = Amount::native_whole(10);
+        exec_ctx
+            .mint_token(&receiver, &nam(), minted.native_denominated())
+            .expect("minting should succeed");
+        let burned = Amount::native_whole(4);
+        exec_ctx
+            .burn_token(&sender, &nam(), burned.native_denominated())
+            .expect("burning should succeed");
+
+        let changes = exec_ctx.token_supply_changes();
+        assert_eq!(
+            changes,
+            [
+                (nam(), minted, MintOrBurn::Mint),
+                (nam(), burned, MintOrBurn::Burn),
+            ]
+        );
+        let net =
+            changes
+                .iter()
+                .fold(Change::default(), |net, (_, amount, kind)| match kind {
+                    MintOrBurn::Mint => net + amount.change(),
+                    MintOrBurn::Burn => net - amount.change(),
+                });
+        assert_eq!(net, Amount::native_whole(6).change());
+    }
+
+    #[test]
+    fn test_transfer_token_to_self() {
+        let mut wl_storage = init_storage();
+        let owner = established_address_1();
+        let balance = Amount::native_whole(100);
+        wl_storage
+            .write_log
+            .write(&balance_key(&nam(), &owner), balance.serialize_to_vec())
+            .expect("write failed");
+        wl_storage.write_log.commit_tx();
+
+        let tx = dummy_tx(&wl_storage);
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx);
+        let mut exec_ctx = PseudoExecutionContext::new(ctx.pre());
+
+        exec_ctx
+            .transfer_token(
+                &owner,
+                &owner,
+                &nam(),
+                Amount::native_whole(60).native_denominated(),
+            )
+            .expect("the transfer should succeed");
+        assert_eq!(
+            exec_ctx
+                .read::<Amount>(&balance_key(&nam(), &owner))
+                .unwrap(),
+            Some(balance)
+        );
+        assert!(exec_ctx.dump_changes().is_empty());
+
+        // the balance of the source is still validated
+        exec_ctx
+            .transfer_token(
+                &owner,
+                &owner,
+                &nam(),
+                Amount::native_whole(101).native_denominated(),
+            )
+            .expect_err("the source has insufficient funds");
+        assert!(exec_ctx.dump_changes().is_empty());
+
+        // an account without any balance can transfer a nil amount to itself
+        let empty = established_address_2();
+        exec_ctx
+            .transfer_token(
+                &empty,
+                &empty,
+                &nam(),
+                Amount::zero().native_denominated(),
+            )
+            .expect("the transfer should succeed");
+        assert!(exec_ctx.dump_changes().is_empty());
+    }
 }
@@ -105,53 +105,58 @@ where
     H: 'static + storage::StorageHasher,
     CA: 'static + WasmCacheAccess,
 {
-    // Check that the transaction correctly revealed the nullifiers
+    // Check that the transactions with shielded inputs correctly revealed the
+    // nullifiers, and that no other nullifier was revealed
     fn valid_nullifiers_reveal(
         &self,
         keys_changed: &BTreeSet<Key>,
-        transaction: &Transaction,
+        transactions: &[&Transaction],
     ) -> Result<bool> {
         let mut revealed_nullifiers = HashSet::new();
-        let shielded_spends = match transaction.sapling_bundle() {
-            Some(bundle) if !bundle.shielded_spends.is_empty() => {
-                &bundle.shielded_spends
-            }
-            _ => {
-                tracing::debug!(
-                    "Missing expected spend descriptions in shielded \
-                     transaction"
-                );
-                return Ok(false);
-            }
-        };
+        for transaction in transactions {
+            let shielded_spends = match transaction.sapling_bundle() {
+                Some(bundle) if !bundle.shielded_spends.is_empty() => {
+                    &bundle.shielded_spends
+                }
+                _ => {
+                    tracing::debug!(
+                        "Missing expected spend descriptions in shielded \
+                         transaction"
+                    );
+                    return Ok(false);
+                }
+            };
+
+            for description in shielded_spends {
+                let nullifier_key = Key::from(MASP.to_db_key())
+                    .push(&MASP_NULLIFIERS_KEY.to_owned())
+                    .expect("Cannot obtain a storage key")
+                    .push(&namada_core::types::hash::Hash(
+                        description.nullifier.0,
+                    ))
+                    .expect("Cannot obtain a storage key");
+                if self.ctx.has_key_pre(&nullifier_key)?
+                    || revealed_nullifiers.contains(&nullifier_key)
+                {
+                    tracing::debug!(
+                        "MASP double spending attempt, the nullifier {:#?} \
+                         has already been revealed previously",
+                        description.nullifier.0
+                    );
+                    return Ok(false);
+                }
 
-        for description in shielded_spends {
-            let nullifier_key = Key::from(MASP.to_db_key())
-                .push(&MASP_NULLIFIERS_KEY.to_owned())
-                .expect("Cannot obtain a storage key")
-                .push(&namada_core::types::hash::Hash(description.nullifier.0))
-                .expect("Cannot obtain a storage key");
-            if self.ctx.has_key_pre(&nullifier_key)?
-                || revealed_nullifiers.contains(&nullifier_key)
-            {
-                tracing::debug!(
-                    "MASP double spending attempt, the nullifier {:#?} has \
-                     already been revealed previously",
-                    description.nullifier.0
-                );
-                return Ok(false);
-            }
+                // Check that the nullifier is indeed committed (no temp write
+                // and no delete) and carries no associated data (the latter
+                // not strictly necessary for validation, but we don't expect
+                // any value for this key anyway)
+                match self.ctx.read_bytes_post(&nullifier_key)? {
+                    Some(value) if value.is_empty() => (),
+                    _ => return Ok(false),
+                }
 
-            // Check that the nullifier is indeed committed (no temp write
-            // and no delete) and carries no associated data (the latter not
-            // strictly necessary for validation, but we don't expect any
-            // value for this key anyway)
-            match self.ctx.read_bytes_post(&nullifier_key)? {
-                Some(value) if value.is_empty() => (),
-                _ => return Ok(false),
+                revealed_nullifiers.insert(nullifier_key);
             }
-
-            revealed_nullifiers.insert(nullifier_key);
         }
 
         for nullifier_key in
@@ -169,11 +174,11 @@ where
         Ok(true)
     }
 
-    // Check that a transaction carrying output descriptions correctly updates
-    // the tree and anchor in storage
+    // Check that the transactions carrying output descriptions correctly
+    // update the tree and anchor in storage, in the given order
     fn valid_note_commitment_update(
         &self,
-        transaction: &Transaction,
+        transactions: &[&Transaction],
     ) -> Result<bool> {
         // Check that the merkle tree in storage has been correctly updated with
         // the output descriptions cmu
@@ -189,12 +194,13 @@ where
                 native_vp::Error::SimpleMessage("Cannot read storage"),
             ))?;
 
-        // Based on the output descriptions of the transaction, update the
+        // Based on the output descriptions of the transactions, update the
         // previous tree in storage
-        for description in transaction
-            .sapling_bundle()
-            .map_or(&vec![], |bundle| &bundle.shielded_outputs)
-        {
+        for description in transactions.iter().flat_map(|transaction| {
+            transaction
+                .sapling_bundle()
+                .map_or(&[][..], |bundle| &bundle.shielded_outputs[..])
+        }) {
             previous_tree
                 .append(Node::from_scalar(description.cmu))
                 .map_err(|()| {
@@ -290,34 +296,33 @@ where
     }
 
     /// Check the correctness of the general storage changes that pertain to all
-    /// types of masp transfers
+    /// types of masp transfers. Each of the shielded actions must be recorded
+    /// under its own tx key, following the order of the actions.
     fn valid_state(
         &self,
         keys_changed: &BTreeSet<Key>,
-        transfer: &Transfer,
-        transaction: &Transaction,
+        actions: &[(Transfer, Transaction)],
     ) -> Result<bool> {
         // Check that the transaction didn't write unallowed masp keys, nor
-        // multiple variations of the same key prefixes
-        let mut found_tx_key = false;
-        let mut found_pin_key = false;
+        // more variations of the same key prefixes than the shielded actions
+        let mut tx_keys = 0_usize;
+        let mut pin_keys = 0_usize;
         for key in keys_changed.iter().filter(|key| is_masp_key(key)) {
             if !is_masp_allowed_key(key) {
                 return Ok(false);
             } else if is_masp_tx_prefix_key(key) {
-                if found_tx_key {
-                    return Ok(false);
-                } else {
-                    found_tx_key = true;
-                }
+                tx_keys += 1;
             } else if is_masp_tx_pin_key(key) {
-                if found_pin_key {
-                    return Ok(false);
-                } else {
-                    found_pin_key = true;
-                }
+                pin_keys += 1;
             }
         }
+        let pinned_actions = actions
+            .iter()
+            .filter(|(transfer, _)| transfer.key.is_some())
+            .count();
+        if tx_keys > actions.len() || pin_keys > pinned_actions {
+            return Ok(false);
+        }
 
         // Validate head tx
         let head_tx_key = Key::from(MASP.to_db_key())
@@ -326,64 +331,62 @@ where
         let pre_head: u64 = self.ctx.read_pre(&head_tx_key)?.unwrap_or(0);
         let post_head: u64 = self.ctx.read_post(&head_tx_key)?.unwrap_or(0);
 
-        if post_head != pre_head + 1 {
+        if post_head != pre_head + actions.len() as u64 {
             return Ok(false);
         }
 
-        // Validate tx key
-        let current_tx_key = Key::from(MASP.to_db_key())
-            .push(&(TX_KEY_PREFIX.to_owned() + &pre_head.to_string()))
-            .expect("Cannot obtain a storage key");
-        match self
-            .ctx
-            .read_post::<(Epoch, BlockHeight, TxIndex, Transfer, Transaction)>(
-                &current_tx_key,
-            )? {
-            Some((
-                epoch,
-                height,
-                tx_index,
-                storage_transfer,
-                storage_transaction,
-            )) if (epoch == self.ctx.get_block_epoch()?
-                && height == self.ctx.get_block_height()?
-                && tx_index == self.ctx.get_tx_index()?
-                && &storage_transfer == transfer
-                && &storage_transaction == transaction) => {}
-            _ => return Ok(false),
-        }
-
-        // Validate pin key
-        if let Some(key) = &transfer.key {
-            let pin_key = Key::from(MASP.to_db_key())
-                .push(&(PIN_KEY_PREFIX.to_owned() + key))
+        for (current_tx_idx, (transfer, transaction)) in
+            (pre_head..).zip(actions)
+        {
+            // Validate tx key
+            let current_tx_key = Key::from(MASP.to_db_key())
+                .push(&(TX_KEY_PREFIX.to_owned() + &current_tx_idx.to_string()))
                 .expect("Cannot obtain a storage key");
-            match self.ctx.read_post::<u64>(&pin_key)? {
-                Some(tx_idx) if tx_idx == pre_head => (),
+            match self.ctx.read_post::<(
+                Epoch,
+                BlockHeight,
+                TxIndex,
+                Transfer,
+                Transaction,
+            )>(&current_tx_key)?
+            {
+                Some((
+                    epoch,
+                    height,
+                    tx_index,
+                    storage_transfer,
+                    storage_transaction,
+                )) if (epoch == self.ctx.get_block_epoch()?
+                    && height == self.ctx.get_block_height()?
+                    && tx_index == self.ctx.get_tx_index()?
+                    && &storage_transfer == transfer
+                    && &storage_transaction == transaction) => {}
                 _ => return Ok(false),
             }
+
+            // Validate pin key
+            if let Some(key) = &transfer.key {
+                let pin_key = Key::from(MASP.to_db_key())
+                    .push(&(PIN_KEY_PREFIX.to_owned() + key))
+                    .expect("Cannot obtain a storage key");
+                match self.ctx.read_post::<u64>(&pin_key)? {
+                    Some(tx_idx) if tx_idx == current_tx_idx => (),
+                    _ => return Ok(false),
+                }
+            }
         }
 
         Ok(true)
     }
-}
 
-impl<'a, DB, H, CA> NativeVp for MaspVp<'a, DB, H, CA>
-where
-    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
-    H: 'static + storage::StorageHasher,
-    CA: 'static + WasmCacheAccess,
-{
-    type Error = Error;
-
-    fn validate_tx(
+    /// Check a single shielded action of the transaction: its transparent
+    /// inputs and outputs, the anchors of its descriptions and its proofs
+    fn valid_shielded_action(
         &self,
-        tx_data: &Tx,
-        keys_changed: &BTreeSet<Key>,
-        _verifiers: &BTreeSet<Address>,
+        epoch: Epoch,
+        transfer: &Transfer,
+        shielded_tx: &Transaction,
     ) -> Result<bool> {
-        let epoch = self.ctx.get_block_epoch()?;
-        let (transfer, shielded_tx) = self.ctx.get_shielded_action(tx_data)?;
         let transfer_amount = transfer
             .amount
             .to_amount(&transfer.token, &self.ctx.pre())?;
@@ -391,10 +394,6 @@ where
         // The Sapling value balance adds to the transparent tx pool
         transparent_tx_pool += shielded_tx.sapling_value_balance();
 
-        if !self.valid_state(keys_changed, &transfer, &shielded_tx)? {
-            return Ok(false);
-        }
-
         if transfer.source != Address::Internal(Masp) {
             // Handle transparent input
             // Note that the asset type is timestamped so shields
@@ -430,7 +429,8 @@ where
             // 4. The convert descriptions's anchors are valid
             // 5. The nullifiers provided by the transaction have not been
             // revealed previously (even in the same tx) and no unneeded
-            // nullifier is being revealed by the tx
+            // nullifier is being revealed by the tx, which is checked over
+            // all the shielded actions of the tx
             if let Some(transp_bundle) = shielded_tx.transparent_bundle() {
                 if !transp_bundle.vin.is_empty() {
                     tracing::debug!(
@@ -442,20 +442,13 @@ where
                 }
             }
 
-            if !(self.valid_spend_descriptions_anchor(&shielded_tx)?
-                && self.valid_convert_descriptions_anchor(&shielded_tx)?
-                && self.valid_nullifiers_reveal(keys_changed, &shielded_tx)?)
+            if !(self.valid_spend_descriptions_anchor(shielded_tx)?
+                && self.valid_convert_descriptions_anchor(shielded_tx)?)
             {
                 return Ok(false);
             }
         }
 
-        // The transaction must correctly update the note commitment tree
-        // in storage with the new output descriptions
-        if !self.valid_note_commitment_update(&shielded_tx)? {
-            return Ok(false);
-        }
-
         if transfer.target != Address::Internal(Masp) {
             // Handle transparent output
             // The following boundary conditions must be satisfied
@@ -595,7 +588,63 @@ where
         self.ctx
             .charge_gas(MASP_VERIFY_SHIELDED_TX_GAS)
             .map_err(Error::NativeVpError)?;
-        Ok(verify_shielded_tx(&shielded_tx))
+        Ok(verify_shielded_tx(shielded_tx))
+    }
+}
+
+impl<'a, DB, H, CA> NativeVp for MaspVp<'a, DB, H, CA>
+where
+    DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: 'static + storage::StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    type Error = Error;
+
+    fn validate_tx(
+        &self,
+        tx_data: &Tx,
+        keys_changed: &BTreeSet<Key>,
+        _verifiers: &BTreeSet<Address>,
+    ) -> Result<bool> {
+        let epoch = self.ctx.get_block_epoch()?;
+        let actions = self.ctx.get_shielded_actions(tx_data)?;
+        if actions.is_empty() {
+            return Err(Error::NativeVpError(native_vp::Error::SimpleMessage(
+                "No shielded action in the transaction",
+            )));
+        }
+
+        if !self.valid_state(keys_changed, &actions)? {
+            return Ok(false);
+        }
+
+        // The nullifiers revealed by all the actions with shielded inputs must
+        // be checked together, to catch double spends across actions
+        let shielded_inputs = actions
+            .iter()
+            .filter(|(transfer, _)| transfer.source == Address::Internal(Masp))
+            .map(|(_, shielded_tx)| shielded_tx)
+            .collect::<Vec<_>>();
+        if !self.valid_nullifiers_reveal(keys_changed, &shielded_inputs)? {
+            return Ok(false);
+        }
+
+        // The transaction must correctly update the note commitment tree
+        // in storage with the new output descriptions of all the actions
+        let shielded_txs = actions
+            .iter()
+            .map(|(_, shielded_tx)| shielded_tx)
+            .collect::<Vec<_>>();
+        if !self.valid_note_commitment_update(&shielded_txs)? {
+            return Ok(false);
+        }
+
+        for (transfer, shielded_tx) in &actions {
+            if !self.valid_shielded_action(epoch, transfer, shielded_tx)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
     }
 }
 
@@ -604,3 +653,163 @@ impl From<native_vp::Error> for Error {
         Self::NativeVpError(err)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use masp_primitives::consensus::{BlockHeight as MaspHeight, BranchId};
+    use masp_primitives::transaction::{TransactionData, TxVersion};
+    use namada_core::ledger::gas::TxGasMeter;
+    use namada_core::ledger::masp_utils::handle_masp_tx;
+    use namada_core::ledger::storage::mockdb::MockDB;
+    use namada_core::ledger::storage::testing::TestWlStorage;
+    use namada_core::ledger::storage::traits::Sha256Hasher;
+    use namada_core::proto::{Data, Section};
+    use namada_core::types::address::nam;
+    use namada_core::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::ledger::gas::VpGasMeter;
+    use crate::vm::{wasm, WasmCacheRwAccess};
+
+    static NO_VERIFIERS: BTreeSet<Address> = BTreeSet::new();
+
+    /// Build an empty masp tx, distinguished by its lock time
+    fn dummy_masp_tx(lock_time: u32) -> Transaction {
+        TransactionData::from_parts(
+            TxVersion::MASPv5,
+            BranchId::MASP,
+            lock_time,
+            MaspHeight::from_u32(0),
+            None,
+            None,
+        )
+        .freeze()
+        .unwrap()
+    }
+
+    /// Add a dummy masp tx section to the tx and return a transfer that
+    /// refers to it, together with the masp tx
+    fn add_shielded_transfer(
+        tx: &mut Tx,
+        lock_time: u32,
+    ) -> (Transfer, Transaction) {
+        let masp_tx = dummy_masp_tx(lock_time);
+        let (_, shielded_hash) = tx.add_masp_tx_section(masp_tx.clone());
+        let transfer = Transfer {
+            source: established_address_1(),
+            target: established_address_2(),
+            token: nam(),
+            amount: token::DenominatedAmount::native(
+                token::Amount::native_whole(lock_time.into()),
+            ),
+            key: None,
+            shielded: Some(shielded_hash),
+        };
+        (transfer, masp_tx)
+    }
+
+    /// Set up the context of the MASP VP. The returned directory holds the
+    /// VP wasm cache and must outlive the context.
+    fn setup_ctx<'a>(
+        wl_storage: &'a TestWlStorage,
+        tx: &'a Tx,
+        keys_changed: &'a BTreeSet<Key>,
+    ) -> (Ctx<'a, MockDB, Sha256Hasher, WasmCacheRwAccess>, TempDir) {
+        let gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(1_000_000_u64.into()),
+        );
+        let (vp_wasm_cache, vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let ctx = Ctx::new(
+            &MASP,
+            &wl_storage.storage,
+            &wl_storage.write_log,
+            tx,
+            &TxIndex(0),
+            gas_meter,
+            keys_changed,
+            &NO_VERIFIERS,
+            vp_wasm_cache,
+        );
+        (ctx, vp_cache_dir)
+    }
+
+    /// Test that all the shielded actions of a tx batching two shielded
+    /// transfers are collected, that the data sections not referenced by the
+    /// tx are ignored and that the singular getter returns the first action.
+    #[test]
+    fn test_get_shielded_actions() {
+        let wl_storage = TestWlStorage::default();
+        let keys_changed = BTreeSet::new();
+
+        let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let actions = vec![
+            add_shielded_transfer(&mut tx, 1),
+            add_shielded_transfer(&mut tx, 2),
+        ];
+        let batch: Vec<Transfer> = actions
+            .iter()
+            .map(|(transfer, _)| transfer.clone())
+            .collect();
+        tx.add_data(batch);
+        // A shielded transfer in a data section that the tx doesn't refer to
+        let (unreferenced, _) = add_shielded_transfer(&mut tx, 3);
+        tx.add_section(Section::Data(Data::new(
+            unreferenced.serialize_to_vec(),
+        )));
+
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx, &keys_changed);
+        assert_eq!(ctx.get_shielded_actions(&tx).unwrap(), actions);
+        assert_eq!(ctx.get_shielded_action(&tx).unwrap(), actions[0]);
+    }
+
+    /// Test that a transfer in the tx data without a shielded section is
+    /// rejected
+    #[test]
+    fn test_get_shielded_actions_unshielded_transfer() {
+        let wl_storage = TestWlStorage::default();
+        let keys_changed = BTreeSet::new();
+
+        let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let (mut transfer, _) = add_shielded_transfer(&mut tx, 1);
+        transfer.shielded = None;
+        tx.add_data(transfer);
+
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx, &keys_changed);
+        assert!(ctx.get_shielded_actions(&tx).is_err());
+    }
+
+    /// Test that the MASP state written by each of the shielded actions of a
+    /// tx is validated, in the order of the actions
+    #[test]
+    fn test_valid_state_of_shielded_actions() {
+        let mut wl_storage = TestWlStorage::default();
+        let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let (first, mut second) = (
+            add_shielded_transfer(&mut tx, 1),
+            add_shielded_transfer(&mut tx, 2),
+        );
+        second.0.key = Some("pin".to_string());
+        for (transfer, masp_tx) in [&first, &second] {
+            handle_masp_tx(&mut wl_storage, transfer, masp_tx).unwrap();
+        }
+        let keys_changed = wl_storage.write_log.get_keys();
+
+        let (ctx, _vp_cache_dir) = setup_ctx(&wl_storage, &tx, &keys_changed);
+        let vp = MaspVp { ctx };
+        assert!(
+            vp.valid_state(&keys_changed, &[first.clone(), second.clone()])
+                .unwrap()
+        );
+        // The actions must match the recorded ones, in order
+        assert!(
+            !vp.valid_state(&keys_changed, &[second.clone(), first.clone()])
+                .unwrap()
+        );
+        // All the recorded actions must be validated
+        assert!(!vp.valid_state(&keys_changed, &[first]).unwrap());
+    }
+}
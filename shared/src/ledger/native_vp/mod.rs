@@ -29,6 +29,7 @@ use crate::types::ibc::IbcEvent;
 use crate::types::storage::{
     BlockHash, BlockHeight, Epoch, Header, Key, TxIndex,
 };
+use crate::types::transaction::Fee;
 use crate::vm::prefix_iter::PrefixIterators;
 use crate::vm::WasmCacheAccess;
 
@@ -565,12 +566,25 @@ where
         }
     }
 
+    fn get_wrapper_fee(&self) -> Result<Option<Fee>, storage_api::Error> {
+        vp_host_fns::get_wrapper_fee(
+            &mut self.gas_meter.borrow_mut(),
+            self.tx,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()
+    }
+
     fn charge_gas(&self, used_gas: u64) -> Result<(), storage_api::Error> {
         self.gas_meter.borrow_mut().consume(used_gas).map_err(|_| {
             Error::SimpleMessage("Gas limit exceeded in native vp")
         })
     }
 
+    fn get_keys_changed(&self) -> &BTreeSet<Key> {
+        self.keys_changed
+    }
+
     fn get_tx_code_hash(&self) -> Result<Option<Hash>, storage_api::Error> {
         vp_host_fns::get_tx_code_hash(
             &mut self.gas_meter.borrow_mut(),
@@ -580,6 +594,15 @@ where
         .into_storage_result()
     }
 
+    fn get_tx_code_hashes(&self) -> Result<Vec<Hash>, storage_api::Error> {
+        vp_host_fns::get_tx_code_hashes(
+            &mut self.gas_meter.borrow_mut(),
+            self.tx,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()
+    }
+
     fn read_pre<T: borsh::BorshDeserialize>(
         &self,
         key: &Key,
@@ -716,3 +739,440 @@ pub(super) mod testing {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use borsh_ext::BorshSerializeExt;
+    use namada_core::ledger::gas::{Gas, TxGasMeter};
+    use tempfile::TempDir;
+
+    use super::*;
+    use crate::core::ledger::storage::mockdb::MockDB;
+    use crate::core::ledger::storage::testing::TestWlStorage;
+    use crate::core::ledger::storage::traits::Sha256Hasher;
+    use crate::core::types::address::nam;
+    use crate::core::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use crate::core::types::key::testing::keypair_1;
+    use crate::core::types::key::RefTo;
+    use crate::core::types::token;
+    use crate::core::types::transaction::{DecryptedTx, TxType, WrapperTx};
+    use crate::ledger::parameters::storage as parameters_storage;
+    use crate::proto::{Code, Section};
+    use crate::vm::{wasm, WasmCacheRwAccess};
+
+    /// No verifiers for the test contexts
+    static NO_VERIFIERS: BTreeSet<Address> = BTreeSet::new();
+
+    /// Set up the context of a native VP owned by `address`, with an
+    /// unlimited gas meter. The returned directory holds the VP wasm cache
+    /// and must outlive the context.
+    fn setup_ctx<'a>(
+        address: &'a Address,
+        wl_storage: &'a TestWlStorage,
+        tx: &'a Tx,
+        keys_changed: &'a BTreeSet<Key>,
+    ) -> (Ctx<'a, MockDB, Sha256Hasher, WasmCacheRwAccess>, TempDir) {
+        let gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(u64::MAX.into()),
+        );
+        let (vp_wasm_cache, vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let ctx = Ctx::new(
+            address,
+            &wl_storage.storage,
+            &wl_storage.write_log,
+            tx,
+            &TxIndex(0),
+            gas_meter,
+            keys_changed,
+            &NO_VERIFIERS,
+            vp_wasm_cache,
+        );
+        (ctx, vp_cache_dir)
+    }
+
+    /// Test that the metered reads charge the gas for both the key lookup and
+    /// the bytes read on a hit, and for the key lookup alone on a miss
+    #[test]
+    fn test_read_metered_charges_gas() {
+        let mut wl_storage = TestWlStorage::default();
+        let address = established_address_1();
+        let hit_key = Key::parse("hit").unwrap();
+        let miss_key = Key::parse("miss").unwrap();
+        let value = 42_u64;
+        wl_storage
+            .storage
+            .write(&hit_key, value.serialize_to_vec())
+            .unwrap();
+
+        let tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let keys_changed = BTreeSet::new();
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&address, &wl_storage, &tx, &keys_changed);
+        let gas_per_byte = 10;
+        let consumed = |read: &dyn Fn()| -> u64 {
+            let before = ctx.gas_meter.borrow().get_current_gas();
+            read();
+            let after = ctx.gas_meter.borrow().get_current_gas();
+            u64::from(after.checked_sub(before).unwrap_or(Gas::default()))
+        };
+
+        // Hit
+        let plain = consumed(&|| {
+            assert_eq!(ctx.read_pre::<u64>(&hit_key).unwrap(), Some(value));
+        });
+        let metered = consumed(&|| {
+            let read =
+                ctx.read_pre_metered::<u64>(&hit_key, gas_per_byte).unwrap();
+            assert_eq!(read, Some(value));
+        });
+        let hit_len = (hit_key.len() + value.serialize_to_vec().len()) as u64;
+        assert_eq!(metered, plain + hit_len * gas_per_byte);
+
+        let plain = consumed(&|| {
+            assert_eq!(ctx.read_post::<u64>(&hit_key).unwrap(), Some(value));
+        });
+        let metered = consumed(&|| {
+            let read = ctx
+                .read_post_metered::<u64>(&hit_key, gas_per_byte)
+                .unwrap();
+            assert_eq!(read, Some(value));
+        });
+        assert_eq!(metered, plain + hit_len * gas_per_byte);
+
+        // Miss
+        let plain = consumed(&|| {
+            assert_eq!(ctx.read_pre::<u64>(&miss_key).unwrap(), None);
+        });
+        let metered = consumed(&|| {
+            let read = ctx
+                .read_pre_metered::<u64>(&miss_key, gas_per_byte)
+                .unwrap();
+            assert_eq!(read, None);
+        });
+        let miss_len = miss_key.len() as u64;
+        assert_eq!(metered, plain + miss_len * gas_per_byte);
+
+        let plain = consumed(&|| {
+            assert_eq!(ctx.read_post::<u64>(&miss_key).unwrap(), None);
+        });
+        let metered = consumed(&|| {
+            let read = ctx
+                .read_post_metered::<u64>(&miss_key, gas_per_byte)
+                .unwrap();
+            assert_eq!(read, None);
+        });
+        assert_eq!(metered, plain + miss_len * gas_per_byte);
+    }
+
+    /// Test that the changed keys are queried from the VP env
+    #[test]
+    fn test_changed_keys_from_vp_env() {
+        let wl_storage = TestWlStorage::default();
+        let address = established_address_1();
+        let key = |key: &str| Key::parse(key).unwrap();
+        let keys_changed =
+            BTreeSet::from([key("a/x"), key("a/y/z"), key("ab/x"), key("b/x")]);
+
+        let tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&address, &wl_storage, &tx, &keys_changed);
+
+        assert!(ctx.key_changed(&key("a/x")));
+        assert!(ctx.key_changed(&key("b/x")));
+        assert!(!ctx.key_changed(&key("a")));
+        assert!(!ctx.key_changed(&key("c/x")));
+
+        let prefix = key("a");
+        let changed = ctx.changed_keys_with_prefix(&prefix).collect::<Vec<_>>();
+        assert_eq!(changed, vec![&key("a/x"), &key("a/y/z")]);
+        let prefix = key("a/y");
+        let changed = ctx.changed_keys_with_prefix(&prefix).collect::<Vec<_>>();
+        assert_eq!(changed, vec![&key("a/y/z")]);
+        let prefix = key("c");
+        assert_eq!(ctx.changed_keys_with_prefix(&prefix).count(), 0);
+    }
+
+    /// Test the balance deltas of an account being credited for the first
+    /// time and of an account being debited
+    #[test]
+    fn test_balance_delta() {
+        let mut wl_storage = TestWlStorage::default();
+        let token = nam();
+        let creditor = established_address_1();
+        let debtor = established_address_2();
+        let debtor_key = token::balance_key(&token, &debtor);
+        wl_storage
+            .storage
+            .write(&debtor_key, token::Amount::from(100).serialize_to_vec())
+            .unwrap();
+        wl_storage
+            .write_log
+            .write(&debtor_key, token::Amount::from(40).serialize_to_vec())
+            .unwrap();
+        wl_storage
+            .write_log
+            .write(
+                &token::balance_key(&token, &creditor),
+                token::Amount::from(60).serialize_to_vec(),
+            )
+            .unwrap();
+
+        let tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let keys_changed = BTreeSet::new();
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&creditor, &wl_storage, &tx, &keys_changed);
+
+        assert_eq!(
+            ctx.balance_delta(&token, &creditor).unwrap(),
+            (token::Amount::zero(), token::Amount::from(60))
+        );
+        assert_eq!(
+            ctx.balance_delta(&token, &debtor).unwrap(),
+            (token::Amount::from(100), token::Amount::from(40))
+        );
+    }
+
+    /// Test the verification of an escrow from the balance deltas and the
+    /// changed keys
+    #[test]
+    fn test_verify_escrow() {
+        let mut wl_storage = TestWlStorage::default();
+        let token = nam();
+        let payer = established_address_1();
+        let escrow = established_address_2();
+        let payer_key = token::balance_key(&token, &payer);
+        let escrow_key = token::balance_key(&token, &escrow);
+        wl_storage
+            .storage
+            .write(&payer_key, token::Amount::from(100).serialize_to_vec())
+            .unwrap();
+        wl_storage
+            .write_log
+            .write(&payer_key, token::Amount::from(90).serialize_to_vec())
+            .unwrap();
+        wl_storage
+            .write_log
+            .write(&escrow_key, token::Amount::from(10).serialize_to_vec())
+            .unwrap();
+
+        let tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let keys_changed = BTreeSet::from([payer_key.clone(), escrow_key]);
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&escrow, &wl_storage, &tx, &keys_changed);
+        let verify = |amount: u64, changed_keys: &BTreeSet<Key>| {
+            ctx.verify_escrow(
+                &token,
+                &payer,
+                &escrow,
+                &token::Amount::from(amount),
+                changed_keys,
+            )
+            .unwrap()
+        };
+
+        // Happy path
+        assert!(verify(10, &keys_changed));
+        // Incorrect deltas
+        assert!(!verify(9, &keys_changed));
+        assert!(!verify(11, &keys_changed));
+        // A nil amount with changed balance keys
+        assert!(!verify(0, &keys_changed));
+        // A nil amount with unchanged balance keys
+        assert!(verify(0, &BTreeSet::new()));
+        // A missing balance key
+        assert!(!verify(10, &BTreeSet::from([payer_key])));
+        // Another balance of the token changed
+        let mut more_keys_changed = keys_changed.clone();
+        more_keys_changed.insert(token::balance_key(&token, &nam()));
+        assert!(!verify(10, &more_keys_changed));
+    }
+
+    /// Test that the wrapper fee is only found for a wrapped tx
+    #[test]
+    fn test_get_wrapper_fee() {
+        let wl_storage = TestWlStorage::default();
+        let address = established_address_1();
+        let fee = Fee {
+            amount_per_gas_unit: token::DenominatedAmount::native(
+                token::Amount::native_whole(10),
+            ),
+            token: nam(),
+        };
+        let decrypted_tx =
+            Tx::from_type(TxType::Decrypted(DecryptedTx::Decrypted));
+        let wrapper_tx =
+            Tx::from_type(TxType::Wrapper(Box::new(WrapperTx::new(
+                fee.clone(),
+                keypair_1().ref_to(),
+                Epoch(0),
+                Default::default(),
+                None,
+            ))));
+
+        for (tx, expected) in [(decrypted_tx, None), (wrapper_tx, Some(fee))] {
+            let keys_changed = BTreeSet::new();
+            let (ctx, _vp_cache_dir) =
+                setup_ctx(&address, &wl_storage, &tx, &keys_changed);
+            assert_eq!(ctx.get_wrapper_fee().unwrap(), expected);
+        }
+    }
+
+    /// Test reading the block headers in a range with a missing header
+    #[test]
+    fn test_get_block_headers_with_gap() {
+        let mut wl_storage = TestWlStorage::default();
+        // Commit blocks with headers at heights 1, 2 and 4
+        for height in [1_u64, 2, 4] {
+            wl_storage
+                .storage
+                .begin_block(BlockHash::default(), BlockHeight(height))
+                .unwrap();
+            wl_storage
+                .storage
+                .set_header(Header {
+                    hash: Hash([height as u8; 32]),
+                    ..Default::default()
+                })
+                .unwrap();
+            wl_storage.commit_block().unwrap();
+        }
+        wl_storage
+            .storage
+            .begin_block(BlockHash::default(), BlockHeight(5))
+            .unwrap();
+
+        let address = established_address_1();
+        let tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let keys_changed = BTreeSet::new();
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&address, &wl_storage, &tx, &keys_changed);
+
+        let headers = ctx
+            .get_block_headers(BlockHeight(1), BlockHeight(4))
+            .unwrap()
+            .into_iter()
+            .map(|(height, header)| (height, header.hash))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            headers,
+            vec![
+                (BlockHeight(1), Hash([1; 32])),
+                (BlockHeight(2), Hash([2; 32])),
+                (BlockHeight(4), Hash([4; 32])),
+            ]
+        );
+
+        let headers = ctx
+            .get_block_headers(BlockHeight(4), BlockHeight(1))
+            .unwrap();
+        assert!(headers.is_empty());
+    }
+
+    /// Test that the sequential evaluation of VPs stops at the first one that
+    /// rejects
+    #[cfg(feature = "wasm-runtime")]
+    #[test]
+    fn test_eval_in_sequence_short_circuits() {
+        use namada_test_utils::TestWasms;
+
+        let mut wl_storage = TestWlStorage::default();
+        let address = established_address_1();
+        let mut store_vp = |vp: TestWasms| {
+            let code = vp.read_bytes();
+            let code_hash = Hash::sha256(&code);
+            let code_len = (code.len() as u64).serialize_to_vec();
+            wl_storage
+                .storage
+                .write(&Key::wasm_code(&code_hash), code)
+                .unwrap();
+            wl_storage
+                .storage
+                .write(&Key::wasm_code_len(&code_hash), code_len)
+                .unwrap();
+            code_hash
+        };
+        let vp_true = store_vp(TestWasms::VpAlwaysTrue);
+        let vp_false = store_vp(TestWasms::VpAlwaysFalse);
+
+        let tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let keys_changed = BTreeSet::new();
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&address, &wl_storage, &tx, &keys_changed);
+        let eval_in_sequence = |vps: &[Hash]| -> (bool, u64) {
+            let vps: Vec<_> = vps.iter().map(|vp| (*vp, tx.clone())).collect();
+            let before = ctx.gas_meter.borrow().get_current_gas();
+            let accepted = ctx.eval_in_sequence(&vps).unwrap();
+            let after = ctx.gas_meter.borrow().get_current_gas();
+            let consumed =
+                u64::from(after.checked_sub(before).unwrap_or(Gas::default()));
+            (accepted, consumed)
+        };
+
+        // warm up the compilation cache
+        assert!(eval_in_sequence(&[vp_true, vp_true, vp_true]).0);
+
+        let (accepted, two_vps_gas) = eval_in_sequence(&[vp_true, vp_false]);
+        assert!(!accepted);
+        // the third VP is never evaluated, so it doesn't consume any gas
+        let (accepted, three_vps_gas) =
+            eval_in_sequence(&[vp_true, vp_false, vp_true]);
+        assert!(!accepted);
+        assert_eq!(three_vps_gas, two_vps_gas);
+    }
+
+    /// Test that the code hashes of all the code sections of a tx are
+    /// queried from the VP env
+    #[test]
+    fn test_get_tx_code_hashes() {
+        let wl_storage = TestWlStorage::default();
+        let address = established_address_1();
+
+        let mut tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let first = Code::new(vec![1], None);
+        let second = Code::new(vec![2], None);
+        tx.add_section(Section::Code(first.clone()));
+        tx.set_code(second.clone());
+
+        let keys_changed = BTreeSet::new();
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&address, &wl_storage, &tx, &keys_changed);
+
+        // the code designated by the header comes first
+        let hashes = ctx.get_tx_code_hashes().unwrap();
+        assert_eq!(hashes, vec![second.code.hash(), first.code.hash()]);
+        // the singular code hash is the first one
+        assert_eq!(ctx.get_tx_code_hash().unwrap(), hashes.first().copied());
+    }
+
+    /// Test the tx and VP whitelist checks, where an empty whitelist allows
+    /// any code
+    #[test]
+    fn test_whitelist_checks() {
+        let mut wl_storage = TestWlStorage::default();
+        let address = established_address_1();
+        let listed = Hash::sha256(b"listed");
+        let unlisted = Hash::sha256(b"unlisted");
+        wl_storage
+            .storage
+            .write(
+                &parameters_storage::get_vp_whitelist_storage_key(),
+                vec![listed.to_string().to_lowercase()].serialize_to_vec(),
+            )
+            .unwrap();
+
+        let tx = Tx::new(wl_storage.storage.chain_id.clone(), None);
+        let keys_changed = BTreeSet::new();
+        let (ctx, _vp_cache_dir) =
+            setup_ctx(&address, &wl_storage, &tx, &keys_changed);
+
+        // the tx whitelist is empty
+        assert!(ctx.is_tx_whitelisted(&listed).unwrap());
+        assert!(ctx.is_tx_whitelisted(&unlisted).unwrap());
+        // the vp whitelist only contains one hash
+        assert!(ctx.is_vp_whitelisted(&listed).unwrap());
+        assert!(!ctx.is_vp_whitelisted(&unlisted).unwrap());
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::num::TryFromIntError;
 
+use borsh_ext::BorshSerializeExt;
 use namada_core::ledger::gas::MEMORY_ACCESS_GAS_PER_BYTE;
 use namada_core::types::address::{Address, ESTABLISHED_ADDRESS_BYTES_LEN};
 use namada_core::types::hash::{Hash, HASH_LENGTH};
@@ -17,6 +18,7 @@ use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{self, write_log, Storage, StorageHasher};
 use crate::proto::{Section, Tx};
 use crate::types::ibc::IbcEvent;
+use crate::types::transaction::Fee;
 
 /// These runtime errors will abort VP execution immediately
 #[allow(missing_docs)]
@@ -315,6 +317,49 @@ pub fn get_tx_code_hash(
     Ok(hash)
 }
 
+/// Getting the hashes of the code of all the code sections of the
+/// transaction. The first one is the code designated by the header, i.e. that
+/// of [`get_tx_code_hash`], followed by the others in the order of the
+/// sections.
+pub fn get_tx_code_hashes(
+    gas_meter: &mut VpGasMeter,
+    tx: &Tx,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<Vec<Hash>> {
+    let code_sechash = tx.code_sechash();
+    let header_code = tx
+        .get_section(code_sechash)
+        .and_then(|x| Section::code_sec(x.as_ref()));
+    let other_code = tx
+        .sections
+        .iter()
+        .filter(|section| &section.get_hash() != code_sechash)
+        .filter_map(Section::code_sec);
+    let hashes: Vec<Hash> = header_code
+        .into_iter()
+        .chain(other_code)
+        .map(|code| code.code.hash())
+        .collect();
+    add_gas(
+        gas_meter,
+        (hashes.len() * HASH_LENGTH) as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        sentinel,
+    )?;
+    Ok(hashes)
+}
+
+/// Getting the fee of the wrapper of the transaction, if any.
+pub fn get_wrapper_fee(
+    gas_meter: &mut VpGasMeter,
+    tx: &Tx,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<Option<Fee>> {
+    let fee = tx.header.wrapper().map(|wrapper| wrapper.fee);
+    let len = fee.serialize_to_vec().len() as u64;
+    add_gas(gas_meter, len * MEMORY_ACCESS_GAS_PER_BYTE, sentinel)?;
+    Ok(fee)
+}
+
 /// Getting the block epoch. The epoch is that of the block to which the
 /// current transaction is being applied.
 pub fn get_block_epoch<DB, H>(
@@ -11,6 +11,9 @@ use namada_core::ledger::gas::{
     GasMetering, TxGasMeter, MEMORY_ACCESS_GAS_PER_BYTE,
 };
 use namada_core::ledger::masp_utils;
+use namada_core::types::account::{
+    verify_account_signatures, Account, AccountPublicKeysMap,
+};
 use namada_core::types::address::ESTABLISHED_ADDRESS_BYTES_LEN;
 use namada_core::types::internal::KeyVal;
 use namada_core::types::storage::TX_INDEX_LENGTH;
@@ -1825,6 +1828,67 @@ where
     vp_host_fns::add_gas(gas_meter, gas, sentinel)
 }
 
+/// Getting the tx code hashes function exposed to the wasm VM VP
+/// environment.
+///
+/// Returns the length of the encoded hashes.
+pub fn vp_get_tx_code_hashes<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    let tx = unsafe { env.ctx.tx.get() };
+    let hashes = vp_host_fns::get_tx_code_hashes(gas_meter, tx, sentinel)?;
+    let value = hashes.serialize_to_vec();
+    let len: i64 = value
+        .len()
+        .try_into()
+        .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+    let result_buffer = unsafe { env.ctx.result_buffer.get() };
+    result_buffer.replace(value);
+    Ok(len)
+}
+
+/// Getting the wrapper fee function exposed to the wasm VM VP environment.
+///
+/// Returns `-1` when the transaction is not wrapped, or the length of the
+/// encoded fee when it is.
+pub fn vp_get_wrapper_fee<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    let tx = unsafe { env.ctx.tx.get() };
+    let fee = vp_host_fns::get_wrapper_fee(gas_meter, tx, sentinel)?;
+    Ok(match fee {
+        Some(fee) => {
+            let value = fee.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
 /// Getting the block epoch function exposed to the wasm VM VP
 /// environment. The epoch is that of the block to which the current
 /// transaction is being applied.
@@ -1919,10 +1983,8 @@ where
         .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
     let public_keys_map =
-        namada_core::types::account::AccountPublicKeysMap::try_from_slice(
-            &public_keys_map,
-        )
-        .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+        AccountPublicKeysMap::try_from_slice(&public_keys_map)
+            .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
     let (signer, gas) = env
         .memory
@@ -1941,12 +2003,16 @@ where
         .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
     let tx = unsafe { env.ctx.tx.get() };
-
-    match tx.verify_signatures(
-        &hashes,
+    let account = Account {
         public_keys_map,
-        &Some(signer),
         threshold,
+        address: signer,
+    };
+
+    match verify_account_signatures(
+        &account,
+        tx,
+        &hashes,
         max_signatures,
         || gas_meter.consume(gas::VERIFY_TX_SIG_GAS),
     ) {
@@ -2533,7 +2599,9 @@ where
     ) -> Result<(), storage_api::Error> {
         use namada_core::types::token;
 
-        let amount = amount.to_amount(token, self)?;
+        let amount = storage_api::token::denominated_amount_checked(
+            self, token, amount,
+        )?;
         if amount != token::Amount::default() && src != dest {
             let src_key = token::balance_key(token, src);
             let dest_key = token::balance_key(token, dest);
@@ -359,6 +359,8 @@ mod native_vp_host_env {
     native_host_fn!(vp_get_block_header(height: u64) -> i64);
     native_host_fn!(vp_get_block_hash(result_ptr: u64));
     native_host_fn!(vp_get_tx_code_hash(result_ptr: u64));
+    native_host_fn!(vp_get_tx_code_hashes() -> i64);
+    native_host_fn!(vp_get_wrapper_fee() -> i64);
     native_host_fn!(vp_get_block_epoch() -> u64);
     native_host_fn!(vp_get_native_token(result_ptr: u64));
     native_host_fn!(vp_eval(
@@ -7,6 +7,7 @@ pub use namada_core::ledger::ibc::{
     IbcActions, IbcCommonContext, IbcStorageContext, ProofSpec, TransferModule,
 };
 use namada_core::ledger::masp_utils;
+use namada_core::ledger::storage_api::token::denominated_amount_checked;
 use namada_core::ledger::tx_env::TxEnv;
 use namada_core::types::address::{Address, InternalAddress};
 pub use namada_core::types::ibc::{IbcEvent, IbcShieldedTransfer};
@@ -46,6 +47,9 @@ impl IbcStorageContext for Ctx {
         token: &Address,
         amount: DenominatedAmount,
     ) -> std::result::Result<(), Error> {
+        // Reject an amount in another denomination than the token's instead
+        // of converting it
+        denominated_amount_checked(self, token, amount)?;
         transfer(self, src, dest, token, amount)
     }
 
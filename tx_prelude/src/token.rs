@@ -1,4 +1,5 @@
 pub use namada_core::ledger::masp_utils;
+use namada_core::ledger::storage_api::token::InsufficientFunds;
 use namada_core::types::address::Address;
 use namada_core::types::token;
 pub use namada_core::types::token::*;
@@ -15,7 +16,10 @@ pub fn transfer(
     amount: DenominatedAmount,
 ) -> TxResult {
     let amount = amount.to_amount(token, ctx)?;
-    if amount != Amount::default() && src != dest {
+    if src == dest {
+        return check_self_transfer(ctx, src, token, amount);
+    }
+    if amount != Amount::default() {
         let src_key = token::balance_key(token, src);
         let dest_key = token::balance_key(token, dest);
         let src_bal: Option<Amount> = ctx.read(&src_key)?;
@@ -40,7 +44,10 @@ pub fn undenominated_transfer(
     token: &Address,
     amount: Amount,
 ) -> TxResult {
-    if amount != Amount::default() && src != dest {
+    if src == dest {
+        return check_self_transfer(ctx, src, token, amount);
+    }
+    if amount != Amount::default() {
         let src_key = token::balance_key(token, src);
         let dest_key = token::balance_key(token, dest);
         let src_bal: Option<Amount> = ctx.read(&src_key)?;
@@ -57,6 +64,28 @@ pub fn undenominated_transfer(
     Ok(())
 }
 
+/// A transfer to oneself leaves the balance untouched, as long as the
+/// source has sufficient funds.
+fn check_self_transfer(
+    ctx: &Ctx,
+    owner: &Address,
+    token: &Address,
+    amount: Amount,
+) -> TxResult {
+    let balance: Amount = ctx
+        .read(&token::balance_key(token, owner))?
+        .unwrap_or_default();
+    if balance < amount {
+        return Err(Error::new(InsufficientFunds {
+            token: token.clone(),
+            owner: owner.clone(),
+            balance,
+            amount,
+        }));
+    }
+    Ok(())
+}
+
 /// Mint that can be used in a transaction.
 pub fn mint(
     ctx: &mut Ctx,
@@ -77,7 +106,7 @@ pub fn mint(
     ctx.write(&minted_key, minted_bal)?;
 
     let minter_key = token::minter_key(token);
-    ctx.write(&minter_key, minter)?;
+    ctx.write_if_changed(&minter_key, minter.clone())?;
 
     Ok(())
 }
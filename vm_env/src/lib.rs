@@ -212,6 +212,12 @@ pub mod vp {
         // Get the current tx hash
         pub fn namada_vp_get_tx_code_hash(result_ptr: u64);
 
+        // Get the code hashes of all the code sections of the current tx
+        pub fn namada_vp_get_tx_code_hashes() -> i64;
+
+        // Get the fee of the current tx wrapper
+        pub fn namada_vp_get_wrapper_fee() -> i64;
+
         // Get the current block epoch
         pub fn namada_vp_get_block_epoch() -> u64;
 
@@ -44,23 +44,21 @@ pub fn sha256(bytes: &[u8]) -> Hash {
 }
 
 pub fn is_tx_whitelisted(ctx: &Ctx) -> VpResult {
-    let tx_hash = ctx.get_tx_code_hash()?;
-    let key = parameters::storage::get_tx_whitelist_storage_key();
-    let whitelist: Vec<String> = ctx.read_pre(&key)?.unwrap_or_default();
-    // if whitelist is empty, allow any transaction
-    Ok(whitelist.is_empty()
-        || (tx_hash.is_some()
-            && whitelist
-                .contains(&tx_hash.unwrap().to_string().to_lowercase())))
+    match ctx.get_tx_code_hash()? {
+        Some(tx_hash) => ctx.is_tx_whitelisted(&tx_hash),
+        None => {
+            let key = parameters::storage::get_tx_whitelist_storage_key();
+            let whitelist: Vec<String> =
+                ctx.read_pre(&key)?.unwrap_or_default();
+            // if whitelist is empty, allow any transaction
+            Ok(whitelist.is_empty())
+        }
+    }
 }
 
 pub fn is_vp_whitelisted(ctx: &Ctx, vp_hash: &[u8]) -> VpResult {
     let vp_hash = Hash::try_from(vp_hash).unwrap();
-    let key = parameters::storage::get_vp_whitelist_storage_key();
-    let whitelist: Vec<String> = ctx.read_pre(&key)?.unwrap_or_default();
-    // if whitelist is empty, allow any transaction
-    Ok(whitelist.is_empty()
-        || whitelist.contains(&vp_hash.to_string().to_lowercase()))
+    ctx.is_vp_whitelisted(&vp_hash)
 }
 
 /// Log a string. The message will be printed at the `tracing::Level::Info`.
@@ -81,19 +79,35 @@ pub fn is_proposal_accepted(ctx: &Ctx, proposal_id: u64) -> VpResult {
 
 /// Verify section signatures
 pub fn verify_signatures(ctx: &Ctx, tx: &Tx, owner: &Address) -> VpResult {
-    let max_signatures_per_transaction =
-        parameters::max_signatures_per_transaction(&ctx.pre())?;
-
-    let public_keys_index_map =
+    let public_keys_map =
         storage_api::account::public_keys_index_map(&ctx.pre(), owner)?;
     let threshold =
         storage_api::account::threshold(&ctx.pre(), owner)?.unwrap_or(1);
+    let account = account::Account {
+        public_keys_map,
+        threshold,
+        address: owner.clone(),
+    };
+
+    verify_account_signatures(ctx, tx, &account)
+}
+
+/// Verify that the tx header is signed by enough of the given account's keys
+/// to meet its threshold. The signatures are verified and gas-metered by the
+/// host environment.
+pub fn verify_account_signatures(
+    ctx: &Ctx,
+    tx: &Tx,
+    account: &account::Account,
+) -> VpResult {
+    let max_signatures_per_transaction =
+        parameters::max_signatures_per_transaction(&ctx.pre())?;
 
     // Serialize parameters
     let max_signatures = max_signatures_per_transaction.serialize_to_vec();
-    let public_keys_map = public_keys_index_map.serialize_to_vec();
+    let public_keys_map = account.public_keys_map.serialize_to_vec();
     let targets = [tx.raw_header_hash()].serialize_to_vec();
-    let signer = owner.serialize_to_vec();
+    let signer = account.address.serialize_to_vec();
 
     let valid = unsafe {
         namada_vp_verify_tx_section_signature(
@@ -103,7 +117,7 @@ pub fn verify_signatures(ctx: &Ctx, tx: &Tx, owner: &Address) -> VpResult {
             public_keys_map.len() as _,
             signer.as_ptr() as _,
             signer.len() as _,
-            threshold,
+            account.threshold,
             max_signatures.as_ptr() as _,
             max_signatures.len() as _,
         )
@@ -154,7 +168,10 @@ macro_rules! debug_log {
 }
 
 #[derive(Debug)]
-pub struct Ctx(());
+pub struct Ctx {
+    /// The storage keys changed by the transaction, provided by the VP input
+    keys_changed: BTreeSet<storage::Key>,
+}
 
 impl Ctx {
     /// Create a host context. The context on WASM side is only provided by
@@ -176,7 +193,25 @@ impl Ctx {
     /// methods implemented on the context.
     #[allow(clippy::new_without_default)]
     pub const unsafe fn new() -> Self {
-        Self(())
+        Self {
+            keys_changed: BTreeSet::new(),
+        }
+    }
+
+    /// Set the storage keys changed by the transaction that is being
+    /// validated.
+    pub fn with_keys_changed(
+        mut self,
+        keys_changed: BTreeSet<storage::Key>,
+    ) -> Self {
+        self.keys_changed = keys_changed;
+        self
+    }
+
+    /// Get the storage keys changed by the transaction that is being
+    /// validated, as given to the VP by `#[validity_predicate]`.
+    pub fn keys_changed(&self) -> &BTreeSet<storage::Key> {
+        &self.keys_changed
     }
 
     /// Read access to the prior storage (state before tx execution)
@@ -351,10 +386,34 @@ impl<'view> VpEnv<'view> for Ctx {
         })
     }
 
+    fn get_tx_code_hashes(&self) -> Result<Vec<Hash>, Error> {
+        let read_result = unsafe { namada_vp_get_tx_code_hashes() };
+        Ok(read_from_buffer(read_result, namada_vp_result_buffer)
+            .map(|value| {
+                Vec::<Hash>::try_from_slice(&value[..])
+                    .expect("The conversion shouldn't fail")
+            })
+            .unwrap_or_default())
+    }
+
+    fn get_wrapper_fee(&self) -> Result<Option<transaction::Fee>, Error> {
+        let read_result = unsafe { namada_vp_get_wrapper_fee() };
+        Ok(read_from_buffer(read_result, namada_vp_result_buffer).map(
+            |value| {
+                transaction::Fee::try_from_slice(&value[..])
+                    .expect("The conversion shouldn't fail")
+            },
+        ))
+    }
+
     fn charge_gas(&self, used_gas: u64) -> Result<(), Error> {
         unsafe { namada_vp_charge_gas(used_gas) };
         Ok(())
     }
+
+    fn get_keys_changed(&self) -> &BTreeSet<storage::Key> {
+        self.keys_changed()
+    }
 }
 
 impl StorageRead for CtxPreStorageRead<'_> {
@@ -5,7 +5,7 @@ fn validate_tx(
     ctx: &Ctx,
     tx_data: Tx,
     addr: Address,
-    keys_changed: BTreeSet<storage::Key>,
+    keys_changed: &BTreeSet<storage::Key>,
     verifiers: BTreeSet<Address>,
 ) -> VpResult {
     log_string(format!(
@@ -6,14 +6,31 @@ use eth_bridge_pool::{GasFee, PendingTransfer, TransferToEthereum};
 use namada_tx_prelude::borsh_ext::BorshSerializeExt;
 use namada_tx_prelude::*;
 
+/// The benchmarked gas cost of adding a single transfer to the pool.
+const GAS_PER_TRANSFER: u64 = 1038546;
+
 #[transaction(gas = 1038546)]
 fn apply_tx(ctx: &mut Ctx, signed: Tx) -> TxResult {
     let data = signed.data().ok_or_err_msg("Missing data").map_err(|err| {
         ctx.set_commitment_sentinel();
         err
     })?;
-    let transfer = PendingTransfer::try_from_slice(&data[..])
+    let transfers = eth_bridge_pool::decode_pending_transfers(&data[..])
         .map_err(|e| Error::wrap("Error deserializing PendingTransfer", e))?;
+    // NB: the cost of the first transfer is charged up front, whereas
+    // the other transfers of a batch (bounded by
+    // `MAX_PENDING_TRANSFERS_PER_TX`) are charged for one by one
+    for (i, transfer) in transfers.into_iter().enumerate() {
+        if i > 0 {
+            ctx.charge_gas(GAS_PER_TRANSFER)?;
+        }
+        add_to_pool(ctx, transfer)?;
+    }
+    Ok(())
+}
+
+/// Escrow the assets and gas fees of a transfer and add it to the pool.
+fn add_to_pool(ctx: &mut Ctx, transfer: PendingTransfer) -> TxResult {
     log_string("Received transfer to add to pool.");
     // pay the gas fees
     let GasFee {
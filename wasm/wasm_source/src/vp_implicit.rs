@@ -68,7 +68,7 @@ fn validate_tx(
     ctx: &Ctx,
     tx_data: Tx,
     addr: Address,
-    keys_changed: BTreeSet<storage::Key>,
+    keys_changed: &BTreeSet<storage::Key>,
     verifiers: BTreeSet<Address>,
 ) -> VpResult {
     debug_log!(
@@ -79,7 +79,13 @@ fn validate_tx(
     );
 
     let valid_sig = Lazy::new(|| {
-        matches!(verify_signatures(ctx, &tx_data, &addr), Ok(true))
+        match storage_api::account::get_account(&ctx.pre(), &addr) {
+            Ok(Some(account)) => matches!(
+                verify_account_signatures(ctx, &tx_data, &account),
+                Ok(true)
+            ),
+            _ => false,
+        }
     });
 
     if !is_valid_tx(ctx, &tx_data)? {
@@ -319,7 +325,7 @@ mod tests {
         vp_host_env::init();
 
         assert!(
-            validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap()
+            validate_tx(&CTX, tx_data, addr, &keys_changed, verifiers).unwrap()
         );
     }
 
@@ -351,7 +357,7 @@ mod tests {
         vp_host_env::set(vp_env);
 
         assert!(
-            validate_tx(&CTX, tx_data, addr.clone(), keys_changed, verifiers)
+            validate_tx(&CTX, tx_data, addr.clone(), &keys_changed, verifiers)
                 .unwrap(),
             "Revealing PK that's not yet revealed and is matching the address \
              must be accepted"
@@ -378,7 +384,8 @@ mod tests {
         vp_host_env::set(vp_env);
 
         assert!(
-            !validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap(),
+            !validate_tx(&CTX, tx_data, addr, &keys_changed, verifiers)
+                .unwrap(),
             "Revealing PK that's already revealed should be rejected"
         );
     }
@@ -419,7 +426,8 @@ mod tests {
         vp_host_env::set(vp_env);
 
         assert!(
-            !validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap(),
+            !validate_tx(&CTX, tx_data, addr, &keys_changed, verifiers)
+                .unwrap(),
             "Mismatching PK must be rejected"
         );
     }
@@ -475,7 +483,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -557,7 +565,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -652,7 +660,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -711,7 +719,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -783,7 +791,7 @@ mod tests {
         vp_host_env::set(vp_env);
 
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -844,7 +852,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -899,7 +907,7 @@ mod tests {
                 vp_env.all_touched_storage_keys();
             let verifiers: BTreeSet<Address> = BTreeSet::default();
             vp_host_env::set(vp_env);
-            assert!(!validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers).unwrap());
+            assert!(!validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers).unwrap());
         }
 
     fn test_signed_arb_storage_write(
@@ -945,7 +953,7 @@ mod tests {
             vp_env.all_touched_storage_keys();
             let verifiers: BTreeSet<Address> = BTreeSet::default();
             vp_host_env::set(vp_env);
-            assert!(validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap());
+            assert!(validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers).unwrap());
         }
     }
 
@@ -983,7 +991,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1039,7 +1047,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1094,7 +1102,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -14,7 +14,7 @@ fn validate_tx(
     ctx: &Ctx,
     tx_data: Tx,
     addr: Address,
-    keys_changed: BTreeSet<storage::Key>,
+    keys_changed: &BTreeSet<storage::Key>,
     verifiers: BTreeSet<Address>,
 ) -> VpResult {
     debug_log!(
@@ -141,7 +141,7 @@ mod tests {
         vp_host_env::init();
 
         assert!(
-            validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap()
+            validate_tx(&CTX, tx_data, addr, &keys_changed, verifiers).unwrap()
         );
     }
 
@@ -189,7 +189,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -226,7 +226,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -275,14 +275,10 @@ mod tests {
             vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
-        assert!(validate_tx(
-            &CTX,
-            signed_tx,
-            vp_owner,
-            keys_changed,
-            verifiers
-        )
-        .unwrap());
+        assert!(
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
+                .unwrap()
+        );
     }
 
     prop_compose! {
@@ -343,7 +339,7 @@ mod tests {
         vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
-        assert!(!validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers).unwrap());
+        assert!(!validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers).unwrap());
     }
 
     /// Test that a debit of less than or equal to [`MAX_FREE_DEBIT`] tokens
@@ -410,7 +406,7 @@ mod tests {
         vp_env.all_touched_storage_keys();
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
-        assert!(validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers).unwrap());
+        assert!(validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers).unwrap());
     }
 
         /// Test that a signed tx that performs arbitrary storage writes or
@@ -466,7 +462,7 @@ mod tests {
             vp_env.all_touched_storage_keys();
             let verifiers: BTreeSet<Address> = BTreeSet::default();
             vp_host_env::set(vp_env);
-            assert!(validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap());
+            assert!(validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers).unwrap());
         }
     }
 }
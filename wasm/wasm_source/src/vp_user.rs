@@ -79,7 +79,7 @@ fn validate_tx(
     ctx: &Ctx,
     tx_data: Tx,
     addr: Address,
-    keys_changed: BTreeSet<storage::Key>,
+    keys_changed: &BTreeSet<storage::Key>,
     verifiers: BTreeSet<Address>,
 ) -> VpResult {
     debug_log!(
@@ -90,7 +90,13 @@ fn validate_tx(
     );
 
     let valid_sig = Lazy::new(|| {
-        matches!(verify_signatures(ctx, &tx_data, &addr), Ok(true))
+        match storage_api::account::get_account(&ctx.pre(), &addr) {
+            Ok(Some(account)) => matches!(
+                verify_account_signatures(ctx, &tx_data, &account),
+                Ok(true)
+            ),
+            _ => false,
+        }
     });
 
     if !is_valid_tx(ctx, &tx_data)? {
@@ -373,7 +379,7 @@ mod tests {
         vp_host_env::init();
 
         assert!(
-            validate_tx(&CTX, tx_data, addr, keys_changed, verifiers).unwrap()
+            validate_tx(&CTX, tx_data, addr, &keys_changed, verifiers).unwrap()
         );
     }
 
@@ -427,7 +433,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -482,7 +488,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -551,7 +557,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -631,7 +637,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -715,7 +721,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -805,7 +811,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, validator, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, validator, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -900,7 +906,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -994,7 +1000,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1099,7 +1105,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, validator, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, validator, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1150,7 +1156,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1205,7 +1211,7 @@ mod tests {
                 vp_env.all_touched_storage_keys();
             let verifiers: BTreeSet<Address> = BTreeSet::default();
             vp_host_env::set(vp_env);
-            assert!(!validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers).unwrap());
+            assert!(!validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers).unwrap());
         }
     }
 
@@ -1257,7 +1263,7 @@ mod tests {
                 vp_env.all_touched_storage_keys();
                 let verifiers: BTreeSet<Address> = BTreeSet::default();
                 vp_host_env::set(vp_env);
-                assert!(validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers).unwrap());
+                assert!(validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers).unwrap());
             }
         }
 
@@ -1294,7 +1300,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, tx_data, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, tx_data, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1345,7 +1351,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1400,7 +1406,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1456,7 +1462,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1512,7 +1518,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            !validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            !validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -1568,7 +1574,7 @@ mod tests {
         let verifiers: BTreeSet<Address> = BTreeSet::default();
         vp_host_env::set(vp_env);
         assert!(
-            validate_tx(&CTX, signed_tx, vp_owner, keys_changed, verifiers)
+            validate_tx(&CTX, signed_tx, vp_owner, &keys_changed, verifiers)
                 .unwrap()
         );
     }
@@ -149,7 +149,7 @@ pub mod main {
         _ctx: &Ctx,
         _tx_data: Tx,
         _addr: Address,
-        _keys_changed: BTreeSet<storage::Key>,
+        _keys_changed: &BTreeSet<storage::Key>,
         _verifiers: BTreeSet<Address>,
     ) -> VpResult {
         accept()
@@ -166,7 +166,7 @@ pub mod main {
         _ctx: &Ctx,
         _tx_data: Tx,
         _addr: Address,
-        _keys_changed: BTreeSet<storage::Key>,
+        _keys_changed: &BTreeSet<storage::Key>,
         _verifiers: BTreeSet<Address>,
     ) -> VpResult {
         reject()
@@ -184,7 +184,7 @@ pub mod main {
         ctx: &Ctx,
         tx_data: Tx,
         _addr: Address,
-        _keys_changed: BTreeSet<storage::Key>,
+        _keys_changed: &BTreeSet<storage::Key>,
         _verifiers: BTreeSet<Address>,
     ) -> VpResult {
         use validity_predicate::EvalVp;
@@ -209,7 +209,7 @@ pub mod main {
         _ctx: &Ctx,
         tx_data: Tx,
         _addr: Address,
-        _keys_changed: BTreeSet<storage::Key>,
+        _keys_changed: &BTreeSet<storage::Key>,
         _verifiers: BTreeSet<Address>,
     ) -> VpResult {
         let len = usize::try_from_slice(&tx_data.data().as_ref().unwrap()[..])
@@ -233,7 +233,7 @@ pub mod main {
         ctx: &Ctx,
         tx_data: Tx,
         _addr: Address,
-        _keys_changed: BTreeSet<storage::Key>,
+        _keys_changed: &BTreeSet<storage::Key>,
         _verifiers: BTreeSet<Address>,
     ) -> VpResult {
         // Allocates a memory of size given from the `tx_data (usize)`